@@ -0,0 +1,396 @@
+use std::{path::Path, sync::OnceLock};
+
+use chrono::{DateTime, Local};
+use log::debug;
+use reqwest::Url;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    ILIAS_URL, IliasElement, client::IliasClient, file::File, id::RefId,
+    learning_progress::LearningProgress, parse_date, querypath_from_href, table::Table,
+    type_from_icon,
+};
+
+/// The marks of all courses and objects from the "Achievements" section
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GradeOverview {
+    pub entries: Vec<GradeEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeEntry {
+    pub title: String,
+    pub type_identifier: Option<String>,
+    pub querypath: Option<String>,
+    pub mark: Option<String>,
+    pub status: Option<LearningProgress>,
+    pub comment: Option<String>,
+    pub date: Option<DateTime<Local>>,
+}
+
+/// The certificates of completed courses and other objects from the "Achievements" section
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CertificateOverview {
+    pub certificates: Vec<Certificate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Certificate {
+    /// The title of the object the certificate was issued for
+    pub title: String,
+    pub description: String,
+    pub issued: Option<DateTime<Local>>,
+    /// The object the certificate was issued for, e.g. a course
+    pub object_querypath: Option<String>,
+    pub download_querypath: String,
+}
+
+/// The badges awarded to the user, from the "Achievements" section
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BadgeOverview {
+    pub badges: Vec<Badge>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Badge {
+    pub name: String,
+    pub description: String,
+    pub image_src: Option<String>,
+    /// The title of the object (e.g. course) that awarded the badge
+    pub issuer: Option<String>,
+    pub issuer_querypath: Option<String>,
+    pub awarded: Option<DateTime<Local>>,
+}
+
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ICON_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PROPERTY_NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PROPERTY_VALUE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CERTIFICATE_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CARD_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CARD_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CARD_IMAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CARD_TEXT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+const TITLE_COLUMNS: [&str; 2] = ["Titel", "Title"];
+
+impl IliasElement for GradeOverview {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+        let icon_selector = ICON_SELECTOR
+            .get_or_init(|| Selector::parse("img[src]").expect("Could not parse selector"));
+
+        let mut entries = vec![];
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            if table.column(&TITLE_COLUMNS).is_none() || table.column(&["Note", "Mark"]).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(title_cell) = table.cell(row, &TITLE_COLUMNS) else {
+                    continue;
+                };
+                let non_empty = |names: &[&str]| {
+                    table
+                        .cell_text(row, names)
+                        .filter(|text| !text.is_empty() && text != "-")
+                };
+
+                entries.push(GradeEntry {
+                    title: title_cell.text().collect::<String>().trim().to_string(),
+                    type_identifier: row
+                        .iter()
+                        .flat_map(|cell| cell.select(icon_selector))
+                        .find_map(|icon| type_from_icon(icon.attr("src")?)),
+                    querypath: title_cell
+                        .select(link_selector)
+                        .next()
+                        .and_then(|link| link.attr("href"))
+                        .map(querypath_from_href),
+                    mark: non_empty(&["Note", "Mark"]),
+                    status: table
+                        .cell(row, &["Status"])
+                        .and_then(LearningProgress::from_element),
+                    comment: non_empty(&["Kommentar", "Comment"]),
+                    date: non_empty(&["Letzte Änderung", "Last Change", "Datum", "Date"])
+                        .and_then(|date| parse_date(&date).ok()),
+                });
+            }
+        }
+        debug!("Grades: {:?}", entries);
+
+        Ok(GradeOverview { entries })
+    }
+}
+
+impl GradeOverview {
+    pub const QUERYPATH: &str = "ilias.php?baseClass=ilDashboardGUI&cmd=jumpToLP";
+
+    pub fn fetch(ilias_client: &IliasClient) -> Result<GradeOverview, Whatever> {
+        let page = ilias_client
+            .get_querypath(Self::QUERYPATH)
+            .whatever_context("Could not get achievements page")?;
+        GradeOverview::parse(page.root_element(), ilias_client)
+    }
+
+    /// Entries that actually have a mark
+    pub fn graded(&self) -> impl Iterator<Item = &GradeEntry> {
+        self.entries.iter().filter(|entry| entry.mark.is_some())
+    }
+}
+
+impl IliasElement for CertificateOverview {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let item_selector = ITEM_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer .il-item").expect("Could not parse selector")
+        });
+        let item_title_selector = ITEM_TITLE_SELECTOR
+            .get_or_init(|| Selector::parse(".il-item-title").expect("Could not parse selector"));
+        let item_description_selector = ITEM_DESCRIPTION_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-description").expect("Could not parse selector")
+        });
+        let property_name_selector = PROPERTY_NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-name").expect("Could not parse selector")
+        });
+        let property_value_selector = PROPERTY_VALUE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-value").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+        let certificate_link_selector = certificate_link_selector();
+
+        let mut certificates = vec![];
+        for item in element.select(item_selector) {
+            let Some(download_querypath) = item
+                .select(certificate_link_selector)
+                .next()
+                .and_then(|link| link.attr("href"))
+                .map(querypath_from_href)
+            else {
+                continue;
+            };
+            let Some(title) = item.select(item_title_selector).next() else {
+                continue;
+            };
+
+            let issued = item
+                .select(property_name_selector)
+                .zip(item.select(property_value_selector))
+                .find(|(name, _)| {
+                    let name = name.text().collect::<String>();
+                    ["Ausgestellt", "Issued", "Datum", "Date"]
+                        .iter()
+                        .any(|prefix| name.trim().starts_with(prefix))
+                })
+                .and_then(|(_, value)| parse_date(value.text().collect::<String>().trim()).ok());
+
+            certificates.push(Certificate {
+                title: title.text().collect::<String>().trim().to_string(),
+                description: item
+                    .select(item_description_selector)
+                    .next()
+                    .map(|description| description.text().collect::<String>().trim().to_string())
+                    .unwrap_or_default(),
+                issued,
+                object_querypath: title
+                    .select(link_selector)
+                    .next()
+                    .and_then(|link| link.attr("href"))
+                    .map(querypath_from_href),
+                download_querypath,
+            });
+        }
+        debug!("Certificates: {:?}", certificates);
+
+        Ok(CertificateOverview { certificates })
+    }
+}
+
+impl CertificateOverview {
+    pub const QUERYPATH: &str = "ilias.php?baseClass=ilDashboardGUI&cmd=jumpToCertificates";
+
+    pub fn fetch(ilias_client: &IliasClient) -> Result<CertificateOverview, Whatever> {
+        let page = ilias_client
+            .get_querypath(Self::QUERYPATH)
+            .whatever_context("Could not get certificates page")?;
+        CertificateOverview::parse(page.root_element(), ilias_client)
+    }
+}
+
+impl Certificate {
+    /// Looks for the certificate on the page of an object, e.g. the one a course offers once
+    /// it is passed
+    pub fn find_on_object(
+        ilias_client: &IliasClient,
+        title: &str,
+        object_querypath: &str,
+    ) -> Result<Option<Certificate>, Whatever> {
+        let page = ilias_client
+            .get_querypath(object_querypath)
+            .whatever_context(format!("Could not get page of {title}"))?;
+
+        Ok(page
+            .select(certificate_link_selector())
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(|href| Certificate {
+                title: title.to_string(),
+                description: String::new(),
+                issued: None,
+                object_querypath: Some(object_querypath.to_string()),
+                download_querypath: querypath_from_href(href),
+            }))
+    }
+
+    /// The certificate as PDF file named after the object
+    pub fn file(&self) -> File {
+        File {
+            name: format!("{}.pdf", self.title),
+            description: self.description.clone(),
+            date: self.issued,
+            download_querypath: Some(self.download_querypath.clone()),
+            id: None,
+            delivered_id: None,
+            license: None,
+            size: None,
+            uploader: None,
+        }
+    }
+}
+
+impl IliasElement for BadgeOverview {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let card_selector = CARD_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer .il-card").expect("Could not parse selector")
+        });
+        let card_title_selector = CARD_TITLE_SELECTOR
+            .get_or_init(|| Selector::parse(".card-title").expect("Could not parse selector"));
+        let card_image_selector = CARD_IMAGE_SELECTOR
+            .get_or_init(|| Selector::parse("img[src]").expect("Could not parse selector"));
+        let card_text_selector = CARD_TEXT_SELECTOR.get_or_init(|| {
+            Selector::parse(".card-body > .il-item-description, .card-text, .card-body > p")
+                .expect("Could not parse selector")
+        });
+        let property_name_selector = PROPERTY_NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-name").expect("Could not parse selector")
+        });
+        let property_value_selector = PROPERTY_VALUE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-value").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        let mut badges = vec![];
+        for card in element.select(card_selector) {
+            let Some(name) = card.select(card_title_selector).next() else {
+                continue;
+            };
+            let properties = card
+                .select(property_name_selector)
+                .map(|name| name.text().collect::<String>().trim().to_string())
+                .zip(card.select(property_value_selector))
+                .collect::<Vec<_>>();
+            let property = |prefixes: &[&str]| {
+                properties
+                    .iter()
+                    .find(|(name, _)| prefixes.iter().any(|prefix| name.starts_with(prefix)))
+                    .map(|(_, value)| *value)
+            };
+            let issuer = property(&["Vergeben durch", "Awarded by", "Objekt", "Object"]);
+
+            badges.push(Badge {
+                name: name.text().collect::<String>().trim().to_string(),
+                description: card
+                    .select(card_text_selector)
+                    .next()
+                    .map(|description| description.text().collect::<String>().trim().to_string())
+                    .unwrap_or_default(),
+                image_src: card
+                    .select(card_image_selector)
+                    .next()
+                    .and_then(|image| image.attr("src"))
+                    .map(str::to_string),
+                issuer: issuer.map(|issuer| issuer.text().collect::<String>().trim().to_string()),
+                issuer_querypath: issuer
+                    .and_then(|issuer| issuer.select(link_selector).next())
+                    .and_then(|link| link.attr("href"))
+                    .map(querypath_from_href),
+                awarded: property(&["Erhalten am", "Received on", "Vergeben am", "Awarded on"])
+                    .and_then(|date| parse_date(date.text().collect::<String>().trim()).ok()),
+            });
+        }
+        debug!("Badges: {:?}", badges);
+
+        Ok(BadgeOverview { badges })
+    }
+}
+
+impl BadgeOverview {
+    pub const QUERYPATH: &str = "ilias.php?baseClass=ilDashboardGUI&cmd=jumpToBadges";
+
+    pub fn fetch(ilias_client: &IliasClient) -> Result<BadgeOverview, Whatever> {
+        let page = ilias_client
+            .get_querypath(Self::QUERYPATH)
+            .whatever_context("Could not get badges page")?;
+        BadgeOverview::parse(page.root_element(), ilias_client)
+    }
+}
+
+impl Badge {
+    /// Downloads the badge image, e.g. to show it on a personal page
+    pub fn download_image(&self, ilias_client: &IliasClient, to: &Path) -> Result<(), Whatever> {
+        let image_src = self
+            .image_src
+            .as_ref()
+            .whatever_context(format!("Badge {} has no image", self.name))?;
+        let url = Url::parse(ILIAS_URL)
+            .whatever_context("Could not parse ilias url")?
+            .join(image_src)
+            .whatever_context("Could not resolve badge image")?;
+        ilias_client.download_url(url, to)
+    }
+}
+
+fn certificate_link_selector() -> &'static Selector {
+    CERTIFICATE_LINK_SELECTOR.get_or_init(|| {
+        Selector::parse(
+            r#"a[href*="deliverCertificate"], a[href*="ilusercertificategui"][href*="cmd=download"], a[href*="ilUserCertificateGUI"][href*="cmd=download"]"#,
+        )
+        .expect("Could not parse selector")
+    })
+}
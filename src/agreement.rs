@@ -0,0 +1,131 @@
+use std::sync::OnceLock;
+
+use log::info;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use super::{IliasElement, client::IliasClient, form::HtmlForm, id::RefId};
+
+/// Terms an object requires to be accepted before it can be accessed, e.g. the
+/// "Einverständniserklärung" of a course or the usage terms of a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Agreement {
+    pub title: String,
+    pub text: String,
+    checkbox_names: Vec<String>,
+    form: HtmlForm,
+}
+
+static FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CHECKBOX_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TEXT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+const AGREEMENT_NAMES: [&str; 4] = ["agreement", "accept", "agree", "copyright"];
+
+impl IliasElement for Agreement {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    /// Finds the agreement form on the page that is shown instead of the object
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let form_selector = FORM_SELECTOR
+            .get_or_init(|| Selector::parse("form[action]").expect("Could not parse selector"));
+        let checkbox_selector = CHECKBOX_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"input[type="checkbox"][name]"#).expect("Could not parse selector")
+        });
+        let title_selector = TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilHeader, .il-page-content-header, h1, h3")
+                .expect("Could not parse selector")
+        });
+        let text_selector = TEXT_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilFormValue, .ilc_Paragraph, .il-agreement, textarea[readonly]")
+                .expect("Could not parse selector")
+        });
+
+        for form in element.select(form_selector) {
+            let action = form.attr("action").unwrap_or_default().to_lowercase();
+            let checkbox_names = form
+                .select(checkbox_selector)
+                .filter_map(|checkbox| checkbox.attr("name"))
+                .filter(|name| is_agreement_name(name) || action.contains("agreement"))
+                .map(ToOwned::to_owned)
+                .collect::<Vec<_>>();
+            if checkbox_names.is_empty() {
+                continue;
+            }
+
+            let text = form
+                .select(text_selector)
+                .map(|text| text.text().collect::<String>().trim().to_string())
+                .filter(|text| !text.is_empty())
+                .collect::<Vec<_>>()
+                .join("\n\n");
+            return Ok(Agreement {
+                title: element
+                    .select(title_selector)
+                    .next()
+                    .map(|title| title.text().collect::<String>().trim().to_string())
+                    .unwrap_or_default(),
+                text,
+                checkbox_names,
+                form: HtmlForm::parse(form)?,
+            });
+        }
+
+        whatever!("Page has no agreement form")
+    }
+}
+
+impl Agreement {
+    /// The agreement a page asks for instead of showing the requested content, if any
+    pub fn detect(page: &Html, ilias_client: &IliasClient) -> Option<Agreement> {
+        Agreement::parse(page.root_element(), ilias_client).ok()
+    }
+
+    /// Fetches the page behind `querypath` and returns the agreement it asks for, if any
+    pub fn for_querypath(
+        ilias_client: &IliasClient,
+        querypath: &str,
+    ) -> Result<Option<Agreement>, Whatever> {
+        let page = ilias_client
+            .get_querypath(querypath)
+            .whatever_context("Could not get page to check for an agreement")?;
+        Ok(Agreement::detect(&page, ilias_client))
+    }
+
+    /// Accepts the terms by ticking all of their checkboxes, only call this if the user agreed
+    pub fn accept(&self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        let mut form = self.form.clone();
+        for name in &self.checkbox_names {
+            form.set(name, "1");
+        }
+        let button = form
+            .buttons
+            .first()
+            .map(|(name, _)| name.clone())
+            .whatever_context("Agreement form has no button")?;
+        let response = form
+            .submit(ilias_client, Some(&button))
+            .whatever_context("Could not submit agreement")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected accepting {}", self.title);
+        }
+
+        info!("Accepted agreement {}", self.title);
+        Ok(())
+    }
+}
+
+fn is_agreement_name(name: &str) -> bool {
+    let name = name.to_lowercase();
+    AGREEMENT_NAMES
+        .iter()
+        .any(|agreement| name.contains(agreement))
+}
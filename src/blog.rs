@@ -0,0 +1,150 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+use log::debug;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement, client::IliasClient, id::RefId, page::Page, parse_date, querypath_from_href,
+    reference::Reference,
+};
+
+/// An ILIAS blog (`blog`), also used as submission type of exercises
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Blog {
+    pub name: String,
+    pub description: String,
+    pub postings: Vec<BlogPosting>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BlogPosting {
+    pub title: String,
+    pub author: Option<String>,
+    pub date: Option<DateTime<Local>>,
+    /// The teaser shown in the list of postings
+    pub snippet: String,
+    content: Reference<Page>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static POSTING_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static POSTING_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static POSTING_SUBTITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static POSTING_SNIPPET_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for Blog {
+    fn type_identifier() -> Option<&'static str> {
+        Some("blog")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let posting_selector = POSTING_SELECTOR
+            .get_or_init(|| Selector::parse(".ilBlogListItem").expect("Could not parse selector"));
+        let posting_title_selector = POSTING_TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilBlogListItemTitle").expect("Could not parse selector")
+        });
+        let posting_subtitle_selector = POSTING_SUBTITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilBlogListItemSubTitle").expect("Could not parse selector")
+        });
+        let posting_snippet_selector = POSTING_SNIPPET_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilBlogListItemSnippet").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+
+        let mut postings = vec![];
+        for posting in element.select(posting_selector) {
+            let title_element = posting
+                .select(posting_title_selector)
+                .next()
+                .whatever_context("Blog posting without title")?;
+            // The subtitle holds date and author, e.g. "13. Nov 2024, 10:00 | Jane Doe"
+            let subtitle_parts = posting
+                .select(posting_subtitle_selector)
+                .next()
+                .map(|subtitle| {
+                    subtitle
+                        .text()
+                        .collect::<String>()
+                        .split('|')
+                        .map(|part| part.trim().to_string())
+                        .filter(|part| !part.is_empty())
+                        .collect::<Vec<_>>()
+                })
+                .unwrap_or_default();
+            let date = subtitle_parts.iter().find_map(|part| parse_date(part).ok());
+            let author = subtitle_parts
+                .iter()
+                .find(|part| parse_date(part).is_err())
+                .cloned();
+
+            postings.push(BlogPosting {
+                title: title_element.text().collect::<String>().trim().to_string(),
+                author,
+                date,
+                snippet: posting
+                    .select(posting_snippet_selector)
+                    .next()
+                    .map(|snippet| snippet.text().collect::<String>().trim().to_string())
+                    .unwrap_or_default(),
+                content: Reference::from_optional_querypath(
+                    title_element
+                        .select(link_selector)
+                        .next()
+                        .and_then(|link| link.attr("href"))
+                        .map(querypath_from_href),
+                ),
+            });
+        }
+
+        let blog = Blog {
+            name,
+            description,
+            postings,
+        };
+        debug!("Blog: {:?}", blog);
+
+        Ok(blog)
+    }
+}
+
+impl BlogPosting {
+    /// The full posting, its images are the media attached to the posting
+    pub fn get_content(&mut self, ilias_client: &IliasClient) -> Result<Option<&Page>, Whatever> {
+        self.content
+            .resolve_cached(ilias_client)
+            .whatever_context("Could not parse blog posting")
+    }
+}
@@ -0,0 +1,261 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+use log::{debug, info};
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use super::{
+    IliasElement, client::IliasClient, form::HtmlForm, id::RefId, parse_date, querypath_from_href,
+    table::Table,
+};
+
+/// An ILIAS booking pool (`book`), e.g. for tutorial or lab slots
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BookingPool {
+    pub name: String,
+    pub description: String,
+    pub objects: Vec<BookingObject>,
+    pub reservations: Vec<Reservation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BookingObject {
+    pub title: String,
+    pub description: String,
+    /// Number of free places, for objects without a schedule
+    pub available: Option<u32>,
+    book_querypath: Option<String>,
+}
+
+/// A bookable time slot of an object with a schedule
+#[derive(Debug, Clone)]
+pub struct BookingSlot {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
+    pub free: Option<u32>,
+    value: String,
+}
+
+/// A reservation of the user that can be cancelled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Reservation {
+    pub title: String,
+    pub date: Option<DateTime<Local>>,
+    cancel_querypath: Option<String>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static SLOT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static SLOT_VALUE_REGEX: OnceLock<Regex> = OnceLock::new();
+static SLOT_FREE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+const TITLE_COLUMNS: [&str; 4] = ["Titel", "Title", "Objekt", "Object"];
+
+impl IliasElement for BookingPool {
+    fn type_identifier() -> Option<&'static str> {
+        Some("book")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+
+        let mut objects = vec![];
+        let mut reservations = vec![];
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            if table.column(&TITLE_COLUMNS).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(title) = table.cell_text(row, &TITLE_COLUMNS) else {
+                    continue;
+                };
+                let action_querypath = |command: &str| {
+                    row.iter()
+                        .flat_map(|cell| cell.select(link_selector))
+                        .filter_map(|link| link.attr("href"))
+                        .find(|href| href.contains(command))
+                        .map(querypath_from_href)
+                };
+
+                if let Some(cancel_querypath) = action_querypath("rsvConfirmCancel") {
+                    reservations.push(Reservation {
+                        title,
+                        date: table
+                            .cell_text(row, &["Zeitraum", "Period", "Datum", "Date"])
+                            .and_then(|period| {
+                                let start = period.split(" - ").next()?;
+                                parse_date(start.trim()).ok()
+                            }),
+                        cancel_querypath: Some(cancel_querypath),
+                    });
+                } else {
+                    objects.push(BookingObject {
+                        title,
+                        description: table
+                            .cell_text(row, &["Beschreibung", "Description"])
+                            .unwrap_or_default(),
+                        available: table
+                            .cell_text(row, &["Verfügbar", "Available"])
+                            .and_then(|available| available.trim().parse().ok()),
+                        book_querypath: action_querypath("cmd=book"),
+                    });
+                }
+            }
+        }
+
+        let booking_pool = BookingPool {
+            name,
+            description,
+            objects,
+            reservations,
+        };
+        debug!("Booking pool: {:?}", booking_pool);
+
+        Ok(booking_pool)
+    }
+}
+
+impl BookingObject {
+    pub fn can_book(&self) -> bool {
+        self.book_querypath.is_some()
+    }
+
+    /// The time slots of the week shown on the booking page, empty for objects without schedule
+    pub fn get_slots(&self, ilias_client: &IliasClient) -> Result<Vec<BookingSlot>, Whatever> {
+        let slot_selector = SLOT_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"input[name="date[]"]"#).expect("Could not parse selector")
+        });
+        let slot_value_regex = SLOT_VALUE_REGEX.get_or_init(|| {
+            Regex::new(r"^(?<start>\d+)_(?<end>\d+)$").expect("Could not parse regex")
+        });
+        let slot_free_regex = SLOT_FREE_REGEX
+            .get_or_init(|| Regex::new(r"\((?<free>\d+)\)").expect("Could not parse regex"));
+
+        let page = self.get_booking_page(ilias_client)?;
+        let slots = page
+            .select(slot_selector)
+            .filter_map(|input| {
+                let value = input.attr("value")?;
+                let captures = slot_value_regex.captures(value)?;
+                let timestamp = |name: &str| {
+                    DateTime::from_timestamp(captures[name].parse().ok()?, 0)
+                        .map(|date| date.with_timezone(&Local))
+                };
+                let label = input
+                    .parent()
+                    .and_then(ElementRef::wrap)
+                    .map(|parent| parent.text().collect::<String>())
+                    .unwrap_or_default();
+
+                Some(BookingSlot {
+                    start: timestamp("start")?,
+                    end: timestamp("end")?,
+                    free: slot_free_regex
+                        .captures(&label)
+                        .and_then(|captures| captures["free"].parse().ok()),
+                    value: value.to_string(),
+                })
+            })
+            .collect();
+
+        Ok(slots)
+    }
+
+    /// Books the object, in the given slot if it has a schedule
+    pub fn book(
+        &self,
+        ilias_client: &IliasClient,
+        slot: Option<&BookingSlot>,
+    ) -> Result<(), Whatever> {
+        let page = self.get_booking_page(ilias_client)?;
+        let mut form = HtmlForm::find(&page, |form| form.has_button("confirm"))
+            .whatever_context("Could not find booking form")?;
+        if let Some(slot) = slot {
+            form.set("date[]", &slot.value);
+        }
+
+        let response = form
+            .submit(ilias_client, Some("confirm"))
+            .whatever_context("Could not submit booking")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected booking {}", self.title);
+        }
+
+        info!("Booked {}", self.title);
+        Ok(())
+    }
+
+    fn get_booking_page(&self, ilias_client: &IliasClient) -> Result<Html, Whatever> {
+        ilias_client
+            .get_querypath(
+                self.book_querypath
+                    .as_ref()
+                    .whatever_context(format!("{} can not be booked", self.title))?,
+            )
+            .whatever_context("Could not get booking page")
+    }
+}
+
+impl Reservation {
+    pub fn cancel(&self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        let page = ilias_client
+            .get_querypath(
+                self.cancel_querypath
+                    .as_ref()
+                    .whatever_context(format!("Can not cancel {}", self.title))?,
+            )
+            .whatever_context("Could not get cancellation page")?;
+        let form = HtmlForm::find(&page, |form| form.has_button("rsvCancel"))
+            .whatever_context("Could not find cancellation form")?;
+
+        let response = form
+            .submit(ilias_client, Some("rsvCancel"))
+            .whatever_context("Could not submit cancellation")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected cancelling {}", self.title);
+        }
+
+        info!("Cancelled reservation of {}", self.title);
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::OnceLock;
+
+use log::{debug, info};
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use super::{
+    ILIAS_URL, IliasElement,
+    client::IliasClient,
+    form::HtmlForm,
+    id::{ObjId, RefId},
+    table::Table,
+};
+
+/// The personal bookmarks of the user, shown in the dashboard
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Bookmarks {
+    pub bookmarks: Vec<Bookmark>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub id: ObjId,
+    pub title: String,
+    pub description: String,
+    pub target: String,
+}
+
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CHECKBOX_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+const TITLE_COLUMNS: [&str; 2] = ["Titel", "Title"];
+
+impl IliasElement for Bookmarks {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let checkbox_selector = CHECKBOX_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"input[name="bm_id[]"]"#).expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        let mut bookmarks = vec![];
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            let Some(title_column) = table.column(&TITLE_COLUMNS) else {
+                continue;
+            };
+
+            for row in &table.rows {
+                // Rows of bookmark folders have ids like "bmf:12", bookmarks "bm:34"
+                let Some(id) = row
+                    .iter()
+                    .flat_map(|cell| cell.select(checkbox_selector))
+                    .filter_map(|checkbox| checkbox.attr("value"))
+                    .find_map(|value| value.strip_prefix("bm:")?.parse().ok())
+                else {
+                    continue;
+                };
+                let Some(title_cell) = row.get(title_column) else {
+                    continue;
+                };
+                let link = title_cell.select(link_selector).next();
+                let title = link
+                    .map(|link| link.text().collect::<String>().trim().to_string())
+                    .unwrap_or_default();
+
+                bookmarks.push(Bookmark {
+                    id,
+                    description: title_cell
+                        .text()
+                        .collect::<String>()
+                        .replacen(&title, "", 1)
+                        .trim()
+                        .to_string(),
+                    title,
+                    target: link
+                        .and_then(|link| link.attr("href"))
+                        .unwrap_or_default()
+                        .to_string(),
+                });
+            }
+        }
+        debug!("Bookmarks: {:?}", bookmarks);
+
+        Ok(Bookmarks { bookmarks })
+    }
+}
+
+impl Bookmarks {
+    pub const QUERYPATH: &str =
+        "ilias.php?baseClass=ilDashboardGUI&cmdClass=ilbookmarkadministrationgui&cmd=view";
+    const NEW_BOOKMARK_QUERYPATH: &str = "ilias.php?baseClass=ilDashboardGUI&cmdClass=ilbookmarkadministrationgui&cmd=newFormBookmark";
+
+    pub fn fetch(ilias_client: &IliasClient) -> Result<Bookmarks, Whatever> {
+        let page = ilias_client
+            .get_querypath(Self::QUERYPATH)
+            .whatever_context("Could not get bookmarks")?;
+        Bookmarks::parse(page.root_element(), ilias_client)
+    }
+
+    pub fn find(&self, target: &str) -> Option<&Bookmark> {
+        self.bookmarks
+            .iter()
+            .find(|bookmark| bookmark.target == target)
+    }
+
+    /// Adds a bookmark to an arbitrary url
+    pub fn add(
+        ilias_client: &IliasClient,
+        title: &str,
+        target: &str,
+        description: &str,
+    ) -> Result<(), Whatever> {
+        let page = ilias_client
+            .get_querypath(Self::NEW_BOOKMARK_QUERYPATH)
+            .whatever_context("Could not get bookmark form")?;
+        let mut form = HtmlForm::find(&page, |form| form.has_button("createBookmark"))
+            .whatever_context("Could not find bookmark form")?;
+        form.set("title", title);
+        form.set("target", target);
+        form.set("description", description);
+
+        let response = form
+            .submit(ilias_client, Some("createBookmark"))
+            .whatever_context("Could not create bookmark")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected bookmark {title}");
+        }
+
+        info!("Bookmarked {title}");
+        Ok(())
+    }
+
+    /// Pins an ILIAS object, e.g. `goto.php/exc/1234`
+    pub fn add_object(
+        ilias_client: &IliasClient,
+        title: &str,
+        querypath: &str,
+    ) -> Result<(), Whatever> {
+        Self::add(
+            ilias_client,
+            title,
+            &format!("{}/{}", ILIAS_URL.trim_end_matches('/'), querypath),
+            "",
+        )
+    }
+}
+
+impl Bookmark {
+    /// Needs [`IliasClient::set_allow_deletion`]
+    pub fn delete(&self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        ilias_client.ensure_deletion_allowed()?;
+        let page = ilias_client
+            .get_querypath(&format!(
+                "ilias.php?baseClass=ilDashboardGUI&cmdClass=ilbookmarkadministrationgui&cmd=delete&bm_id=bm:{}",
+                self.id
+            ))
+            .whatever_context("Could not get bookmark deletion page")?;
+        let form = HtmlForm::find(&page, |form| form.has_button("confirm"))
+            .whatever_context("Could not find bookmark deletion form")?;
+
+        let response = form
+            .submit(ilias_client, Some("confirm"))
+            .whatever_context("Could not delete bookmark")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected deleting bookmark {}", self.title);
+        }
+
+        info!("Deleted bookmark {}", self.title);
+        Ok(())
+    }
+}
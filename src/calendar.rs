@@ -0,0 +1,188 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local, NaiveTime};
+use log::debug;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{IliasElement, client::IliasClient, id::RefId, parse_date, querypath_from_href};
+
+/// The personal calendar of the user in its list (agenda) presentation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Calendar {
+    pub appointments: Vec<Appointment>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Appointment {
+    pub title: String,
+    pub description: String,
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    pub location: Option<String>,
+    /// Title of the course or object the appointment belongs to
+    pub object_title: Option<String>,
+    pub object_querypath: Option<String>,
+}
+
+static GROUP_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static GROUP_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PROPERTY_NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PROPERTY_VALUE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static OBJECT_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LIST_VIEW_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static TIME_RANGE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl IliasElement for Calendar {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let group_selector = GROUP_SELECTOR
+            .get_or_init(|| Selector::parse(".il-item-group").expect("Could not parse selector"));
+        let group_title_selector = GROUP_TITLE_SELECTOR
+            .get_or_init(|| Selector::parse("h3").expect("Could not parse selector"));
+        let item_selector = ITEM_SELECTOR
+            .get_or_init(|| Selector::parse(".il-item").expect("Could not parse selector"));
+        let item_title_selector = ITEM_TITLE_SELECTOR
+            .get_or_init(|| Selector::parse(".il-item-title").expect("Could not parse selector"));
+        let item_description_selector = ITEM_DESCRIPTION_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-description").expect("Could not parse selector")
+        });
+        let property_name_selector = PROPERTY_NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-name").expect("Could not parse selector")
+        });
+        let property_value_selector = PROPERTY_VALUE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-value").expect("Could not parse selector")
+        });
+        let object_link_selector = OBJECT_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(r#".il-item-property-value a[href]"#).expect("Could not parse selector")
+        });
+        let time_range_regex = TIME_RANGE_REGEX.get_or_init(|| {
+            Regex::new(r"(?<start>\d{1,2}:\d{2})(?:\s*-\s*(?<end>\d{1,2}:\d{2}))?")
+                .expect("Could not parse regex")
+        });
+
+        let mut appointments = vec![];
+        for group in element.select(group_selector) {
+            let day = group
+                .select(group_title_selector)
+                .next()
+                .whatever_context("Appointment group without day")?
+                .text()
+                .collect::<String>();
+            // Headers look like "Mittwoch, 13. Nov 2024" or just "Heute"
+            let day = day.split_once(',').map_or(day.as_str(), |(_, date)| date);
+            let day = parse_date(&format!("{}, 00:00", day.trim()))
+                .whatever_context(format!("Could not parse appointment day {day}"))?;
+
+            for item in group.select(item_selector) {
+                let title = item
+                    .select(item_title_selector)
+                    .next()
+                    .whatever_context("Appointment without title")?
+                    .text()
+                    .collect::<String>()
+                    .trim()
+                    .to_string();
+                let description = item
+                    .select(item_description_selector)
+                    .next()
+                    .map(|description| description.text().collect::<String>().trim().to_string())
+                    .unwrap_or_default();
+                let properties = item
+                    .select(property_name_selector)
+                    .map(|name| name.text().collect::<String>().trim().to_string())
+                    .zip(
+                        item.select(property_value_selector)
+                            .map(|value| value.text().collect::<String>().trim().to_string()),
+                    )
+                    .collect::<Vec<_>>();
+                let property = |names: &[&str]| {
+                    properties
+                        .iter()
+                        .find(|(name, _)| names.contains(&name.as_str()))
+                        .map(|(_, value)| value.clone())
+                };
+
+                let item_text = item.text().collect::<String>();
+                let time_range = time_range_regex.captures(&item_text);
+                let time_at = |name: &str| {
+                    time_range
+                        .as_ref()
+                        .and_then(|captures| captures.name(name))
+                        .and_then(|time| NaiveTime::parse_from_str(time.as_str(), "%H:%M").ok())
+                        .and_then(|time| day.with_time(time).earliest())
+                };
+                let start = time_at("start").unwrap_or(day);
+                let end = time_at("end");
+
+                let object_link = item.select(object_link_selector).next();
+
+                appointments.push(Appointment {
+                    title,
+                    description,
+                    start,
+                    end,
+                    location: property(&["Ort", "Location"]),
+                    object_title: object_link
+                        .map(|link| link.text().collect::<String>().trim().to_string()),
+                    object_querypath: object_link
+                        .and_then(|link| link.attr("href"))
+                        .map(querypath_from_href),
+                });
+            }
+        }
+        debug!("Appointments: {:?}", appointments);
+
+        Ok(Calendar { appointments })
+    }
+}
+
+impl Calendar {
+    pub const QUERYPATH: &str = "ilias.php?baseClass=ilDashboardGUI&cmd=jumpToCalendar";
+
+    /// Fetches the calendar, switching to the list presentation if necessary
+    pub fn fetch(ilias_client: &IliasClient) -> Result<Calendar, Whatever> {
+        let list_view_selector = LIST_VIEW_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#".il-viewcontrol-mode button[aria-label="Liste"], .il-viewcontrol-mode button[aria-label="List"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+
+        let page = ilias_client.get_querypath(Self::QUERYPATH)?;
+        let list_view_querypath = page
+            .select(list_view_selector)
+            .next()
+            .filter(|button| button.attr("aria-pressed") != Some("true"))
+            .and_then(|button| button.attr("data-action"))
+            .map(querypath_from_href);
+
+        match list_view_querypath {
+            Some(querypath) => {
+                let list_page = ilias_client.get_querypath(&querypath)?;
+                Calendar::parse(list_page.root_element(), ilias_client)
+            }
+            None => Calendar::parse(page.root_element(), ilias_client),
+        }
+    }
+
+    pub fn upcoming(&self) -> impl Iterator<Item = &Appointment> {
+        let now = Local::now();
+        self.appointments
+            .iter()
+            .filter(move |appointment| appointment.end.unwrap_or(appointment.start) >= now)
+    }
+}
@@ -0,0 +1,218 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasContainer, IliasElement,
+    client::IliasClient,
+    folder::FolderElement,
+    id::RefId,
+    locator::{Ancestor, parse_locator},
+    querypath_from_href,
+    reference::Reference,
+    type_from_icon,
+    unknown::UnknownElement,
+};
+
+/// An ILIAS category (`cat`), the repository root is a category as well
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Category {
+    pub name: String,
+    pub description: String,
+    pub id: RefId,
+    pub children: Vec<RepositoryItem>,
+    locator: Vec<Ancestor>,
+}
+
+/// An object listed in a category, of any type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryItem {
+    pub name: String,
+    pub description: String,
+    /// The ILIAS type of the object (e.g. `crs`), guessed from its icon
+    pub type_identifier: Option<String>,
+    pub querypath: String,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PERMALINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_ICON_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl IliasElement for Category {
+    fn type_identifier() -> Option<&'static str> {
+        Some("cat")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let permalink_selector = PERMALINK_SELECTOR.get_or_init(|| {
+            Selector::parse("#current_perma_link").expect("Could not parse selector")
+        });
+        let item_selector = ITEM_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilObjListRow, .il_ContainerListItem, .il-card")
+                .expect("Could not parse selector")
+        });
+        let item_title_selector = ITEM_TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il_ContainerItemTitle a[href], .card-title a[href], .caption a[href]")
+                .expect("Could not parse selector")
+        });
+        let item_description_selector = ITEM_DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".il_Description").expect("Could not parse selector"));
+        let item_icon_selector = ITEM_ICON_SELECTOR.get_or_init(|| {
+            Selector::parse("img.ilListItemIcon, img.icon").expect("Could not parse selector")
+        });
+        let id_regex = ID_REGEX.get_or_init(|| {
+            Regex::new(r"(ref_id=|cat/|cat_|root/|root_)(?<id>\d+)").expect("Could not parse regex")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+        let permalink = element
+            .select(permalink_selector)
+            .next()
+            .and_then(|permalink| permalink.attr("value"))
+            .whatever_context("Could not find permalink")?;
+        let id = id_regex
+            .captures(permalink)
+            .whatever_context(format!("Could not find id in {permalink}"))?["id"]
+            .parse()?;
+
+        // Nested rows (e.g. in item groups) are matched twice, so skip known querypaths
+        let mut children: Vec<RepositoryItem> = vec![];
+        for item in element.select(item_selector) {
+            let Some(title) = item.select(item_title_selector).next() else {
+                continue;
+            };
+            let querypath = querypath_from_href(title.attr("href").unwrap_or_default());
+            if children.iter().any(|child| child.querypath == querypath) {
+                continue;
+            }
+
+            children.push(RepositoryItem {
+                name: title.text().collect::<String>().trim().to_string(),
+                description: item
+                    .select(item_description_selector)
+                    .next()
+                    .map(|description| description.text().collect::<String>().trim().to_string())
+                    .unwrap_or_default(),
+                type_identifier: item
+                    .select(item_icon_selector)
+                    .next()
+                    .and_then(|icon| icon.attr("src"))
+                    .and_then(type_from_icon),
+                querypath,
+            });
+        }
+
+        let category = Category {
+            name,
+            description,
+            id,
+            children,
+            locator: parse_locator(element),
+        };
+        debug!("Category: {:?}", category);
+
+        Ok(category)
+    }
+}
+
+impl IliasContainer for Category {
+    /// The listed objects as [`FolderElement::Unknown`], resolve them to get their content
+    fn children(&self, _ilias_client: &IliasClient) -> Result<Vec<FolderElement>, Whatever> {
+        Ok(self
+            .children
+            .iter()
+            .map(RepositoryItem::to_folder_element)
+            .collect())
+    }
+}
+
+impl Category {
+    /// The top of the repository ("Magazin")
+    pub const ROOT_QUERYPATH: &str = "ilias.php?baseClass=ilrepositorygui&ref_id=1";
+
+    pub fn fetch_root(ilias_client: &IliasClient) -> Result<Category, Whatever> {
+        let page = ilias_client
+            .get_querypath(Self::ROOT_QUERYPATH)
+            .whatever_context("Could not get repository root")?;
+        Category::parse(page.root_element(), ilias_client)
+    }
+
+    /// The containers above the category, starting at the repository root
+    pub fn path(&self) -> &[Ancestor] {
+        &self.locator
+    }
+
+    pub fn children_of_type<'a>(
+        &'a self,
+        type_identifier: &'a str,
+    ) -> impl Iterator<Item = &'a RepositoryItem> {
+        self.children
+            .iter()
+            .filter(move |child| child.type_identifier.as_deref() == Some(type_identifier))
+    }
+
+    pub fn courses(&self) -> impl Iterator<Item = &RepositoryItem> {
+        self.children_of_type("crs")
+    }
+
+    pub fn categories(&self) -> impl Iterator<Item = &RepositoryItem> {
+        self.children_of_type("cat")
+    }
+}
+
+impl RepositoryItem {
+    pub fn to_folder_element(&self) -> FolderElement {
+        FolderElement::Unknown {
+            element: UnknownElement {
+                name: self.name.clone(),
+                description: self.description.clone(),
+                type_identifier: self.type_identifier.clone(),
+                id: RefId::from_querypath(&self.querypath),
+                querypath: self.querypath.clone(),
+            },
+            deletion_querypath: None,
+        }
+    }
+
+    /// Fetches and parses the object, e.g. as `Category` or `Course` depending on its type
+    pub fn resolve<T: IliasElement>(&self, ilias_client: &IliasClient) -> Result<T, Whatever> {
+        Reference::<T>::Unresolved(self.querypath.clone())
+            .resolve(ilias_client)
+            .whatever_context(format!("Could not parse {}", self.name))
+    }
+}
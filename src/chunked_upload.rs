@@ -0,0 +1,226 @@
+use std::{
+    env, fs,
+    io::{self, Read, Seek, SeekFrom},
+    path::PathBuf,
+    process,
+    sync::OnceLock,
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use log::{debug, warn};
+use regex::Regex;
+use reqwest::{
+    Response,
+    multipart::{Form, Part},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use snafu::{OptionExt, Report, ResultExt, Whatever};
+
+use super::{
+    client::{IliasClient, TransferProgress, write_private},
+    file::{File, content_hash},
+    hex,
+    local_file::NamedLocalFile,
+};
+
+static CHUNKING_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// How often a chunk is sent before the upload fails
+const CHUNK_ATTEMPTS: u64 = 3;
+
+/// The content of a file sent in chunks, to check what ILIAS assembled from them
+#[derive(Debug)]
+pub(crate) struct ChunkedUpload {
+    pub size: u64,
+    /// Hex encoded SHA-256 of the local file
+    pub sha256: String,
+}
+
+/// What a later run needs to continue an interrupted upload, kept in [`resume_path`]
+#[derive(Debug, Serialize, Deserialize)]
+struct ResumeState {
+    uuid: String,
+    size: u64,
+    sha256: String,
+    chunk_size: u64,
+    /// The first chunk ILIAS did not take yet
+    next_chunk: u64,
+}
+
+/// The chunk size if the file input in `script` takes files in chunks, read from the last
+/// arguments of its `il.UI.Input.File.init` call
+pub(crate) fn chunk_size(script: &str) -> Option<u64> {
+    let chunking_regex = CHUNKING_REGEX.get_or_init(|| {
+        Regex::new(
+            r"il\.UI\.Input\.File\.init\([^;]*,\s*'?(?<chunked>true|false|1|0)'?\s*,\s*(?<chunk_size>\d+)\s*\)",
+        )
+        .expect("Could not parse regex")
+    });
+    chunking_regex
+        .captures(script)
+        .filter(|captures| matches!(&captures["chunked"], "true" | "1"))
+        .and_then(|captures| captures["chunk_size"].parse::<u64>().ok())
+        .filter(|&chunk_size| chunk_size > 0)
+}
+
+/// Sends `file` in chunks like the Dropzone library of the file input does and returns the
+/// response to the last chunk. `form` wraps each chunk into the fields the upload endpoint
+/// expects.
+///
+/// A chunk that fails, e.g. on a flaky connection, is sent again without repeating the chunks
+/// ILIAS already took. If the upload still fails, the next upload of the same content continues
+/// after the last chunk ILIAS took, also from another run. Use [`ChunkedUpload::is_assembled_in`] to check
+/// what ILIAS assembled, as a resumed upload relies on ILIAS still having the earlier chunks.
+pub(crate) fn upload_chunked(
+    ilias_client: &IliasClient,
+    upload_querypath: &str,
+    file: &NamedLocalFile,
+    chunk_size: u64,
+    form: impl Fn(Part) -> Form,
+    mut progress_hook: impl FnMut(&TransferProgress),
+) -> Result<(Response, ChunkedUpload), Whatever> {
+    let (size, sha256) = content_hash(&file.path)?;
+    let chunk_count = size.div_ceil(chunk_size).max(1);
+    let resume_path = resume_path(&sha256, chunk_size);
+    let mut state = match fs::read(&resume_path)
+        .ok()
+        .and_then(|state| serde_json::from_slice::<ResumeState>(&state).ok())
+    {
+        Some(state)
+            if state.size == size && state.sha256 == sha256 && state.chunk_size == chunk_size =>
+        {
+            debug!(
+                "Resuming upload of {} at chunk {} as {}",
+                file.name, state.next_chunk, state.uuid
+            );
+            state
+        }
+        _ => ResumeState {
+            uuid: upload_uuid(&sha256),
+            size,
+            sha256: sha256.clone(),
+            chunk_size,
+            next_chunk: 0,
+        },
+    };
+    debug!(
+        "Uploading {} in {chunk_count} chunks as {}",
+        file.name, state.uuid
+    );
+
+    let mut source = fs::File::open(&file.path)
+        .whatever_context(format!("Could not open {}", file.path.display()))?;
+    let resumed_from = (state.next_chunk * chunk_size).min(size);
+    source
+        .seek(SeekFrom::Start(resumed_from))
+        .whatever_context(format!("Could not read {}", file.path.display()))?;
+    let mut progress = TransferProgress {
+        resumed_from,
+        transferred: resumed_from,
+        total: Some(size),
+    };
+    let mut last_response = None;
+    for index in state.next_chunk..chunk_count {
+        let offset = index * chunk_size;
+        let mut chunk = vec![0; chunk_size.min(size - offset) as usize];
+        source
+            .read_exact(&mut chunk)
+            .whatever_context(format!("Could not read {}", file.path.display()))?;
+
+        let mut attempt = 1;
+        let response = loop {
+            let chunk_form = form(Part::bytes(chunk.clone()).file_name(file.name.clone()))
+                .text("dzuuid", state.uuid.clone())
+                .text("dzchunkindex", index.to_string())
+                .text("dztotalfilesize", size.to_string())
+                .text("dzchunksize", chunk_size.to_string())
+                .text("dztotalchunkcount", chunk_count.to_string())
+                .text("dzchunkbyteoffset", offset.to_string());
+            match ilias_client.post_querypath_multipart(upload_querypath, chunk_form) {
+                Ok(response) => break response,
+                Err(error) if attempt < CHUNK_ATTEMPTS => {
+                    warn!(
+                        "Sending chunk {index} of {} again: {}",
+                        file.name,
+                        Report::from_error(error)
+                    );
+                    thread::sleep(Duration::from_secs(attempt));
+                    attempt += 1;
+                }
+                Err(error) => {
+                    return Err(error).whatever_context(format!(
+                        "Could not upload chunk {index} of {}, the next upload continues there",
+                        file.name
+                    ));
+                }
+            }
+        };
+        progress.transferred = offset + chunk.len() as u64;
+        progress_hook(&progress);
+        last_response = Some(response);
+
+        state.next_chunk = index + 1;
+        if state.next_chunk < chunk_count {
+            let stored = serde_json::to_vec(&state)
+                .map_err(io::Error::other)
+                .and_then(|state| {
+                    fs::create_dir_all(resume_path.parent().unwrap_or(&resume_path))?;
+                    write_private(&resume_path, &state)
+                });
+            if let Err(error) = stored {
+                warn!("Could not store resume state of {}: {error}", file.name);
+            }
+        }
+    }
+    // ILIAS assembles the file on the last chunk, so there is nothing left to resume
+    if let Err(error) = fs::remove_file(&resume_path)
+        && error.kind() != io::ErrorKind::NotFound
+    {
+        warn!("Could not remove resume state of {}: {error}", file.name);
+    }
+
+    let response = last_response.whatever_context(format!("No chunk of {} was sent", file.name))?;
+    Ok((response, ChunkedUpload { size, sha256 }))
+}
+
+impl ChunkedUpload {
+    /// Whether `uploaded`, as ILIAS serves it, has exactly the content that was sent
+    pub fn is_assembled_in(
+        &self,
+        ilias_client: &IliasClient,
+        uploaded: &File,
+    ) -> Result<bool, Whatever> {
+        let mut reader = uploaded.reader(ilias_client)?;
+        let mut hasher = Sha256::new();
+        let size = io::copy(&mut reader, &mut hasher)
+            .whatever_context(format!("Could not download {} to verify it", uploaded.name))?;
+        let sha256 = hex(&hasher.finalize());
+        debug!(
+            "ILIAS assembled {} with {size} bytes and hash {sha256}",
+            uploaded.name
+        );
+        Ok(size == self.size && sha256 == self.sha256)
+    }
+}
+
+fn resume_path(sha256: &str, chunk_size: u64) -> PathBuf {
+    env::temp_dir()
+        .join("ilias-uploads")
+        .join(format!("{sha256}-{chunk_size}.json"))
+}
+
+/// An id for the chunks of one upload, formatted like the UUIDs Dropzone sends
+fn upload_uuid(sha256: &str) -> String {
+    let seed = format!("{sha256}{:?}{}", SystemTime::now(), process::id());
+    let hash = hex(&Sha256::digest(seed));
+    format!(
+        "{}-{}-4{}-a{}-{}",
+        &hash[..8],
+        &hash[8..12],
+        &hash[13..16],
+        &hash[17..20],
+        &hash[20..32]
+    )
+}
@@ -1,163 +1,266 @@
-use std::{borrow::Cow, fmt::Debug, path::Path};
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    env,
+    fmt::Debug,
+    fs::{self, OpenOptions},
+    future::{self, Future},
+    io::{self, Read, Write},
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    pin::Pin,
+    process,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    task::{Context, Poll},
+    thread,
+    time::Duration,
+};
 
-use log::info;
+use log::{debug, info, warn};
 use reqwest::{
+    cookie::{CookieStore, Jar},
+    header::{CONTENT_TYPE, FROM, HeaderMap, HeaderValue, RANGE},
     multipart::{self, Form, Part},
-    Client, Response, Url,
+    Body, Client, Response, StatusCode, Url,
 };
-use scraper::{Html, Selector};
+use scraper::{ElementRef, Html, Selector};
 use serde::{de::DeserializeOwned, Serialize};
 use snafu::{whatever, OptionExt, ResultExt, Whatever};
-use tokio::{fs::File, io::BufWriter, runtime::Runtime};
-use tokio_stream::StreamExt;
-use tokio_util::io::StreamReader;
+use tokio::{
+    fs::File,
+    io::{AsyncRead, AsyncWriteExt, BufWriter, ReadBuf},
+    runtime::Runtime,
+    sync::mpsc::{self, UnboundedReceiver, UnboundedSender},
+};
+use tokio_stream::{Stream, StreamExt};
+
+use super::{
+    Querypath, fixture,
+    http_cache::HttpCache,
+    interstitial::{self, Interstitial},
+    lockfile::DirectoryLock,
+    membership::Membership,
+    politeness::{PolitenessProfile, Throttle, ThrottlePermit},
+    querypath_from_href,
+    search::{self, SearchFilter, SearchHit},
+};
 
-use super::Querypath;
+/// How many bytes of an uploaded file are read into the request at once
+const UPLOAD_CHUNK_SIZE: usize = 64 * 1024;
+
+/// How many info or confirmation pages a download follows before giving up on finding the file
+const MAX_INTERSTITIALS: usize = 3;
+
+/// State of a client that has not authenticated yet
+#[derive(Debug)]
+pub struct LoggedOut;
 
+/// State of a client with an authenticated session, required for everything but logging in
 #[derive(Debug)]
-pub struct IliasClient {
+pub struct LoggedIn;
+
+/// Progress of a transfer, reported to progress hooks after every chunk
+#[derive(Debug, Clone, Copy)]
+pub struct TransferProgress {
+    /// Bytes that were already transferred before this transfer was resumed
+    pub resumed_from: u64,
+    /// Bytes transferred in total, including `resumed_from`
+    pub transferred: u64,
+    /// The complete size if the server reported it
+    pub total: Option<u64>,
+}
+
+/// Collects the progress of the file parts of one multipart upload, see
+/// [`IliasClient::post_querypath_multipart_with_progress`]
+#[derive(Debug)]
+pub(crate) struct UploadProgress {
+    sender: UnboundedSender<(usize, u64)>,
+    receiver: UnboundedReceiver<(usize, u64)>,
+    totals: Vec<u64>,
+}
+
+/// The body of an uploaded file, reporting every chunk that is read into the request
+#[derive(Debug)]
+struct ProgressBody {
+    file: File,
+    index: usize,
+    sender: UnboundedSender<(usize, u64)>,
+}
+
+/// Reads the body of a download chunk by chunk as it arrives, see [`IliasClient::download_reader`]
+#[derive(Debug)]
+pub struct DownloadReader<'a> {
+    ilias_client: &'a IliasClient,
+    response: Response,
+    chunk: Vec<u8>,
+    position: usize,
+    _permit: Option<ThrottlePermit<'a>>,
+}
+
+#[derive(Debug)]
+pub struct IliasClient<State = LoggedIn> {
     client: Client,
+    cookies: Arc<Jar>,
     runtime: Runtime,
     base_url: Url,
+    fixture_directory: Option<PathBuf>,
+    http_cache: Option<HttpCache>,
+    throttle: Option<Throttle>,
+    allow_deletion: bool,
+    state: PhantomData<State>,
 }
 
-impl IliasClient {
-    pub fn new(base_url: Url) -> Result<IliasClient, Whatever> {
-        let client = Client::builder()
-            .cookie_store(true)
-            .use_rustls_tls()
-            .build()
-            .whatever_context("Could not build reqwest client")?;
+impl IliasClient<LoggedOut> {
+    pub fn new(base_url: Url) -> Result<IliasClient<LoggedOut>, Whatever> {
+        let cookies = Arc::new(Jar::default());
+        let client = build_client(&cookies, None)?;
         let runtime = Runtime::new().unwrap();
 
         Ok(IliasClient {
             client,
+            cookies,
             runtime,
             base_url,
+            fixture_directory: None,
+            http_cache: None,
+            throttle: None,
+            allow_deletion: false,
+            state: PhantomData,
         })
     }
 
-    pub fn get_querypath(&self, querypath: &str) -> Result<Html, Whatever> {
-        let mut url = self.base_url.clone();
-        url.set_querypath(querypath);
-
-        let text = self
-            .runtime
-            .block_on(async {
-                let response = self
-                    .client
-                    .get(url.clone())
-                    .send()
-                    .await
-                    .whatever_context(format!("No response for {url}"))?;
-                let text = response
-                    .text()
-                    .await
-                    .whatever_context(format!("Could not get text of response for {url}"))?;
-                Result::<_, Whatever>::Ok(text)
-            })
-            .whatever_context("Could not get text for querypath")?;
-        let html = Html::parse_document(&text);
-
-        Ok(html)
+    pub fn login(self, username: &str, password: &str) -> Result<IliasClient, Whatever> {
+        self.authenticate(username, password)?;
+        Ok(self.into_logged_in())
     }
 
-    pub fn post_querypath_form<T: Serialize + ?Sized + Debug>(
-        &self,
-        querypath: &str,
-        form: &T,
-    ) -> Result<Response, Whatever> {
+    /// Logs in with a local ILIAS account instead of Shibboleth, e.g. on a test installation
+    pub fn login_local(self, username: &str, password: &str) -> Result<IliasClient, Whatever> {
+        info!("Authenticating with local account!");
+
         let mut url = self.base_url.clone();
-        url.set_querypath(querypath);
+        url.set_querypath("login.php?cmd=force_login");
+        let permit = self.permit();
+        let login_page = self
+            .runtime
+            .block_on(async { self.client.get(url).send().await?.text().await })
+            .whatever_context("Could not get login page")?;
+        drop(permit);
+        let login_page = Html::parse_document(&login_page);
+
+        let form_selector = Selector::parse("form[action]").expect("Could not parse selector");
+        let input_selector = Selector::parse("input[name]").expect("Could not parse selector");
+        let password_selector =
+            Selector::parse("input[type=password]").expect("Could not parse selector");
+        let form = login_page
+            .select(&form_selector)
+            .find(|form| form.select(&password_selector).next().is_some())
+            .whatever_context("Could not find login form")?;
+        let form_data = form
+            .select(&input_selector)
+            .map(|input| {
+                let name = input.attr("name").unwrap_or_default();
+                let value = match input.attr("type").unwrap_or("text") {
+                    "password" => password,
+                    "text" | "email" => username,
+                    _ => input.attr("value").unwrap_or_default(),
+                };
+                (name, value)
+            })
+            .chain([("cmd[doStandardAuthentication]", "Login")])
+            .collect::<Vec<_>>();
+        let url = self
+            .base_url
+            .join(form.attr("action").unwrap_or_default())
+            .whatever_context("Could not resolve login form action")?;
 
+        let permit = self.permit();
         let response = self
             .runtime
-            .block_on(self.client.post(url).form(form).send())
-            .whatever_context("Could not post to querypath")?;
-        if response.url().as_str().contains("error") {
-            whatever!("Ilias error page");
+            .block_on(self.client.post(url).form(&form_data).send())
+            .whatever_context("Could not post login form")?;
+        drop(permit);
+        if !response.status().is_success() || response.url().path().contains("login.php") {
+            whatever!("Login as {username} was rejected");
         }
-        Ok(response
-            .error_for_status()
-            .whatever_context("Response had an error status code")?)
-    }
 
-    pub fn get_text(&self, response: Response) -> Result<String, Whatever> {
-        Ok(self
-            .runtime
-            .block_on(response.text())
-            .whatever_context("Could not get text of response")?)
+        Ok(self.into_logged_in())
     }
 
-    pub fn get_json<T: DeserializeOwned>(&self, response: Response) -> Result<T, Whatever> {
-        Ok(self
-            .runtime
-            .block_on(response.json())
-            .whatever_context("Could not get json from response")?)
-    }
-
-    pub fn is_alert_response(&self, response: Response) -> Result<bool, Whatever> {
-        let html = Html::parse_document(&self.get_text(response)?);
-        let selector =
-            Selector::parse(".alert-danger").whatever_context("Could not parse selector")?;
-        Ok(html.select(&selector).next().is_some())
+    /// `$XDG_CACHE_HOME/ilias` or `~/.cache/ilias`, for tools that want to share one session
+    pub fn default_session_directory() -> Option<PathBuf> {
+        env::var_os("XDG_CACHE_HOME")
+            .filter(|directory| !directory.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .map(|directory| directory.join("ilias"))
     }
 
-    pub fn post_querypath_multipart(
-        &self,
-        querypath: &str,
-        form: multipart::Form,
-    ) -> Result<Response, Whatever> {
-        let mut url = self.base_url.clone();
-        url.set_querypath(querypath);
+    /// Logs in reusing the session stored in `directory` by other clients if it is still valid.
+    ///
+    /// The directory is locked while the session is checked, so only one of several tools
+    /// started at once logs in and the others pick up its session.
+    pub fn login_with_session(
+        self,
+        directory: &Path,
+        username: &str,
+        password: &str,
+    ) -> Result<IliasClient, Whatever> {
+        let _lock = DirectoryLock::acquire(directory, Duration::from_secs(300))?;
+        let session_path = directory.join(SESSION_FILE_NAME);
 
-        let response = self
-            .runtime
-            .block_on(self.client.post(url).multipart(form).send())
-            .whatever_context("Could not send multipart form")?;
+        if let Ok(session) = fs::read_to_string(&session_path) {
+            for cookie in session
+                .split(';')
+                .map(str::trim)
+                .filter(|cookie| !cookie.is_empty())
+            {
+                self.cookies
+                    .add_cookie_str(&format!("{cookie}; Path=/"), &self.base_url);
+            }
+            if self.has_valid_session() {
+                info!("Reusing stored session");
+                return Ok(self.into_logged_in());
+            }
+        }
 
-        Ok(response
-            .error_for_status()
-            .whatever_context("Response had an error status code")?)
+        self.authenticate(username, password)?;
+        match self.cookies.cookies(&self.base_url) {
+            Some(session) => store_session(&session_path, &session)?,
+            None => warn!("Got no session cookies to store"),
+        }
+        Ok(self.into_logged_in())
     }
 
-    pub fn download_file(&self, querypath: &str, to: &Path) -> Result<(), Whatever> {
+    fn has_valid_session(&self) -> bool {
         let mut url = self.base_url.clone();
-        url.set_querypath(querypath);
-
+        url.set_querypath("ilias.php?baseClass=ilDashboardGUI&cmd=show");
+        let _permit = self.permit();
         self.runtime
-            .block_on(async {
-                let response = self
-                    .client
-                    .get(url.clone())
-                    .send()
-                    .await
-                    .whatever_context("Could not get response for download url")?;
-                let body_stream = response.bytes_stream();
-                let body_stream = body_stream.map(|result| {
-                    result.map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))
-                });
-                let mut body_reader = StreamReader::new(body_stream);
-
-                let mut options = File::options();
-                options.write(true);
-                options.create(true);
-                let file = options
-                    .open(to)
-                    .await
-                    .whatever_context("Unable to open file")?;
-                let mut file_writer = BufWriter::new(file);
-
-                tokio::io::copy(&mut body_reader, &mut file_writer)
-                    .await
-                    .whatever_context("Could not copy reader to writer")?;
-                Result::<_, Whatever>::Ok(())
+            .block_on(self.client.get(url).send())
+            .is_ok_and(|response| {
+                response.status().is_success() && !response.url().path().contains("login.php")
             })
-            .whatever_context("Could not download file")?;
-        Ok(())
     }
 
-    pub fn authenticate(&self, username: &str, password: &str) -> Result<(), Whatever> {
+    fn into_logged_in(self) -> IliasClient {
+        IliasClient {
+            client: self.client,
+            cookies: self.cookies,
+            runtime: self.runtime,
+            base_url: self.base_url,
+            fixture_directory: self.fixture_directory,
+            http_cache: self.http_cache,
+            throttle: self.throttle,
+            allow_deletion: self.allow_deletion,
+            state: PhantomData,
+        }
+    }
+
+    fn authenticate(&self, username: &str, password: &str) -> Result<(), Whatever> {
         info!("Authenticating!");
 
         let shib_path = "shib_login.php";
@@ -173,10 +276,12 @@ impl IliasClient {
         url.set_path(shib_path);
         let shib_url = url.as_str().to_owned();
 
+        let permit = self.permit();
         let shib_login_page = self
             .runtime
             .block_on(self.client.post(url).form(&shib_params).send())
             .whatever_context("Could not send multipart form")?;
+        drop(permit);
 
         let mut url = shib_login_page.url().to_owned();
         let is_ilias = url.as_str().starts_with(
@@ -226,6 +331,7 @@ impl IliasClient {
                 .whatever_context("Could not get login querypath action")?;
 
             url.set_querypath(post_querypath);
+            let _permit = self.permit();
             let continue_response = self
                 .runtime
                 .block_on(self.client.post(url).form(&form_data).send())
@@ -261,6 +367,7 @@ impl IliasClient {
             .attr("action")
             .unwrap();
 
+        let _permit = self.permit();
         let ilias_home = self
             .client
             .post(continue_url)
@@ -278,10 +385,758 @@ impl IliasClient {
             whatever!("Ilias login not successful!")
         }
     }
+}
+
+const SESSION_FILE_NAME: &str = "session";
+
+/// The session cookies are credentials, so only the user may read them
+fn store_session(path: &Path, session: &HeaderValue) -> Result<(), Whatever> {
+    write_private(path, session.as_bytes()).whatever_context("Could not store session")
+}
+
+/// Writes a file only the user may read. The content goes into a new file that is created with
+/// these permissions and then renamed over `path`, so it is never readable by others and readers
+/// never see a partial file.
+pub(crate) fn write_private(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let temporary_path = path.with_extension(format!("{}.tmp", process::id()));
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let result = options
+        .open(&temporary_path)
+        .and_then(|mut file| file.write_all(contents))
+        .and_then(|()| fs::rename(&temporary_path, path));
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary_path);
+    }
+    result
+}
+
+fn build_client(
+    cookies: &Arc<Jar>,
+    politeness: Option<&PolitenessProfile>,
+) -> Result<Client, Whatever> {
+    let mut builder = Client::builder()
+        .cookie_provider(cookies.clone())
+        .use_rustls_tls();
+    if let Some(politeness) = politeness {
+        let mut headers = HeaderMap::new();
+        if let Some(contact) = &politeness.contact {
+            headers.insert(
+                FROM,
+                HeaderValue::from_str(contact).whatever_context("Invalid contact address")?,
+            );
+        }
+        builder = builder
+            .user_agent(&politeness.user_agent)
+            .default_headers(headers);
+    }
+    builder
+        .build()
+        .whatever_context("Could not build reqwest client")
+}
+
+impl<State> IliasClient<State> {
+    /// Identifies the client and limits its request rate as given, or removes all limits.
+    ///
+    /// The session is kept, so this can be switched at any time.
+    pub fn set_politeness(&mut self, profile: Option<PolitenessProfile>) -> Result<(), Whatever> {
+        self.client = build_client(&self.cookies, profile.as_ref())?;
+        self.throttle = profile.map(Throttle::new);
+        Ok(())
+    }
+
+    pub fn politeness(&self) -> Option<&PolitenessProfile> {
+        self.throttle.as_ref().map(Throttle::profile)
+    }
+
+    /// Waits until the politeness profile allows another request
+    fn permit(&self) -> Option<ThrottlePermit<'_>> {
+        self.throttle
+            .as_ref()
+            .map(|throttle| self.runtime.block_on(throttle.acquire()))
+    }
+
+    /// Pages that fail to parse are saved (redacted) to this directory together with the error
+    pub fn set_fixture_directory(&mut self, directory: Option<PathBuf>) {
+        self.fixture_directory = directory;
+    }
+
+    /// Keeps pages in `directory` and only downloads them again if the server reports a change.
+    ///
+    /// Several tools can share one directory, e.g. `http` in
+    /// [`IliasClient::default_session_directory`] next to their shared session.
+    pub fn set_cache_directory(&mut self, directory: Option<&Path>) -> Result<(), Whatever> {
+        self.http_cache = directory.map(HttpCache::new).transpose()?;
+        Ok(())
+    }
+
+    /// Deleting objects fails unless this is set, so that nothing is deleted by accident
+    pub fn set_allow_deletion(&mut self, allow_deletion: bool) {
+        self.allow_deletion = allow_deletion;
+    }
+
+    pub(crate) fn ensure_deletion_allowed(&self) -> Result<(), Whatever> {
+        if !self.allow_deletion {
+            whatever!("Deletion is not allowed, enable it with IliasClient::set_allow_deletion");
+        }
+        Ok(())
+    }
+}
+
+const FIRST_LISTING_PAGE: &str = "first";
+
+/// The page of a listing a link leads to, from the offset of a table navigation (`*_nav`) or the
+/// page number of a pagination (`page`, `*_page`). The first page has the same key everywhere.
+fn listing_page(url: &Url) -> Option<String> {
+    url.query_pairs().find_map(|(name, value)| {
+        let page = if name.ends_with("_nav") {
+            value.split(':').nth(2)?.to_string()
+        } else if name == "page" || name.ends_with("_page") {
+            value.to_string()
+        } else {
+            return None;
+        };
+        Some(if page.is_empty() || page == "0" {
+            FIRST_LISTING_PAGE.to_string()
+        } else {
+            format!("{name}={page}")
+        })
+    })
+}
+
+impl IliasClient {
+    pub(crate) fn capture_fixture(&self, querypath: &str, html: &Html, error: &Whatever) {
+        if let Some(directory) = &self.fixture_directory {
+            fixture::capture(directory, querypath, &html.html(), error);
+        }
+    }
+
+    /// Fetches the further pages of a paginated listing, following the links `navigation`
+    /// selects on `page` and on every page fetched. `page` has to be the first page of the
+    /// listing, links back to it from later pages are not followed.
+    pub(crate) fn get_linked_pages(
+        &self,
+        page: ElementRef,
+        navigation: &Selector,
+    ) -> Result<Vec<Html>, Whatever> {
+        let navigation_links = |page: ElementRef| {
+            page.select(navigation)
+                .filter_map(|link| link.attr("href").or_else(|| link.attr("data-action")))
+                .filter(|href| !href.starts_with('#'))
+                .map(querypath_from_href)
+                .collect::<Vec<_>>()
+        };
+
+        // Links to the same page differ in sorting and other parameters
+        let page_of = |querypath: &str| {
+            let mut url = self.base_url.clone();
+            url.set_querypath(querypath);
+            listing_page(&url).unwrap_or_else(|| querypath.to_string())
+        };
+
+        let mut unvisited = navigation_links(page);
+        let mut visited = HashSet::from([FIRST_LISTING_PAGE.to_string()]);
+        let mut pages = vec![];
+        while let Some(querypath) = unvisited.pop() {
+            if !visited.insert(page_of(&querypath)) {
+                continue;
+            }
+            let linked_page = self
+                .get_querypath(&querypath)
+                .whatever_context("Could not get further page of listing")?;
+            unvisited.extend(
+                navigation_links(linked_page.root_element())
+                    .into_iter()
+                    .filter(|querypath| !visited.contains(&page_of(querypath))),
+            );
+            pages.push(linked_page);
+        }
+        Ok(pages)
+    }
+
+    pub fn get_querypath(&self, querypath: &str) -> Result<Html, Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        let cached = self
+            .http_cache
+            .as_ref()
+            .and_then(|cache| cache.lookup(&url));
+        let _permit = self.permit();
+        let text = self
+            .runtime
+            .block_on(async {
+                let mut request = self.client.get(url.clone());
+                if let Some(cached) = &cached {
+                    request = request.headers(HttpCache::conditional_headers(cached));
+                }
+                let response = request
+                    .send()
+                    .await
+                    .whatever_context(format!("No response for {url}"))?;
+                if response.status() == StatusCode::NOT_MODIFIED
+                    && let Some(cached) = cached
+                {
+                    debug!("Using cached {url}");
+                    return Ok(cached.body);
+                }
+                let headers = response.headers().clone();
+                let text = response
+                    .text()
+                    .await
+                    .whatever_context(format!("Could not get text of response for {url}"))?;
+                if let Some(cache) = &self.http_cache {
+                    cache.store(&url, &headers, &text);
+                }
+                Result::<_, Whatever>::Ok(text)
+            })
+            .whatever_context("Could not get text for querypath")?;
+        let html = Html::parse_document(&text);
+
+        Ok(html)
+    }
+
+    /// Fetches a page without blocking, for use from async code. Waiting for the politeness
+    /// profile does not block either.
+    pub async fn get_querypath_async(&self, querypath: &str) -> Result<Html, Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        let _permit = match &self.throttle {
+            Some(throttle) => Some(throttle.acquire().await),
+            None => None,
+        };
+        let text = self
+            .client
+            .get(url.clone())
+            .send()
+            .await
+            .and_then(Response::error_for_status)
+            .whatever_context(format!("Could not get {url}"))?
+            .text()
+            .await
+            .whatever_context(format!("Could not get text of response for {url}"))?;
+        Ok(Html::parse_document(&text))
+    }
+
+    /// Fetches the pages with up to `parallelism` requests at the same time, which the politeness
+    /// profile may lower further. The requests are all polled on the calling thread, so pages and
+    /// errors do not have to be sent between threads. The results are in the order of
+    /// `querypaths`.
+    pub(crate) fn get_querypaths_concurrently(
+        &self,
+        querypaths: &[&str],
+        parallelism: usize,
+    ) -> Vec<Result<Html, Whatever>> {
+        let parallelism = self
+            .politeness()
+            .map_or(parallelism, |profile| {
+                parallelism.min(profile.max_concurrency)
+            })
+            .max(1);
+
+        let mut results = querypaths.iter().map(|_| None).collect::<Vec<_>>();
+        let mut pending = querypaths.iter().enumerate();
+        let mut running = vec![];
+        self.runtime.block_on(future::poll_fn(|context| {
+            loop {
+                while running.len() < parallelism
+                    && let Some((index, querypath)) = pending.next()
+                {
+                    running.push((index, Box::pin(self.get_querypath_async(querypath))));
+                }
+                let polled = running.len();
+                running.retain_mut(|(index, request)| match request.as_mut().poll(context) {
+                    Poll::Ready(result) => {
+                        results[*index] = Some(result);
+                        false
+                    }
+                    Poll::Pending => true,
+                });
+                if running.is_empty() && pending.len() == 0 {
+                    return Poll::Ready(());
+                }
+                // Nothing finished, so every running request has registered to be woken
+                if running.len() == polled {
+                    return Poll::Pending;
+                }
+            }
+        }));
+        results
+            .into_iter()
+            .map(|result| result.expect("Every page is fetched"))
+            .collect()
+    }
+
+    /// Calls `f` for every item on up to `parallelism` threads, which the politeness profile may
+    /// lower further. The results are in the order of `items`.
+    pub(crate) fn map_concurrently<T: Sync, R: Send>(
+        &self,
+        items: &[T],
+        parallelism: usize,
+        f: impl Fn(&T) -> R + Sync,
+    ) -> Vec<R> {
+        let parallelism = self
+            .politeness()
+            .map_or(parallelism, |profile| {
+                parallelism.min(profile.max_concurrency)
+            })
+            .min(items.len())
+            .max(1);
+
+        let next = AtomicUsize::new(0);
+        let mut results = thread::scope(|scope| {
+            let workers = (0..parallelism)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = vec![];
+                        loop {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(item) = items.get(index) else {
+                                break;
+                            };
+                            results.push((index, f(item)));
+                        }
+                        results
+                    })
+                })
+                .collect::<Vec<_>>();
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().expect("Worker thread panicked"))
+                .collect::<Vec<_>>()
+        });
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    /// Lists the courses and groups the user is a member of
+    pub fn get_memberships(&self) -> Result<Vec<Membership>, Whatever> {
+        let page = self
+            .get_querypath(Membership::OVERVIEW_QUERYPATH)
+            .whatever_context("Could not get membership overview")?;
+        Ok(Membership::parse_overview(page.root_element()))
+    }
+
+    /// Searches the repository like the search page does, collecting the hits of all pages
+    pub fn search(&self, query: &str, filter: &SearchFilter) -> Result<Vec<SearchHit>, Whatever> {
+        search::search(self, query, filter)
+    }
+
+    pub fn post_querypath_form<T: Serialize + ?Sized + Debug>(
+        &self,
+        querypath: &str,
+        form: &T,
+    ) -> Result<Response, Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        let _permit = self.permit();
+        let response = self
+            .runtime
+            .block_on(self.client.post(url).form(form).send())
+            .whatever_context("Could not post to querypath")?;
+        if response.url().as_str().contains("error") {
+            whatever!("Ilias error page");
+        }
+        response
+            .error_for_status()
+            .whatever_context("Response had an error status code")
+    }
+
+    /// The url a querypath finally redirects to, without reading the target's content
+    pub fn resolve_redirect(&self, querypath: &str) -> Result<Url, Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        let _permit = self.permit();
+        let response = self
+            .runtime
+            .block_on(self.client.get(url.clone()).send())
+            .whatever_context(format!("No response for {url}"))?;
+        Ok(response.url().clone())
+    }
+
+    pub fn get_text(&self, response: Response) -> Result<String, Whatever> {
+        self.runtime
+            .block_on(response.text())
+            .whatever_context("Could not get text of response")
+    }
+
+    pub fn get_json<T: DeserializeOwned>(&self, response: Response) -> Result<T, Whatever> {
+        self.runtime
+            .block_on(response.json())
+            .whatever_context("Could not get json from response")
+    }
+
+    pub fn is_alert_response(&self, response: Response) -> Result<bool, Whatever> {
+        let html = Html::parse_document(&self.get_text(response)?);
+        let selector =
+            Selector::parse(".alert-danger").whatever_context("Could not parse selector")?;
+        Ok(html.select(&selector).next().is_some())
+    }
+
+    pub fn post_querypath_multipart(
+        &self,
+        querypath: &str,
+        form: multipart::Form,
+    ) -> Result<Response, Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        let _permit = self.permit();
+        let response = self
+            .runtime
+            .block_on(self.client.post(url).multipart(form).send())
+            .whatever_context("Could not send multipart form")?;
+
+        response
+            .error_for_status()
+            .whatever_context("Response had an error status code")
+    }
+
+    /// Posts a multipart form and calls `progress_hook` with the index of a file part whenever
+    /// more of it was sent. The file parts have to be built with
+    /// [`construct_file_part_with_progress`](Self::construct_file_part_with_progress).
+    pub(crate) fn post_querypath_multipart_with_progress(
+        &self,
+        querypath: &str,
+        form: multipart::Form,
+        progress: UploadProgress,
+        mut progress_hook: impl FnMut(usize, &TransferProgress),
+    ) -> Result<Response, Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        let UploadProgress {
+            mut receiver,
+            totals,
+            ..
+        } = progress;
+        let mut transferred = vec![0; totals.len()];
+
+        let _permit = self.permit();
+        let mut request = self
+            .runtime
+            .spawn(self.client.post(url).multipart(form).send());
+        let response = self
+            .runtime
+            .block_on(future::poll_fn(|context| {
+                while let Poll::Ready(Some((index, length))) = receiver.poll_recv(context) {
+                    transferred[index] += length;
+                    progress_hook(
+                        index,
+                        &TransferProgress {
+                            resumed_from: 0,
+                            transferred: transferred[index],
+                            total: Some(totals[index]),
+                        },
+                    );
+                }
+                Pin::new(&mut request).poll(context)
+            }))
+            .whatever_context("Upload was aborted")?
+            .whatever_context("Could not send multipart form")?;
+
+        response
+            .error_for_status()
+            .whatever_context("Response had an error status code")
+    }
+
+    pub fn download_file(&self, querypath: &str, to: &Path) -> Result<(), Whatever> {
+        self.download_file_with_progress(querypath, to, false, |_| {})
+    }
+
+    pub fn download_file_with_progress(
+        &self,
+        querypath: &str,
+        to: &Path,
+        resume: bool,
+        progress_hook: impl FnMut(&TransferProgress),
+    ) -> Result<(), Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        self.download_url_with_progress(url, to, resume, progress_hook)
+    }
+
+    /// Downloads from an absolute url with the session of this client, e.g. for media servers
+    pub fn download_url(&self, url: Url, to: &Path) -> Result<(), Whatever> {
+        self.download_url_with_progress(url, to, false, |_| {})
+    }
+
+    /// Downloads and returns the Content-Type the server sent, e.g. to infer a missing extension
+    pub fn download_file_with_content_type(
+        &self,
+        querypath: &str,
+        to: &Path,
+    ) -> Result<Option<String>, Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        self.download_url_with_content_type(url, to, false, |_| {})
+    }
+
+    /// Downloads while reporting progress after every chunk.
+    ///
+    /// With `resume` an existing partial file is continued via a range request if the server
+    /// supports it, otherwise the download starts over.
+    pub fn download_url_with_progress(
+        &self,
+        url: Url,
+        to: &Path,
+        resume: bool,
+        progress_hook: impl FnMut(&TransferProgress),
+    ) -> Result<(), Whatever> {
+        self.download_url_with_content_type(url, to, resume, progress_hook)
+            .map(|_| ())
+    }
+
+    fn download_url_with_content_type(
+        &self,
+        url: Url,
+        to: &Path,
+        resume: bool,
+        mut progress_hook: impl FnMut(&TransferProgress),
+    ) -> Result<Option<String>, Whatever> {
+        let _permit = self.permit();
+        self.runtime
+            .block_on(async {
+                let existing_length = if resume {
+                    tokio::fs::metadata(to)
+                        .await
+                        .map(|metadata| metadata.len())
+                        .unwrap_or(0)
+                } else {
+                    0
+                };
+
+                let mut request = self.client.get(url.clone());
+                if existing_length > 0 {
+                    request = request.header(RANGE, format!("bytes={existing_length}-"));
+                }
+                let response = request
+                    .send()
+                    .await
+                    .whatever_context("Could not get response for download url")?;
+                if response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+                    // The partial file is already complete
+                    progress_hook(&TransferProgress {
+                        resumed_from: existing_length,
+                        transferred: existing_length,
+                        total: Some(existing_length),
+                    });
+                    return Ok(None);
+                }
+                let mut response = response
+                    .error_for_status()
+                    .whatever_context("Download had an error status code")?;
+
+                // Follow info and confirmation pages to the file, keeping the page if it turns
+                // out to be the content
+                let mut prefetched = None;
+                for _ in 0..MAX_INTERSTITIALS {
+                    if response.status() != StatusCode::OK || !interstitial::is_candidate(&response)
+                    {
+                        break;
+                    }
+                    // Read by chunk so the response stays usable if the page is the content
+                    let mut body = vec![];
+                    while let Some(chunk) = response
+                        .chunk()
+                        .await
+                        .whatever_context("Could not get chunk of download")?
+                    {
+                        body.extend_from_slice(&chunk);
+                    }
+                    let request = {
+                        let page = Html::parse_document(&String::from_utf8_lossy(&body));
+                        let mut next_url = self.base_url.clone();
+                        match interstitial::find(&page) {
+                            Some(Interstitial::Link(querypath)) => {
+                                next_url.set_querypath(&querypath);
+                                self.client.get(next_url)
+                            }
+                            Some(Interstitial::Form { form, button }) => {
+                                next_url.set_querypath(&form.action);
+                                let form_data = form.fields.into_iter().chain(button);
+                                self.client
+                                    .post(next_url)
+                                    .form(&form_data.collect::<Vec<_>>())
+                            }
+                            None => {
+                                prefetched = Some(body);
+                                break;
+                            }
+                        }
+                    };
+                    info!("Following download interstitial of {url}");
+                    response = request
+                        .send()
+                        .await
+                        .whatever_context("Could not follow download interstitial")?
+                        .error_for_status()
+                        .whatever_context("Download had an error status code")?;
+                }
+                let content_type = content_type(&response);
+
+                let resumed_from = if response.status() == StatusCode::PARTIAL_CONTENT {
+                    existing_length
+                } else {
+                    0
+                };
+                let mut progress = TransferProgress {
+                    resumed_from,
+                    transferred: resumed_from,
+                    total: response
+                        .content_length()
+                        .map(|length| length + resumed_from),
+                };
+                progress_hook(&progress);
+
+                let mut options = File::options();
+                options.create(true);
+                if resumed_from > 0 {
+                    options.append(true);
+                } else {
+                    options.write(true).truncate(true);
+                }
+                let file = options
+                    .open(to)
+                    .await
+                    .whatever_context("Unable to open file")?;
+                let mut file_writer = BufWriter::new(file);
+
+                if let Some(body) = prefetched {
+                    file_writer
+                        .write_all(&body)
+                        .await
+                        .whatever_context("Could not write download to file")?;
+                    progress.transferred += body.len() as u64;
+                    progress_hook(&progress);
+                }
+                let mut body_stream = response.bytes_stream();
+                while let Some(chunk) = body_stream.next().await {
+                    let chunk = chunk.whatever_context("Could not get chunk of download")?;
+                    file_writer
+                        .write_all(&chunk)
+                        .await
+                        .whatever_context("Could not write chunk to file")?;
+                    progress.transferred += chunk.len() as u64;
+                    progress_hook(&progress);
+                }
+                file_writer
+                    .flush()
+                    .await
+                    .whatever_context("Could not flush file")?;
+                Result::<_, Whatever>::Ok(content_type)
+            })
+            .whatever_context("Could not download file")
+    }
+
+    /// Streams the body of a response that already is the file into `to`, returning its size
+    pub(crate) fn save_response(&self, response: Response, to: &Path) -> Result<u64, Whatever> {
+        self.runtime
+            .block_on(async {
+                let file = File::create(to)
+                    .await
+                    .whatever_context("Unable to open file")?;
+                let mut file_writer = BufWriter::new(file);
+                let mut written = 0;
+                let mut body_stream = response.bytes_stream();
+                while let Some(chunk) = body_stream.next().await {
+                    let chunk = chunk.whatever_context("Could not get chunk of download")?;
+                    file_writer
+                        .write_all(&chunk)
+                        .await
+                        .whatever_context("Could not write chunk to file")?;
+                    written += chunk.len() as u64;
+                }
+                file_writer
+                    .flush()
+                    .await
+                    .whatever_context("Could not flush file")?;
+                Result::<_, Whatever>::Ok(written)
+            })
+            .whatever_context("Could not save response")
+    }
+
+    /// Downloads into memory, for small files that are parsed right away
+    pub fn download_bytes(&self, querypath: &str) -> Result<Vec<u8>, Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        self.download_url_bytes(url)
+    }
+
+    /// Downloads from an absolute url into memory, e.g. for preview images
+    pub fn download_url_bytes(&self, url: Url) -> Result<Vec<u8>, Whatever> {
+        let _permit = self.permit();
+        self.runtime
+            .block_on(async {
+                let response = self
+                    .client
+                    .get(url.clone())
+                    .send()
+                    .await
+                    .whatever_context(format!("No response for {url}"))?
+                    .error_for_status()
+                    .whatever_context("Download had an error status code")?;
+                let bytes = response
+                    .bytes()
+                    .await
+                    .whatever_context("Could not get body of download")?;
+                Result::<_, Whatever>::Ok(bytes.to_vec())
+            })
+            .whatever_context("Could not download file")
+    }
+
+    /// Starts a download whose body is read on demand through [`std::io::Read`]
+    pub fn download_reader(&self, querypath: &str) -> Result<DownloadReader<'_>, Whatever> {
+        let mut url = self.base_url.clone();
+        url.set_querypath(querypath);
+
+        let permit = self.permit();
+        let response = self
+            .runtime
+            .block_on(self.client.get(url.clone()).send())
+            .whatever_context(format!("No response for {url}"))?
+            .error_for_status()
+            .whatever_context("Download had an error status code")?;
+
+        Ok(DownloadReader {
+            ilias_client: self,
+            response,
+            chunk: vec![],
+            position: 0,
+            _permit: permit,
+        })
+    }
 
     pub fn construct_file_part<T: AsRef<Path>>(&self, path: T) -> Result<Part, Whatever> {
+        self.file_part(path.as_ref(), None)
+    }
+
+    /// Builds a file part that reports how much of it was sent through `progress`
+    pub(crate) fn construct_file_part_with_progress<T: AsRef<Path>>(
+        &self,
+        path: T,
+        progress: &mut UploadProgress,
+    ) -> Result<Part, Whatever> {
+        self.file_part(path.as_ref(), Some(progress))
+    }
+
+    fn file_part(
+        &self,
+        path: &Path,
+        progress: Option<&mut UploadProgress>,
+    ) -> Result<Part, Whatever> {
         let part = async {
-            let path = path.as_ref();
             let file_name = path
                 .file_name()
                 .map(|filename| filename.to_string_lossy().into_owned());
@@ -295,7 +1150,18 @@ impl IliasClient {
                 .await
                 .whatever_context("Could not get file length")?
                 .len();
-            let field = Part::stream_with_length(file, length)
+            let body = match progress {
+                Some(progress) => {
+                    progress.totals.push(length);
+                    Body::wrap_stream(ProgressBody {
+                        file,
+                        index: progress.totals.len() - 1,
+                        sender: progress.sender.clone(),
+                    })
+                }
+                None => Body::from(file),
+            };
+            let field = Part::stream_with_length(body, length)
                 .mime_str(mime.as_ref())
                 .whatever_context("Could not add mime string")?;
 
@@ -306,13 +1172,87 @@ impl IliasClient {
             })
         };
 
-        Ok(self
-            .runtime
+        self.runtime
             .block_on(part)
-            .whatever_context("Could not construct file part")?)
+            .whatever_context("Could not construct file part")
+    }
+}
+
+impl UploadProgress {
+    pub fn new() -> UploadProgress {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        UploadProgress {
+            sender,
+            receiver,
+            totals: vec![],
+        }
+    }
+}
+
+impl Stream for ProgressBody {
+    type Item = io::Result<Vec<u8>>;
+
+    fn poll_next(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut chunk = vec![0; UPLOAD_CHUNK_SIZE];
+        let mut buffer = ReadBuf::new(&mut chunk);
+        match Pin::new(&mut self.file).poll_read(context, &mut buffer) {
+            Poll::Ready(Ok(())) => {
+                let length = buffer.filled().len();
+                if length == 0 {
+                    return Poll::Ready(None);
+                }
+                chunk.truncate(length);
+                // The upload goes on even if nobody listens for its progress anymore
+                let _ = self.sender.send((self.index, length as u64));
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(Err(error)) => Poll::Ready(Some(Err(error))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl DownloadReader<'_> {
+    /// The size of the download if the server reported it
+    pub fn content_length(&self) -> Option<u64> {
+        self.response.content_length()
+    }
+
+    pub fn content_type(&self) -> Option<String> {
+        content_type(&self.response)
+    }
+}
+
+impl Read for DownloadReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.chunk.len() {
+            let chunk = self
+                .ilias_client
+                .runtime
+                .block_on(self.response.chunk())
+                .map_err(io::Error::other)?;
+            let Some(chunk) = chunk else {
+                return Ok(0);
+            };
+            self.chunk = chunk.to_vec();
+            self.position = 0;
+        }
+
+        let length = buf.len().min(self.chunk.len() - self.position);
+        buf[..length].copy_from_slice(&self.chunk[self.position..self.position + length]);
+        self.position += length;
+        Ok(length)
     }
 }
 
+fn content_type(response: &Response) -> Option<String> {
+    response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .map(ToOwned::to_owned)
+}
+
 pub trait AddFileWithFilename {
     fn file_with_name<T, V>(
         self,
@@ -0,0 +1,107 @@
+use std::sync::OnceLock;
+
+use log::info;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use super::{IliasElement, client::IliasClient, form::HtmlForm, id::RefId};
+
+/// A "I have read this" confirmation that objects with manual learning progress show
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Confirmation {
+    pub label: String,
+    pub confirmed: bool,
+    checkbox_name: String,
+    form: HtmlForm,
+}
+
+static FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CHECKBOX_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LABEL_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+const CONFIRMATION_LABELS: [&str; 6] = [
+    "gelesen",
+    "bearbeitet",
+    "bestätige",
+    "have read",
+    "completed",
+    "confirm",
+];
+
+impl IliasElement for Confirmation {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    /// Finds the confirmation form on the page of the object it belongs to
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let form_selector = FORM_SELECTOR
+            .get_or_init(|| Selector::parse("form[action]").expect("Could not parse selector"));
+        let checkbox_selector = CHECKBOX_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"input[type="checkbox"][name]"#).expect("Could not parse selector")
+        });
+        let label_selector = LABEL_SELECTOR
+            .get_or_init(|| Selector::parse("label").expect("Could not parse selector"));
+
+        for form in element.select(form_selector) {
+            let Some(checkbox) = form.select(checkbox_selector).find(|checkbox| {
+                checkbox
+                    .attr("name")
+                    .is_some_and(|name| name.contains("lp_edit") || name.contains("read"))
+            }) else {
+                continue;
+            };
+            let label = form
+                .select(label_selector)
+                .map(|label| label.text().collect::<String>().trim().to_string())
+                .find(|label| {
+                    let label = label.to_lowercase();
+                    CONFIRMATION_LABELS
+                        .iter()
+                        .any(|keyword| label.contains(keyword))
+                })
+                .unwrap_or_default();
+
+            return Ok(Confirmation {
+                label,
+                confirmed: checkbox.attr("checked").is_some(),
+                checkbox_name: checkbox
+                    .attr("name")
+                    .whatever_context("Checkbox without name")?
+                    .to_string(),
+                form: HtmlForm::parse(form)?,
+            });
+        }
+
+        whatever!("Page has no confirmation form")
+    }
+}
+
+impl Confirmation {
+    pub fn confirm(&mut self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        if self.confirmed {
+            return Ok(());
+        }
+
+        let mut form = self.form.clone();
+        form.set(&self.checkbox_name, "1");
+        let response = form
+            .submit(
+                ilias_client,
+                form.buttons.first().map(|(name, _)| name.as_str()),
+            )
+            .whatever_context("Could not submit confirmation")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected the confirmation of {}", self.label);
+        }
+
+        info!("Confirmed {}", self.label);
+        self.confirmed = true;
+        Ok(())
+    }
+}
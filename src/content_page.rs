@@ -0,0 +1,88 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, Whatever};
+
+use super::{
+    IliasElement,
+    client::IliasClient,
+    file::File,
+    id::RefId,
+    page::{ContentBlock, Page},
+};
+
+/// An ILIAS content page (`copa`), often used as landing page of a course
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContentPage {
+    pub name: String,
+    pub description: String,
+    pub page: Page,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for ContentPage {
+    fn type_identifier() -> Option<&'static str> {
+        Some("copa")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+        let page = Page::parse(element, ilias_client)?;
+
+        let content_page = ContentPage {
+            name,
+            description,
+            page,
+        };
+        debug!("Content page: {:?}", content_page.name);
+
+        Ok(content_page)
+    }
+}
+
+impl ContentPage {
+    pub fn blocks(&self) -> Vec<ContentBlock> {
+        self.page.blocks()
+    }
+
+    /// The files linked from the page, e.g. slides offered for download on a landing page
+    pub fn files(&self) -> Vec<File> {
+        self.blocks()
+            .into_iter()
+            .filter_map(|block| match block {
+                ContentBlock::FileLink(file) => Some(file),
+                _ => None,
+            })
+            .collect()
+    }
+}
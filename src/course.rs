@@ -0,0 +1,472 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use regex::Regex;
+use reqwest::Url;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    ILIAS_URL, IliasContainer, IliasElement,
+    client::{IliasClient, TransferProgress},
+    folder::{
+        ContainerSection, FileUpload, FolderElement, create_folder_in, element_indices_in,
+        parse_container_elements, upload_to_container,
+    },
+    group::Group,
+    id::RefId,
+    info_screen::{InfoProperty, InfoScreen},
+    learning_progress::{LearningProgress, LearningProgressOverview},
+    local_file::NamedLocalFile,
+    locator::{Ancestor, parse_locator},
+    news::{NewsItem, NewsTimeline},
+    reference::Reference,
+};
+
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct Course {
+    pub name: String,
+    pub description: String,
+    pub id: RefId,
+    pub elements: Vec<FolderElement>,
+    pub sections: Vec<ContainerSection>,
+    /// Only set for courses with objective-driven presentation
+    pub objectives: Vec<LearningObjective>,
+    /// The latest announcements from the news block of the course page
+    pub news: Vec<NewsItem>,
+    info: Reference<InfoScreen>,
+    learning_progress: Reference<LearningProgressOverview>,
+    timeline: Reference<NewsTimeline>,
+    upload_page_querypath: Option<String>,
+    create_folder_querypath: Option<String>,
+    locator: Vec<Ancestor>,
+}
+
+/// A lecturer, tutor or other contact person listed on a course info page
+#[derive(Debug, Clone)]
+pub struct Contact {
+    pub name: String,
+    pub role: Option<String>,
+    pub login: Option<String>,
+    pub email: Option<String>,
+    pub phone: Option<String>,
+    pub profile_querypath: Option<String>,
+    pub mail_querypath: Option<String>,
+}
+
+/// A learning objective of an objective-driven course and the materials grouped under it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearningObjective {
+    pub title: String,
+    pub description: String,
+    pub status: Option<LearningProgress>,
+    /// Materials of locked objectives stay hidden until the initial or qualifying test is passed
+    pub locked: bool,
+    element_indices: Vec<usize>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ID_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static INFO_TAB_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LEARNING_PROGRESS_TAB_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TIMELINE_TAB_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static NEWS_BLOCK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static UPLOAD_FILE_PAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CREATE_FOLDER_PAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static OBJECTIVE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static OBJECTIVE_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static OBJECTIVE_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+const CONTACT_SECTION_TITLES: [&str; 4] =
+    ["Kontakt", "Contact", "Ansprechpartner", "Contact Persons"];
+
+impl IliasElement for Course {
+    fn type_identifier() -> Option<&'static str> {
+        Some("crs")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let id_selector = ID_SELECTOR.get_or_init(|| {
+            Selector::parse(".breadcrumbs span:last-child a").expect("Could not parse selector")
+        });
+        let info_tab_selector = INFO_TAB_SELECTOR.get_or_init(|| {
+            Selector::parse("#tab_info_short a").expect("Could not parse selector")
+        });
+        let learning_progress_tab_selector = LEARNING_PROGRESS_TAB_SELECTOR.get_or_init(|| {
+            Selector::parse("#tab_learning_progress a").expect("Could not parse selector")
+        });
+        let timeline_tab_selector = TIMELINE_TAB_SELECTOR.get_or_init(|| {
+            Selector::parse("#tab_news_timeline a").expect("Could not parse selector")
+        });
+        let news_block_selector = NEWS_BLOCK_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"[id^="block_news_"]"#).expect("Could not parse selector")
+        });
+        let upload_file_page_selector = UPLOAD_FILE_PAGE_SELECTOR.get_or_init(|| {
+            Selector::parse("#il-add-new-item-gl #file").expect("Could not parse selector")
+        });
+        let create_folder_page_selector = CREATE_FOLDER_PAGE_SELECTOR.get_or_init(|| {
+            Selector::parse("#il-add-new-item-gl #fold").expect("Could not parse selector")
+        });
+        let id_regex = ID_REGEX.get_or_init(|| {
+            Regex::new(r"(ref_id=|crs/|crs_)(?<id>\d+)").expect("Could not parse regex")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+        let breadcrumb_link = element
+            .select(id_selector)
+            .next()
+            .whatever_context("Could not find link in breadcrumbs")?
+            .attr("href")
+            .whatever_context("Link missing href attribute")?;
+        let id = id_regex
+            .captures(breadcrumb_link)
+            .whatever_context(format!("Could not find id in {breadcrumb_link}"))?["id"]
+            .parse()?;
+
+        let elements = parse_container_elements(element, ilias_client)?;
+        let sections = ContainerSection::parse_all(element);
+        let objectives = LearningObjective::parse_all(element);
+        let news = element
+            .select(news_block_selector)
+            .flat_map(NewsItem::parse_block)
+            .collect();
+
+        let info_querypath = element
+            .select(info_tab_selector)
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(str::to_string);
+        let learning_progress_querypath = element
+            .select(learning_progress_tab_selector)
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(str::to_string);
+        let timeline_querypath = element
+            .select(timeline_tab_selector)
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(str::to_string);
+        let upload_page_querypath = element
+            .select(upload_file_page_selector)
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(str::to_string);
+        let create_folder_querypath = element
+            .select(create_folder_page_selector)
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(str::to_string);
+
+        let course = Course {
+            name,
+            description,
+            id,
+            elements,
+            sections,
+            objectives,
+            news,
+            info: Reference::from_optional_querypath(info_querypath),
+            learning_progress: Reference::from_optional_querypath(learning_progress_querypath),
+            timeline: Reference::from_optional_querypath(timeline_querypath),
+            upload_page_querypath,
+            create_folder_querypath,
+            locator: parse_locator(element),
+        };
+        debug!("Course: {:?}", course);
+
+        Ok(course)
+    }
+}
+
+impl IliasContainer for Course {
+    fn children(&self, _ilias_client: &IliasClient) -> Result<Vec<FolderElement>, Whatever> {
+        Ok(self.elements.clone())
+    }
+}
+
+impl Course {
+    /// The containers above the course, starting at the repository root
+    pub fn path(&self) -> &[Ancestor] {
+        &self.locator
+    }
+
+    /// Uploads files named like the local files, only possible for users that may edit the course
+    pub fn upload_files(
+        &self,
+        ilias_client: &IliasClient,
+        files: &[NamedLocalFile],
+    ) -> Result<(), Whatever> {
+        self.upload_files_with_progress(ilias_client, files, |_, _| {})
+    }
+
+    /// Uploads files while reporting the progress of each file after every chunk
+    pub fn upload_files_with_progress(
+        &self,
+        ilias_client: &IliasClient,
+        files: &[NamedLocalFile],
+        progress_hook: impl FnMut(&NamedLocalFile, &TransferProgress),
+    ) -> Result<(), Whatever> {
+        let uploads = files
+            .iter()
+            .map(|file| FileUpload {
+                file,
+                title: &file.name,
+                description: "",
+            })
+            .collect::<Vec<_>>();
+        upload_to_container(
+            ilias_client,
+            self.upload_page_querypath.as_deref(),
+            &uploads,
+            progress_hook,
+        )
+    }
+
+    /// Creates a folder, only possible for users that may edit the course
+    pub fn create_folder(
+        &self,
+        ilias_client: &IliasClient,
+        title: &str,
+        description: &str,
+    ) -> Result<FolderElement, Whatever> {
+        create_folder_in(
+            ilias_client,
+            self.create_folder_querypath.as_deref(),
+            title,
+            description,
+        )
+    }
+
+    /// Creates a file object with the given title and description, like "Add New Item → File"
+    pub fn upload_file(
+        &self,
+        ilias_client: &IliasClient,
+        file: &NamedLocalFile,
+        title: &str,
+        description: &str,
+    ) -> Result<(), Whatever> {
+        upload_to_container(
+            ilias_client,
+            self.upload_page_querypath.as_deref(),
+            &[FileUpload {
+                file,
+                title,
+                description,
+            }],
+            |_, _| {},
+        )
+    }
+
+    pub fn get_info(
+        &mut self,
+        ilias_client: &IliasClient,
+    ) -> Result<Option<&InfoScreen>, Whatever> {
+        self.info
+            .resolve_cached(ilias_client)
+            .whatever_context("Could not parse course info page")
+    }
+
+    /// The learning progress of the objects in the course, if the course tracks it
+    pub fn learning_progress(
+        &mut self,
+        ilias_client: &IliasClient,
+    ) -> Result<Option<&LearningProgressOverview>, Whatever> {
+        self.learning_progress
+            .resolve_cached(ilias_client)
+            .whatever_context("Could not parse learning progress of course")
+    }
+
+    /// All announcements of the course, if it has the news timeline enabled
+    pub fn timeline(
+        &mut self,
+        ilias_client: &IliasClient,
+    ) -> Result<Option<&NewsTimeline>, Whatever> {
+        self.timeline
+            .resolve_cached(ilias_client)
+            .whatever_context("Could not parse news timeline of course")
+    }
+
+    pub fn get_contacts(&mut self, ilias_client: &IliasClient) -> Result<Vec<Contact>, Whatever> {
+        Ok(self
+            .get_info(ilias_client)?
+            .map(Contact::from_info_screen)
+            .unwrap_or_default())
+    }
+
+    pub fn elements_with_sections(&self) -> impl Iterator<Item = (Option<&str>, &FolderElement)> {
+        ContainerSection::attach(&self.sections, &self.elements)
+    }
+
+    /// The materials of an objective of this course
+    pub fn objective_elements<'a>(
+        &'a self,
+        objective: &'a LearningObjective,
+    ) -> impl Iterator<Item = &'a FolderElement> {
+        objective
+            .element_indices
+            .iter()
+            .filter_map(|&index| self.elements.get(index))
+    }
+
+    /// Fetches all groups of the course, e.g. to pick a tutorial group with free places
+    pub fn get_groups(&self, ilias_client: &IliasClient) -> Result<Vec<Group>, Whatever> {
+        self.elements
+            .iter()
+            .filter_map(|element| match element {
+                FolderElement::Group { id, .. } => Some(id),
+                _ => None,
+            })
+            .map(|&id| {
+                Reference::<Group>::from_optional_querypath(Group::querypath_from_id(id))
+                    .resolve(ilias_client)
+                    .whatever_context(format!("Could not parse group {id}"))
+            })
+            .collect()
+    }
+}
+
+impl LearningObjective {
+    fn parse_all(element: ElementRef) -> Vec<LearningObjective> {
+        let objective_selector = OBJECTIVE_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilLOObjective, .ilContainerObjectives .il_VAccordionInnerContainer")
+                .expect("Could not parse selector")
+        });
+        let objective_title_selector = OBJECTIVE_TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilLOObjectiveTitle, .il_VAccordionHead")
+                .expect("Could not parse selector")
+        });
+        let objective_description_selector = OBJECTIVE_DESCRIPTION_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilLOObjectiveDescription, .il_Description")
+                .expect("Could not parse selector")
+        });
+
+        element
+            .select(objective_selector)
+            .filter_map(|objective| {
+                let title_element = objective.select(objective_title_selector).next()?;
+                let text = objective.text().collect::<String>().to_lowercase();
+                let element_indices = element_indices_in(element, objective);
+
+                Some(LearningObjective {
+                    title: title_element.text().collect::<String>().trim().to_string(),
+                    description: objective
+                        .select(objective_description_selector)
+                        .next()
+                        .map(|description| {
+                            description.text().collect::<String>().trim().to_string()
+                        })
+                        .unwrap_or_default(),
+                    status: LearningProgress::from_element(title_element),
+                    locked: element_indices.is_empty()
+                        && (text.contains("gesperrt") || text.contains("locked")),
+                    element_indices,
+                })
+            })
+            .collect()
+    }
+}
+
+impl Contact {
+    pub fn from_info_screen(info: &InfoScreen) -> Vec<Contact> {
+        let Some(section) = info.section(&CONTACT_SECTION_TITLES) else {
+            return vec![];
+        };
+
+        let mut contacts = vec![];
+        for property in &section.properties {
+            contacts.extend(Self::from_profile_links(property));
+        }
+
+        // The general course contact is a set of plain text properties instead of user links
+        if let Some(name) = section.property(&["Name", "Kontakt Name", "Contact Name"]) {
+            let value_of = |names: &[&str]| {
+                section
+                    .property(names)
+                    .map(|property| property.value.clone())
+                    .filter(|value| !value.is_empty())
+            };
+            contacts.push(Contact {
+                name: name.value.clone(),
+                role: value_of(&["Verantwortlichkeit", "Responsibility"]),
+                login: None,
+                email: value_of(&["E-Mail", "Email"]),
+                phone: value_of(&["Telefon", "Phone"]),
+                profile_querypath: None,
+                mail_querypath: None,
+            });
+        }
+
+        contacts
+    }
+
+    fn from_profile_links(property: &InfoProperty) -> Vec<Contact> {
+        let mut contacts: Vec<Contact> = vec![];
+        for link in &property.links {
+            let lowercase_querypath = link.querypath.to_lowercase();
+            if lowercase_querypath.contains("ilpublicuserprofilegui")
+                || lowercase_querypath.contains("target=usr_")
+                || lowercase_querypath.contains("/usr/")
+            {
+                contacts.push(Contact {
+                    name: link.text.clone(),
+                    role: Some(property.name.clone()),
+                    login: None,
+                    email: None,
+                    phone: None,
+                    profile_querypath: Some(link.querypath.clone()),
+                    mail_querypath: None,
+                });
+            } else if lowercase_querypath.contains("ilmailgui")
+                || lowercase_querypath.contains("rcp_to=")
+            {
+                // Mail links follow the profile link of the person they belong to
+                if let Some(contact) = contacts.last_mut() {
+                    contact.login = Self::mail_recipient(&link.querypath);
+                    contact.mail_querypath = Some(link.querypath.clone());
+                }
+            }
+        }
+        contacts
+    }
+
+    fn mail_recipient(querypath: &str) -> Option<String> {
+        Url::parse(ILIAS_URL)
+            .ok()?
+            .join(querypath)
+            .ok()?
+            .query_pairs()
+            .find(|(key, _)| key == "rcp_to")
+            .map(|(_, login)| login.into_owned())
+    }
+}
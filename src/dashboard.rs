@@ -0,0 +1,148 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+use log::debug;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::Whatever;
+
+use super::{
+    IliasElement, client::IliasClient, id::RefId, parse_date, querypath_from_href, type_from_icon,
+};
+
+/// The personal dashboard (`ilDashboardGUI`) that users land on after login
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Dashboard {
+    pub favourites: Vec<DashboardItem>,
+    pub news: Vec<DashboardItem>,
+    pub appointments: Vec<DashboardItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DashboardItem {
+    pub title: String,
+    pub description: String,
+    /// The ILIAS type of the linked object (e.g. `crs`), guessed from its icon
+    pub type_identifier: Option<String>,
+    pub date: Option<DateTime<Local>>,
+    pub querypath: Option<String>,
+}
+
+static FAVOURITES_BLOCK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static NEWS_BLOCK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CALENDAR_BLOCK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_ICON_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_PROPERTY_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for Dashboard {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let favourites_block_selector = FAVOURITES_BLOCK_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"[id^="block_pditems_"]"#).expect("Could not parse selector")
+        });
+        let news_block_selector = NEWS_BLOCK_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"[id^="block_pdnews_"]"#).expect("Could not parse selector")
+        });
+        let calendar_block_selector = CALENDAR_BLOCK_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"[id^="block_pdcal_"]"#).expect("Could not parse selector")
+        });
+
+        let favourites = element
+            .select(favourites_block_selector)
+            .flat_map(Self::parse_block_items)
+            .collect();
+        let news = element
+            .select(news_block_selector)
+            .flat_map(Self::parse_block_items)
+            .collect();
+        let appointments = element
+            .select(calendar_block_selector)
+            .flat_map(Self::parse_block_items)
+            .collect();
+
+        let dashboard = Dashboard {
+            favourites,
+            news,
+            appointments,
+        };
+        debug!("Dashboard: {:?}", dashboard);
+
+        Ok(dashboard)
+    }
+}
+
+impl Dashboard {
+    pub const QUERYPATH: &str = "ilias.php?baseClass=ilDashboardGUI&cmd=show";
+
+    pub fn fetch(ilias_client: &IliasClient) -> Result<Dashboard, Whatever> {
+        let page = ilias_client.get_querypath(Self::QUERYPATH)?;
+        Dashboard::parse(page.root_element(), ilias_client)
+    }
+
+    fn parse_block_items(block: ElementRef) -> Vec<DashboardItem> {
+        let item_selector = ITEM_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item, .il_ContainerListItem").expect("Could not parse selector")
+        });
+        let item_title_selector = ITEM_TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-title, .il_ContainerItemTitle")
+                .expect("Could not parse selector")
+        });
+        let item_description_selector = ITEM_DESCRIPTION_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-description, .il_Description")
+                .expect("Could not parse selector")
+        });
+        let item_icon_selector = ITEM_ICON_SELECTOR.get_or_init(|| {
+            Selector::parse("img.icon, img.ilListItemIcon").expect("Could not parse selector")
+        });
+        let item_property_selector = ITEM_PROPERTY_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-value, .il_ItemProperty")
+                .expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        block
+            .select(item_selector)
+            .filter_map(|item| {
+                let title_element = item.select(item_title_selector).next()?;
+                let querypath = title_element
+                    .select(link_selector)
+                    .next()
+                    .or(Some(title_element).filter(|title| title.attr("href").is_some()))
+                    .and_then(|link| link.attr("href"))
+                    .map(querypath_from_href);
+
+                Some(DashboardItem {
+                    title: title_element.text().collect::<String>().trim().to_string(),
+                    description: item
+                        .select(item_description_selector)
+                        .next()
+                        .map(|description| {
+                            description.text().collect::<String>().trim().to_string()
+                        })
+                        .unwrap_or_default(),
+                    type_identifier: item
+                        .select(item_icon_selector)
+                        .next()
+                        .and_then(|icon| icon.attr("src"))
+                        .and_then(type_from_icon),
+                    date: item.select(item_property_selector).find_map(|property| {
+                        parse_date(property.text().collect::<String>().trim()).ok()
+                    }),
+                    querypath,
+                })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,285 @@
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+
+use log::debug;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement, client::IliasClient, file::File, id::RefId, querypath_from_href, table::Table,
+};
+
+/// An ILIAS data collection (`dcl`), e.g. for sign-up lists or equipment inventories
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DataCollection {
+    pub name: String,
+    pub description: String,
+    pub id: String,
+    pub tables: Vec<DataTableSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataTableSummary {
+    pub id: String,
+    pub title: String,
+}
+
+/// All records of a data collection table, collected from every page of the record list
+#[derive(Debug)]
+pub struct DataTable {
+    /// The fields shown in the record list, in their order
+    pub columns: Vec<String>,
+    pub records: Vec<DataRecord>,
+}
+
+#[derive(Debug)]
+pub struct DataRecord {
+    pub values: HashMap<String, DataValue>,
+    pub detail_querypath: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum DataValue {
+    Text(String),
+    File(File),
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PERMALINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TABLE_OPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static RECORD_TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TABLE_NAVIGATION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+const ACTION_COLUMNS: [&str; 2] = ["Aktionen", "Actions"];
+
+impl IliasElement for DataCollection {
+    fn type_identifier() -> Option<&'static str> {
+        Some("dcl")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let permalink_selector = PERMALINK_SELECTOR.get_or_init(|| {
+            Selector::parse("#current_perma_link").expect("Could not parse selector")
+        });
+        let table_option_selector = TABLE_OPTION_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"select[name="table_id"] option[value]"#)
+                .expect("Could not parse selector")
+        });
+        let id_regex = ID_REGEX.get_or_init(|| {
+            Regex::new(r"(ref_id=|dcl/|dcl_)(?<id>\d+)").expect("Could not parse regex")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+        let permalink = element
+            .select(permalink_selector)
+            .next()
+            .and_then(|permalink| permalink.attr("value"))
+            .whatever_context("Could not find permalink")?;
+        let id = id_regex
+            .captures(permalink)
+            .whatever_context(format!("Could not find id in {permalink}"))?["id"]
+            .to_string();
+
+        // Collections with a single visible table do not show the table switch
+        let tables = element
+            .select(table_option_selector)
+            .map(|option| DataTableSummary {
+                id: option.attr("value").unwrap_or_default().to_string(),
+                title: option.text().collect::<String>().trim().to_string(),
+            })
+            .collect();
+
+        let data_collection = DataCollection {
+            name,
+            description,
+            id,
+            tables,
+        };
+        debug!("Data collection: {:?}", data_collection);
+
+        Ok(data_collection)
+    }
+}
+
+impl DataCollection {
+    fn records_querypath(&self, table_id: Option<&str>) -> String {
+        let querypath = format!(
+            "ilias.php?baseClass=ilrepositorygui&cmdClass=ildclrecordlistgui&cmd=listRecords&ref_id={}",
+            self.id
+        );
+        match table_id {
+            Some(table_id) => format!("{querypath}&table_id={table_id}"),
+            None => querypath,
+        }
+    }
+
+    /// Fetches all records of the table, the default table if `table` is `None`
+    pub fn get_table(
+        &self,
+        ilias_client: &IliasClient,
+        table: Option<&DataTableSummary>,
+    ) -> Result<DataTable, Whatever> {
+        let record_table_selector = RECORD_TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let table_navigation_selector = TABLE_NAVIGATION_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilTableNav a[href]").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        let mut data_table = DataTable {
+            columns: vec![],
+            records: vec![],
+        };
+        let mut unvisited_querypaths =
+            vec![self.records_querypath(table.map(|table| table.id.as_str()))];
+        let mut visited_querypaths = HashSet::new();
+        while let Some(querypath) = unvisited_querypaths.pop() {
+            if !visited_querypaths.insert(querypath.clone()) {
+                continue;
+            }
+            let page = ilias_client
+                .get_querypath(&querypath)
+                .whatever_context("Could not get data collection records")?;
+
+            for html_table in page.select(record_table_selector) {
+                let table = Table::parse(html_table);
+                let columns = table
+                    .headers()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, header)| {
+                        !header.is_empty()
+                            && !ACTION_COLUMNS.iter().any(|name| header.starts_with(name))
+                    })
+                    .collect::<Vec<_>>();
+                if columns.is_empty() {
+                    continue;
+                }
+                if data_table.columns.is_empty() {
+                    data_table.columns = columns
+                        .iter()
+                        .map(|(_, header)| header.to_string())
+                        .collect();
+                }
+
+                for row in &table.rows {
+                    let detail_querypath = row
+                        .iter()
+                        .flat_map(|cell| cell.select(link_selector))
+                        .filter_map(|link| link.attr("href"))
+                        .find(|href| href.contains("renderRecord"))
+                        .map(querypath_from_href);
+                    if detail_querypath.is_some()
+                        && data_table
+                            .records
+                            .iter()
+                            .any(|record| record.detail_querypath == detail_querypath)
+                    {
+                        continue;
+                    }
+
+                    let values = columns
+                        .iter()
+                        .filter_map(|&(index, column)| {
+                            let cell = row.get(index)?;
+                            Some((column.clone(), DataValue::parse(*cell, link_selector)))
+                        })
+                        .collect();
+                    data_table.records.push(DataRecord {
+                        values,
+                        detail_querypath,
+                    });
+                }
+            }
+
+            for link in page.select(table_navigation_selector) {
+                let querypath = querypath_from_href(link.attr("href").unwrap_or_default());
+                if !visited_querypaths.contains(&querypath) {
+                    unvisited_querypaths.push(querypath);
+                }
+            }
+        }
+        debug!("Data collection table: {:?}", data_table);
+
+        Ok(data_table)
+    }
+}
+
+impl DataValue {
+    fn parse(cell: ElementRef, link_selector: &Selector) -> DataValue {
+        let text = cell.text().collect::<String>().trim().to_string();
+        let download_link = cell.select(link_selector).find(|link| {
+            link.attr("href")
+                .is_some_and(|href| href.contains("sendFile") || href.contains("deliver"))
+        });
+        match download_link {
+            Some(link) => DataValue::File(File {
+                name: link.text().collect::<String>().trim().to_string(),
+                description: String::new(),
+                date: None,
+                download_querypath: link.attr("href").map(querypath_from_href),
+                id: None,
+                delivered_id: None,
+                license: None,
+                size: None,
+                uploader: None,
+            }),
+            None => DataValue::Text(text),
+        }
+    }
+
+    pub fn as_text(&self) -> &str {
+        match self {
+            DataValue::Text(text) => text,
+            DataValue::File(file) => &file.name,
+        }
+    }
+}
+
+impl DataRecord {
+    pub fn value(&self, column: &str) -> Option<&DataValue> {
+        self.values.get(column)
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &File> {
+        self.values.values().filter_map(|value| match value {
+            DataValue::File(file) => Some(file),
+            DataValue::Text(_) => None,
+        })
+    }
+}
@@ -0,0 +1,157 @@
+use std::{
+    fmt::{self, Debug},
+    fs::{self, File},
+    io::{self, BufReader, BufWriter, Write},
+    iter,
+    path::{Path, PathBuf},
+};
+
+use age::{Decryptor, Encryptor, x25519};
+use log::debug;
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use super::local_file::files_below;
+
+/// Encrypts synced and mirrored content at rest with [age](https://age-encryption.org), so
+/// copies on shared or cloud-synced machines can only be read with the identity.
+///
+/// One identity per course keeps courses apart, see
+/// [`sync_encrypted`](crate::sync::sync_encrypted).
+pub struct Encryption {
+    identity: x25519::Identity,
+}
+
+impl Debug for Encryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Encryption")
+            .field("recipient", &self.identity.to_public().to_string())
+            .finish()
+    }
+}
+
+impl Encryption {
+    /// Appended to the names of encrypted files
+    pub const EXTENSION: &str = "age";
+
+    pub fn new(identity: x25519::Identity) -> Encryption {
+        Encryption { identity }
+    }
+
+    /// Reads the identity from a key file as written by `age-keygen`
+    pub fn from_identity_file(path: &Path) -> Result<Encryption, Whatever> {
+        let keys = fs::read_to_string(path).whatever_context("Could not read identity file")?;
+        let key = keys
+            .lines()
+            .map(str::trim)
+            .find(|line| line.starts_with("AGE-SECRET-KEY-"))
+            .whatever_context(format!("{} contains no age identity", path.display()))?;
+        match key.parse() {
+            Ok(identity) => Ok(Encryption::new(identity)),
+            Err(error) => whatever!("Could not parse age identity: {error}"),
+        }
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>, Whatever> {
+        age::encrypt(&self.identity.to_public(), plaintext).whatever_context("Could not encrypt")
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Whatever> {
+        age::decrypt(&self.identity, ciphertext).whatever_context("Could not decrypt")
+    }
+
+    /// Where the encrypted copy of `path` goes
+    pub fn encrypted_path(path: &Path) -> PathBuf {
+        let mut name = path.file_name().unwrap_or_default().to_os_string();
+        name.push(".");
+        name.push(Self::EXTENSION);
+        path.with_file_name(name)
+    }
+
+    /// Encrypts the file `from` into `to` without holding it in memory
+    pub fn encrypt_file(&self, from: &Path, to: &Path) -> Result<(), Whatever> {
+        let recipient = self.identity.to_public();
+        let encryptor = Encryptor::with_recipients(iter::once(&recipient as _))
+            .whatever_context("Could not set up encryption")?;
+        let mut plaintext =
+            File::open(from).whatever_context(format!("Could not open {}", from.display()))?;
+        let encrypted =
+            File::create(to).whatever_context(format!("Could not create {}", to.display()))?;
+        let mut writer = encryptor
+            .wrap_output(BufWriter::new(encrypted))
+            .whatever_context("Could not write encryption header")?;
+        io::copy(&mut plaintext, &mut writer)
+            .whatever_context(format!("Could not encrypt {}", from.display()))?;
+        writer
+            .finish()
+            .and_then(|mut encrypted| encrypted.flush())
+            .whatever_context(format!("Could not finish {}", to.display()))
+    }
+
+    /// Decrypts the file `from` into `to` without holding it in memory
+    pub fn decrypt_file(&self, from: &Path, to: &Path) -> Result<(), Whatever> {
+        let encrypted =
+            File::open(from).whatever_context(format!("Could not open {}", from.display()))?;
+        let decryptor = Decryptor::new_buffered(BufReader::new(encrypted))
+            .whatever_context(format!("{} is not encrypted with age", from.display()))?;
+        let mut reader = decryptor
+            .decrypt(iter::once(&self.identity as _))
+            .whatever_context(format!("Could not decrypt {}", from.display()))?;
+        let mut plaintext =
+            File::create(to).whatever_context(format!("Could not create {}", to.display()))?;
+        io::copy(&mut reader, &mut plaintext)
+            .whatever_context(format!("Could not decrypt {}", from.display()))?;
+        Ok(())
+    }
+
+    /// Runs `write` on a private staging directory and encrypts everything it put there into
+    /// `directory`, so no plaintext ever lands in `directory`. Paths that `write` returns point
+    /// into the staging directory, which is removed afterwards.
+    ///
+    /// This is how mirrors are encrypted, e.g. with
+    /// [`HtmlLearningModule::archive`](crate::html_learning_module::HtmlLearningModule::archive).
+    pub fn encrypt_into<T>(
+        &self,
+        directory: &Path,
+        write: impl FnOnce(&Path) -> Result<T, Whatever>,
+    ) -> Result<T, Whatever> {
+        let staging = tempfile::Builder::new()
+            .prefix("ilias-encrypt-")
+            .tempdir()
+            .whatever_context("Could not create staging directory")?;
+        let result = write(staging.path())?;
+        self.encrypt_directory(staging.path(), directory)?;
+        Ok(result)
+    }
+
+    /// Encrypts every file below `from` into the same layout below `to`
+    pub fn encrypt_directory(&self, from: &Path, to: &Path) -> Result<(), Whatever> {
+        for relative in files_below(from)? {
+            let target = Self::encrypted_path(&to.join(&relative));
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).whatever_context("Could not create directory")?;
+            }
+            self.encrypt_file(&from.join(&relative), &target)?;
+            debug!("Encrypted {}", relative.display());
+        }
+        Ok(())
+    }
+
+    /// Decrypts every encrypted file below `from` into the same layout below `to`, e.g. to
+    /// browse an encrypted mirror
+    pub fn decrypt_directory(&self, from: &Path, to: &Path) -> Result<(), Whatever> {
+        for relative in files_below(from)? {
+            if relative
+                .extension()
+                .is_none_or(|extension| extension != Self::EXTENSION)
+            {
+                continue;
+            }
+            let target = to.join(relative.with_extension(""));
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent).whatever_context("Could not create directory")?;
+            }
+            self.decrypt_file(&from.join(&relative), &target)?;
+        }
+        Ok(())
+    }
+}
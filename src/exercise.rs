@@ -5,20 +5,33 @@ use grades::Grades;
 use log::debug;
 use regex::Regex;
 use scraper::{selectable::Selectable, ElementRef, Selector};
+use serde::{Deserialize, Serialize};
 use snafu::{OptionExt, ResultExt, Whatever};
 
 pub mod assignment;
+pub mod constraints;
+pub mod diff;
 pub mod grades;
+pub mod history;
+pub mod peer_review;
 
-use super::{client::IliasClient, reference::Reference, IliasElement};
+use super::{
+    IliasElement,
+    client::IliasClient,
+    id::RefId,
+    locator::{Ancestor, parse_locator},
+    reference::Reference,
+};
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct Exercise {
     pub name: String,
+    pub id: Option<RefId>,
     pub description: String,
     pub assignments: Vec<Assignment>,
     pub grades: Reference<Grades>,
+    locator: Vec<Ancestor>,
 }
 
 static ASSIGNMENT_SELECTOR: OnceLock<Selector> = OnceLock::new();
@@ -26,6 +39,7 @@ static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static GRADES_TAB_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static DEFAULT_MODE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PERMALINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
 
 static BASE_GRADES_QUERYPATH_REGEX: OnceLock<Regex> = OnceLock::new();
 
@@ -34,7 +48,7 @@ impl IliasElement for Exercise {
         Some("exc")
     }
 
-    fn querypath_from_id(id: &str) -> Option<String> {
+    fn querypath_from_id(id: RefId) -> Option<String> {
         Some(format!(
             "goto.php?target={}_{}&client_id=produktiv",
             Self::type_identifier().unwrap(),
@@ -61,6 +75,10 @@ impl IliasElement for Exercise {
             .expect("Could not parse selector")
         });
 
+        let permalink_selector = PERMALINK_SELECTOR.get_or_init(|| {
+            Selector::parse("#current_perma_link").expect("Could not parse selector")
+        });
+
         let base_grades_querypath_regex = BASE_GRADES_QUERYPATH_REGEX
             .get_or_init(|| Regex::new(r".*ref_id=\d+").expect("Could not parse regex"));
 
@@ -96,24 +114,58 @@ impl IliasElement for Exercise {
         } else {
             None
         };
+        let id = element
+            .select(permalink_selector)
+            .next()
+            .and_then(|permalink| permalink.attr("value"))
+            .and_then(RefId::from_querypath);
         let mut assignments = vec![];
-        for assignment in element.select(assignment_selector) {
-            let assignment = Assignment::parse(assignment, ilias_client)
+        for (index, assignment) in element.select(assignment_selector).enumerate() {
+            let mut assignment = Assignment::parse(assignment, ilias_client)
                 .whatever_context("Could not parse assignment")?;
+            assignment.index = index;
+            assignment.exercise_id = id;
             assignments.push(assignment);
         }
         debug!("Assignments: {:?}", assignments);
 
         Ok(Exercise {
             name,
+            id,
             description,
             assignments,
             grades: Reference::from_optional_querypath(grades_tab_querypath),
+            locator: parse_locator(element),
         })
     }
 }
 
 impl Exercise {
+    /// The containers above the exercise, starting at the repository root
+    pub fn path(&self) -> &[Ancestor] {
+        &self.locator
+    }
+
+    /// The assignment names, with the id or position appended to names used more than once
+    pub fn unique_assignment_names(&self) -> Vec<String> {
+        self.assignments
+            .iter()
+            .map(|assignment| {
+                let duplicated = self
+                    .assignments
+                    .iter()
+                    .filter(|other| other.name == assignment.name)
+                    .count()
+                    > 1;
+                match (duplicated, &assignment.id) {
+                    (false, _) => assignment.name.clone(),
+                    (true, Some(id)) => format!("{} ({id})", assignment.name),
+                    (true, None) => format!("{} ({})", assignment.name, assignment.index + 1),
+                }
+            })
+            .collect()
+    }
+
     pub fn get_grades(&mut self, ilias_client: &IliasClient) -> Option<&Grades> {
         let grades = &mut self.grades;
         match grades {
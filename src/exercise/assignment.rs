@@ -1,30 +1,101 @@
-use std::sync::OnceLock;
+use std::{
+    collections::{HashMap, HashSet},
+    mem,
+    sync::OnceLock,
+};
 
 use chrono::{DateTime, Local};
-use log::debug;
+use log::{debug, info, warn};
 use regex::Regex;
-use reqwest::multipart::Form;
-use scraper::{selectable::Selectable, ElementRef, Selector};
-use snafu::{OptionExt, ResultExt, Whatever};
+use reqwest::{multipart::Form, Url};
+use scraper::{selectable::Selectable, ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{whatever, OptionExt, ResultExt, Whatever};
 
 use crate::reference::Reference;
 
+use super::{
+    constraints::UploadConstraints,
+    history::SubmissionHistory,
+    peer_review::{PeerFeedback, PeerReview},
+};
+
 use super::super::{
-    client::{AddFileWithFilename, IliasClient},
+    chunked_upload::{chunk_size, upload_chunked},
+    client::{AddFileWithFilename, IliasClient, TransferProgress, UploadProgress},
     file::File,
-    local_file::NamedLocalFile,
-    parse_date, IliasElement,
+    form::HtmlForm,
+    id::{ObjId, RefId},
+    local_file::{NamedLocalFile, file_name_component},
+    page::Page,
+    parse_date, querypath_from_href, table::Table, IliasElement, ILIAS_URL,
 };
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct Assignment {
     pub name: String,
+    /// The ILIAS-internal id (`ass_id`), unique even if assignments share a name
+    pub id: Option<ObjId>,
+    /// Position of the assignment in its exercise
+    pub index: usize,
+    /// The ref_id of the exercise the assignment belongs to
+    pub exercise_id: Option<RefId>,
     pub instructions: Option<String>,
+    /// The instructions with their formatting, links and images, see [`Page::blocks`]
+    pub instructions_page: Option<Page>,
     pub submission_start_date: Option<DateTime<Local>>,
+    /// The deadline of the assignment, see [`Assignment::deadline`] for the one that applies
     pub submission_end_date: Option<DateTime<Local>>,
+    /// A deadline tutors granted the user instead of the general one
+    pub individual_end_date: Option<DateTime<Local>>,
+    /// The end of the period after the deadline in which late submissions are accepted
+    pub grace_period_end_date: Option<DateTime<Local>>,
+    /// The instruction files from the files section of the assignment
     pub attachments: Vec<File>,
+    /// Files only linked in the instruction text
+    pub additional_material: Vec<File>,
+    pub grading_status: GradingStatus,
+    pub mark: Option<String>,
+    /// The textual feedback a tutor left on the submission
+    pub tutor_feedback: Option<String>,
+    /// Files tutors uploaded as feedback, e.g. corrected submissions
+    pub feedback_files: Vec<File>,
+    /// Files of the sample solution, usually published after the deadline
+    pub sample_solution: Vec<File>,
+    pub upload_constraints: UploadConstraints,
     submission: Reference<AssignmentSubmission>,
+    peer_review: Reference<PeerReview>,
+    received_peer_feedback_querypath: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DeadlineState {
+    NotStarted,
+    Open,
+    /// The deadline passed, but late submissions are still accepted
+    GracePeriod,
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GradingStatus {
+    NotGraded,
+    Passed,
+    Failed,
+}
+
+impl GradingStatus {
+    pub fn from_text(text: &str) -> GradingStatus {
+        let text = text.trim().to_lowercase();
+        if text.contains("nicht bestanden") || text.contains("failed") {
+            GradingStatus::Failed
+        } else if text.contains("bestanden") || text.contains("passed") {
+            GradingStatus::Passed
+        } else {
+            GradingStatus::NotGraded
+        }
+    }
 }
 
 static PANEL_SELECTOR: OnceLock<Selector> = OnceLock::new();
@@ -37,13 +108,21 @@ static ATTACHMENT_ROW_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static SUBMISSION_PAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static INFO_PROPERTY_VALUE_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static INFO_PROPERTY_KEY_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DETAIL_TEXT_EDIT_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PEER_REVIEW_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static RECEIVED_PEER_FEEDBACK_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PANEL_ROW_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ASSIGNMENT_ID_INPUT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static ASSIGNMENT_ID_REGEX: OnceLock<Regex> = OnceLock::new();
 
 impl IliasElement for Assignment {
     fn type_identifier() -> Option<&'static str> {
         Some("ass")
     }
 
-    fn querypath_from_id(_: &str) -> Option<String> {
+    fn querypath_from_id(_: RefId) -> Option<String> {
         None
     }
 
@@ -88,7 +167,26 @@ impl IliasElement for Assignment {
                 .or_else(|_| Self::get_value_for_keys(&properties, &["Beendet am", "Ended On"]))
                 .and_then(|date| parse_date(date.trim()))
                 .ok();
-        debug!("Start: {submission_start_date:?}; End: {submission_end_date:?}");
+        let individual_end_date = Self::get_value_for_keys(
+            &properties,
+            &["Individueller Abgabetermin", "Individual Deadline"],
+        )
+        .and_then(|date| parse_date(date.trim()))
+        .ok();
+        let grace_period_end_date = Self::get_value_for_keys(
+            &properties,
+            &[
+                "Nachfrist",
+                "Ende der Nachfrist",
+                "Grace Period",
+                "Grace Period Ends",
+            ],
+        )
+        .and_then(|date| parse_date(date.trim()))
+        .ok();
+        debug!(
+            "Start: {submission_start_date:?}; End: {submission_end_date:?}; Individual end: {individual_end_date:?}; Grace period end: {grace_period_end_date:?}"
+        );
 
         let detail_querypath = element
             .select(name_selector)
@@ -96,57 +194,88 @@ impl IliasElement for Assignment {
             .whatever_context("Did not find name element for detail querypath")?
             .attr("href")
             .whatever_context("Could not get href attr for detail querypath")?;
+        let assignment_id_regex = ASSIGNMENT_ID_REGEX
+            .get_or_init(|| Regex::new(r"ass_id=(?<id>\d+)").expect("Could not parse regex"));
+        let id = assignment_id_regex
+            .captures(detail_querypath)
+            .and_then(|captures| captures["id"].parse().ok());
         let detail_page = ilias_client
             .get_querypath(detail_querypath)
             .whatever_context("Could not get detail html")?;
 
-        let panels: Vec<_> = detail_page.select(panel_selector).collect();
-
-        let instruction_panel = panels.iter().find(|panel| {
-            panel
-                .select(panel_name_selector)
+        // Some views only carry the id in their forms
+        let id = id.or_else(|| {
+            let assignment_id_input_selector = ASSIGNMENT_ID_INPUT_SELECTOR.get_or_init(|| {
+                Selector::parse(r#"input[name="ass_id"][value]"#).expect("Could not parse selector")
+            });
+            detail_page
+                .select(assignment_id_input_selector)
                 .next()
-                .map(|name| {
-                    ["Arbeitsanweisung", "Work Instructions"]
-                        .contains(&name.text().collect::<String>().as_str())
-                })
-                .unwrap_or(false)
+                .and_then(|input| input.attr("value"))
+                .and_then(|id| id.parse().ok())
         });
-        let instructions = if let Some(panel) = instruction_panel {
-            let body = panel
-                .select(panel_body_selector)
-                .next()
-                .and_then(|body| body.child_elements().next())
-                .and_then(|body| body.child_elements().next())
-                .whatever_context("Could not get body for instruction panel")?;
-            Some(body.text().collect::<String>().trim().to_string())
-        } else {
-            None
-        };
+
+        let panels: Vec<_> = detail_page.select(panel_selector).collect();
+
+        let upload_constraints = UploadConstraints::from_text(
+            &detail_page
+                .root_element()
+                .text()
+                .collect::<Vec<_>>()
+                .join(" "),
+        );
+        debug!("Upload constraints: {upload_constraints:?}");
+
+        let instruction_panel = Self::find_panel(
+            &panels,
+            panel_name_selector,
+            &["Arbeitsanweisung", "Work Instructions"],
+        );
+        let instruction_body = instruction_panel
+            .map(|panel| {
+                panel
+                    .select(panel_body_selector)
+                    .next()
+                    .and_then(|body| body.child_elements().next())
+                    .and_then(|body| body.child_elements().next())
+                    .whatever_context("Could not get body for instruction panel")
+            })
+            .transpose()?;
+        let instructions =
+            instruction_body.map(|body| body.text().collect::<String>().trim().to_string());
+        let instructions_page = instruction_body.map(|body| Page::from_content(name.clone(), body));
         debug!("Instructions: {instructions:?}");
 
-        let attachment_panel = panels.iter().find(|panel| {
-            panel
-                .select(panel_name_selector)
-                .next()
-                .map(|name| {
-                    ["Dateien", "Files"].contains(&name.text().collect::<String>().as_str())
-                })
-                .unwrap_or(false)
-        });
+        let attachment_panel =
+            Self::find_panel(&panels, panel_name_selector, &["Dateien", "Files"]);
         let attachments = if let Some(panel) = attachment_panel {
             let file_rows: Vec<_> = panel.select(attachment_row_selector).collect();
             let mut attachments = vec![];
 
-            for row in &file_rows[0..file_rows.len() - 2] {
+            for row in &file_rows[0..file_rows.len().saturating_sub(2)] {
                 let mut children = row.child_elements();
-                let filename = children
+                // The name may be followed by a description in the same column
+                let mut name_lines = children
                     .next()
                     .whatever_context("Could not get attachment filename")?
                     .text()
-                    .collect::<String>()
-                    .trim()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty());
+                let filename = name_lines
+                    .next()
+                    .whatever_context("Attachment has no filename")?
                     .to_string();
+                // The size is listed below the name or in a column of its own, like the date
+                let (sizes, description): (Vec<_>, Vec<_>) =
+                    name_lines.partition(|line| File::size_from_property(line).is_some());
+                let size = sizes
+                    .first()
+                    .and_then(|size| File::size_from_property(size))
+                    .or_else(|| {
+                        row.child_elements().find_map(|column| {
+                            File::size_from_property(&column.text().collect::<String>())
+                        })
+                    });
                 let download_querypath = children
                     .next()
                     .and_then(|div| div.child_elements().next())
@@ -158,10 +287,16 @@ impl IliasElement for Assignment {
 
                 let file = File {
                     name: filename,
-                    description: "".to_string(),
+                    description: description.join(" "),
                     download_querypath: Some(download_querypath.to_string()),
-                    date: None,
+                    date: row.child_elements().find_map(|column| {
+                        parse_date(column.text().collect::<String>().trim()).ok()
+                    }),
                     id: None,
+                    delivered_id: None,
+                    license: None,
+                    size,
+                    uploader: None,
                 };
 
                 attachments.push(file);
@@ -171,55 +306,355 @@ impl IliasElement for Assignment {
         } else {
             vec![]
         };
-        debug!("Attachments: {attachments:?}");
+        let mut additional_material: Vec<File> = vec![];
+        if let Some(body) = instruction_body {
+            for file in Self::linked_files(body) {
+                let querypath = file.download_querypath.as_deref().map(querypath_from_href);
+                if !attachments
+                    .iter()
+                    .chain(&additional_material)
+                    .any(|attachment| {
+                        attachment
+                            .download_querypath
+                            .as_deref()
+                            .map(querypath_from_href)
+                            == querypath
+                    })
+                {
+                    additional_material.push(file);
+                }
+            }
+        }
+        debug!("Attachments: {attachments:?}; Additional material: {additional_material:?}");
+
+        // Results are shown in their own panel once the tutors graded the submission
+        let grading_panel = Self::find_panel(
+            &panels,
+            panel_name_selector,
+            &["Ergebnis", "Bewertung", "Result", "Grading"],
+        );
+        let grading_value = |keys: &[&str]| {
+            grading_panel
+                .and_then(|panel| Self::panel_value(panel, keys))
+                .map(|value| value.text().collect::<String>().trim().to_string())
+                .or_else(|| Self::get_value_for_keys(&properties, keys).ok())
+                .filter(|value| !value.is_empty())
+        };
+        let grading_status = grading_value(&["Status"])
+            .map(|status| GradingStatus::from_text(&status))
+            .unwrap_or(GradingStatus::NotGraded);
+        let mark = grading_value(&["Note", "Mark"]);
+        let tutor_feedback = grading_value(&["Kommentar", "Rückmeldung", "Comment", "Feedback"]);
+        debug!("Grading: {grading_status:?}; Mark: {mark:?}; Feedback: {tutor_feedback:?}");
 
-        let submission_page_querypath = detail_page.select(submission_page_selector).next()
+        let feedback_file_panel = Self::find_panel(
+            &panels,
+            panel_name_selector,
+            &["Feedback-Dateien", "Feedback Files"],
+        );
+        let feedback_files = feedback_file_panel
+            .map(Self::panel_files)
+            .unwrap_or_default();
+        debug!("Feedback files: {feedback_files:?}");
+
+        let sample_solution_panel = Self::find_panel(
+            &panels,
+            panel_name_selector,
+            &["Musterlösung", "Sample Solution"],
+        );
+        let sample_solution = sample_solution_panel
+            .map(Self::panel_files)
+            .unwrap_or_default();
+        debug!("Sample solution: {sample_solution:?}");
+
+        // Text assignments are edited directly from the assignment instead of a submission tab
+        let detail_text_edit_link_selector = DETAIL_TEXT_EDIT_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#"a[href*="editAssignmentText"], button[data-action*="editAssignmentText"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+        let submission_page_querypath = detail_page
+            .select(submission_page_selector)
+            .next()
             .and_then(|link| link.attr("href"))
+            .or_else(|| {
+                detail_page
+                    .select(detail_text_edit_link_selector)
+                    .next()
+                    .and_then(|link| link.attr("href").or_else(|| link.attr("data-action")))
+            })
             .map(|querypath| querypath.to_string());
 
+        let peer_review_link_selector = PEER_REVIEW_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#"a[href*="cmd=editPeerReview&"], button[data-action*="cmd=editPeerReview&"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+        let received_peer_feedback_link_selector =
+            RECEIVED_PEER_FEEDBACK_LINK_SELECTOR.get_or_init(|| {
+                Selector::parse(
+                    r#"a[href*="showReceivedPeerReview"], button[data-action*="showReceivedPeerReview"]"#,
+                )
+                .expect("Could not parse selector")
+            });
+        let peer_review_querypath = detail_page
+            .select(peer_review_link_selector)
+            .next()
+            .and_then(|link| link.attr("href").or_else(|| link.attr("data-action")))
+            .map(querypath_from_href);
+        let received_peer_feedback_querypath = detail_page
+            .select(received_peer_feedback_link_selector)
+            .next()
+            .and_then(|link| link.attr("href").or_else(|| link.attr("data-action")))
+            .map(querypath_from_href);
+        debug!(
+            "Peer review: {peer_review_querypath:?}; Received: {received_peer_feedback_querypath:?}"
+        );
+
         Ok(Assignment {
             name,
+            id,
+            index: 0,
+            exercise_id: None,
             instructions,
+            instructions_page,
             submission_start_date,
             submission_end_date,
+            individual_end_date,
+            grace_period_end_date,
             attachments,
+            additional_material,
+            grading_status,
+            mark,
+            tutor_feedback,
+            feedback_files,
+            sample_solution,
+            upload_constraints,
             submission: Reference::from_optional_querypath(submission_page_querypath),
+            peer_review: Reference::from_optional_querypath(peer_review_querypath),
+            received_peer_feedback_querypath,
         })
     }
 }
 
 impl Assignment {
+    /// The deadline that applies to the user, preferring an individual one
+    pub fn deadline(&self) -> Option<DateTime<Local>> {
+        self.individual_end_date.or(self.submission_end_date)
+    }
+
+    pub fn deadline_state(&self) -> DeadlineState {
+        let now = Local::now();
+        if self.submission_start_date.is_some_and(|date| date > now) {
+            DeadlineState::NotStarted
+        } else if self.deadline().is_none_or(|date| date >= now) {
+            DeadlineState::Open
+        } else if self.grace_period_end_date.is_some_and(|date| date >= now) {
+            DeadlineState::GracePeriod
+        } else {
+            DeadlineState::Closed
+        }
+    }
+
+    /// Whether files can be submitted, possibly as late submission
     pub fn is_active(&self) -> bool {
-        self.submission_end_date
-            .map_or(true, |date| date >= Local::now())
-            && self
-                .submission_start_date
-                .map_or(true, |date| date <= Local::now())
+        matches!(
+            self.deadline_state(),
+            DeadlineState::Open | DeadlineState::GracePeriod
+        )
+    }
+
+    /// The instruction files followed by the additional material
+    pub fn all_attachments(&self) -> impl Iterator<Item = &File> {
+        self.attachments.iter().chain(&self.additional_material)
+    }
+
+    /// A key that stays the same across runs and differs between assignments of an exercise,
+    /// e.g. for naming local mirrors
+    pub fn unique_key(&self) -> String {
+        match self.id {
+            Some(id) => id.to_string(),
+            None => format!("{}_{}", self.index, self.name),
+        }
+    }
+
+    /// A link that opens exactly this assignment, e.g. for notifications
+    pub fn permalink(&self) -> Option<String> {
+        Some(format!(
+            "{ILIAS_URL}/goto.php?target=exc_{}_{}&client_id=produktiv",
+            self.exercise_id?, self.id?
+        ))
     }
 
     pub fn get_submission(
         &mut self,
         ilias_client: &IliasClient,
     ) -> Result<Option<&AssignmentSubmission>, Whatever> {
-        let submission = &mut self.submission;
-        let res = match submission {
-            Reference::Unavailable => None,
-            &mut Reference::Resolved(ref submission) => Some(submission),
-            Reference::Unresolved(querypath) => {
-                let ass_sub = AssignmentSubmission::parse_submissions_page(
-                    ilias_client
-                        .get_querypath(querypath)
-                        .whatever_context("Could not get submission page")?
-                        .root_element(),
-                    ilias_client,
-                )
-                .whatever_context("Could not parse submission page")?;
-                *submission = Reference::Resolved(ass_sub);
+        let submission = self
+            .submission
+            .resolve_cached_with(ilias_client, |submission, querypath| {
+                submission.querypath = querypath.to_string();
+                submission.upload_constraints = mem::take(&mut submission.upload_constraints)
+                    .merge(self.upload_constraints.clone());
+            })
+            .whatever_context("Could not parse submission page")?;
+        Ok(submission.map(|submission| &*submission))
+    }
 
-                submission.try_get_resolved()
-            }
+    /// The peers to review, if the assignment has peer feedback enabled
+    pub fn get_peer_review(
+        &mut self,
+        ilias_client: &IliasClient,
+    ) -> Result<Option<&PeerReview>, Whatever> {
+        self.peer_review
+            .resolve_cached(ilias_client)
+            .whatever_context("Could not parse peer review page")
+    }
+
+    /// Feedback other students gave on the own submission
+    pub fn received_peer_feedback(
+        &self,
+        ilias_client: &IliasClient,
+    ) -> Result<Vec<PeerFeedback>, Whatever> {
+        let Some(querypath) = &self.received_peer_feedback_querypath else {
+            return Ok(vec![]);
         };
-        Ok(res)
+        let page = ilias_client
+            .get_querypath(querypath)
+            .whatever_context("Could not get received peer feedback")?;
+        Ok(PeerFeedback::parse_received(page.root_element()))
+    }
+
+    /// Records the current submission into `history`, starting a new one if none is given
+    pub fn record_submission_history(
+        &mut self,
+        ilias_client: &IliasClient,
+        history: Option<SubmissionHistory>,
+    ) -> Result<SubmissionHistory, Whatever> {
+        let mut history = history.unwrap_or_else(|| SubmissionHistory::new(&self.name));
+        if let Some(submission) = self.get_submission(ilias_client)? {
+            history.record(submission);
+        }
+        Ok(history)
+    }
+
+    /// Files that are linked in the instruction text instead of being attached
+    fn linked_files(instructions: ElementRef) -> Vec<File> {
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        instructions
+            .select(link_selector)
+            .filter_map(|link| {
+                let href = link.attr("href")?;
+                let querypath = querypath_from_href(href);
+                let lowercase_querypath = querypath.to_lowercase();
+                let is_download = lowercase_querypath.contains("target=file_")
+                    || lowercase_querypath.contains("/file/")
+                    || lowercase_querypath.contains("cmd=sendfile")
+                    || lowercase_querypath.contains("cmd=download")
+                    || lowercase_querypath.contains("deliverfile");
+                if !is_download || !querypath_is_ilias(href) {
+                    return None;
+                }
+
+                let text = link.text().collect::<String>().trim().to_string();
+                Some(File {
+                    name: if text.is_empty() {
+                        querypath.clone()
+                    } else {
+                        text
+                    },
+                    description: link.attr("title").unwrap_or_default().trim().to_string(),
+                    download_querypath: Some(querypath),
+                    date: None,
+                    id: None,
+                    delivered_id: None,
+                    license: None,
+                    size: None,
+                    uploader: None,
+                })
+            })
+            .collect()
+    }
+
+    fn find_panel<'a>(
+        panels: &[ElementRef<'a>],
+        panel_name_selector: &Selector,
+        names: &[&str],
+    ) -> Option<ElementRef<'a>> {
+        panels
+            .iter()
+            .find(|panel| {
+                panel
+                    .select(panel_name_selector)
+                    .next()
+                    .map(|name| names.contains(&name.text().collect::<String>().trim()))
+                    .unwrap_or(false)
+            })
+            .copied()
+    }
+
+    /// The value next to a label in the rows of a panel
+    fn panel_value<'a>(panel: ElementRef<'a>, keys: &[&str]) -> Option<ElementRef<'a>> {
+        let panel_row_selector = PANEL_ROW_SELECTOR
+            .get_or_init(|| Selector::parse(".row").expect("Could not parse selector"));
+
+        panel.select(panel_row_selector).find_map(|row| {
+            let mut children = row.child_elements();
+            let key = children.next()?.text().collect::<String>();
+            if keys.contains(&key.trim()) {
+                children.next()
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Files listed in the rows of a panel, with the name next to the download link
+    fn panel_files(panel: ElementRef) -> Vec<File> {
+        let panel_row_selector = PANEL_ROW_SELECTOR
+            .get_or_init(|| Selector::parse(".row").expect("Could not parse selector"));
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        panel
+            .select(panel_row_selector)
+            .filter_map(|row| {
+                let mut children = row.child_elements();
+                let mut name_lines = children
+                    .next()?
+                    .text()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty());
+                let name = name_lines.next()?.to_string();
+                let (sizes, description): (Vec<_>, Vec<_>) =
+                    name_lines.partition(|line| File::size_from_property(line).is_some());
+                let download_querypath = children
+                    .next()?
+                    .select(link_selector)
+                    .next()?
+                    .attr("href")?;
+
+                Some(File {
+                    name,
+                    description: description.join(" "),
+                    download_querypath: Some(querypath_from_href(download_querypath)),
+                    date: row.child_elements().find_map(|column| {
+                        parse_date(column.text().collect::<String>().trim()).ok()
+                    }),
+                    id: None,
+                    delivered_id: None,
+                    license: None,
+                    size: sizes
+                        .first()
+                        .and_then(|size| File::size_from_property(size)),
+                    uploader: None,
+                })
+            })
+            .collect()
     }
 
     fn get_value_element_for_keys<'a>(
@@ -261,25 +696,62 @@ impl Assignment {
     }
 }
 
-#[derive(Debug)]
+/// Relative links and absolute links to the ILIAS installation itself
+fn querypath_is_ilias(href: &str) -> bool {
+    match Url::parse(href) {
+        Ok(url) => Url::parse(ILIAS_URL).is_ok_and(|ilias| ilias.host_str() == url.host_str()),
+        Err(_) => true,
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
 pub struct AssignmentSubmission {
     pub submissions: Vec<File>,
-    delete_querypath: String,
-    upload_querypath: String,
+    /// Comments of tutors on single submitted files, by delivered id
+    pub file_comments: HashMap<u64, String>,
+    /// Delivered ids of files submitted after the deadline
+    pub late_file_ids: HashSet<u64>,
+    pub upload_constraints: UploadConstraints,
+    /// The team the submission is shared with, for team assignments
+    pub team: Option<Team>,
+    /// The handed in text, for assignments submitted as text instead of files
+    pub text: Option<String>,
+    querypath: String,
+    delete_querypath: Option<String>,
+    upload_querypath: Option<String>,
+    /// Set if the upload takes files in chunks of this size
+    upload_chunk_size: Option<u64>,
+    text_querypath: Option<String>,
 }
 
 static UPLOAD_BUTTON_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static CONTENT_FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static FILE_ROW_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FILE_HEADER_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DELIVERED_CHECKBOX_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TEAM_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TEXT_EDIT_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static SOURCE_TAG_SELECTOR: OnceLock<Selector> = OnceLock::new();
 
+static TEXT_EDITOR_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ALERT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
 static UPLOAD_QUERYPATH_REGEX: OnceLock<Regex> = OnceLock::new();
 
-impl AssignmentSubmission {
-    fn parse_submissions_page(
-        submission_page: ElementRef,
-        ilias_client: &IliasClient,
-    ) -> Result<AssignmentSubmission, Whatever> {
+/// The textarea of the text submission editor
+const TEXT_FIELD: &str = "atxt";
+
+impl IliasElement for AssignmentSubmission {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    /// The page does not tell its own querypath, which the caller has to fill in
+    fn parse(submission_page: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever> {
         let upload_button_selector = UPLOAD_BUTTON_SELECTOR.get_or_init(|| {
             Selector::parse(".navbar-form button").expect("Could not parse selector")
         });
@@ -289,16 +761,108 @@ impl AssignmentSubmission {
         let source_tag_selector = SOURCE_TAG_SELECTOR.get_or_init(|| {
             Selector::parse("body > script:not([src])").expect("Could not parse selector")
         });
-        let file_row_selector = FILE_ROW_SELECTOR.get_or_init(|| {
-            Selector::parse("#ilContentContainer form tbody tr").expect("Could not parse selector")
-        });
+        let file_row_selector = FILE_ROW_SELECTOR
+            .get_or_init(|| Selector::parse("tbody tr").expect("Could not parse selector"));
         let upload_querypath_regex = UPLOAD_QUERYPATH_REGEX.get_or_init(|| {
             Regex::new(r#"'(?P<querypath>ilias\.php\?[a-zA-Z=&0-9:_]+cmd=upload[a-zA-Z=&0-9:_]+)'"#)
                 .expect("Could not parse regex")
         });
 
-        let file_rows = submission_page.select(file_row_selector);
+        let file_header_selector = FILE_HEADER_SELECTOR
+            .get_or_init(|| Selector::parse("thead th").expect("Could not parse selector"));
+        let delivered_checkbox_selector = DELIVERED_CHECKBOX_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"input[name="delivered[]"]"#).expect("Could not parse selector")
+        });
+        let team_link_selector = TEAM_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#"a[href*="submissionScreenTeam"], button[data-action*="submissionScreenTeam"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+        let text_edit_link_selector = TEXT_EDIT_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#"a[href*="editAssignmentText"], button[data-action*="editAssignmentText"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+
+        let text_editor_selector = TEXT_EDITOR_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"textarea[name="atxt"]"#).expect("Could not parse selector")
+        });
+
+        let team = submission_page
+            .select(team_link_selector)
+            .next()
+            .and_then(|link| link.attr("href").or_else(|| link.attr("data-action")))
+            .map(|querypath| Team::fetch(ilias_client, querypath))
+            .transpose()?;
+        debug!("Team: {team:?}");
+
+        let text_querypath = submission_page
+            .select(text_edit_link_selector)
+            .next()
+            .and_then(|link| link.attr("href").or_else(|| link.attr("data-action")))
+            .map(querypath_from_href)
+            .or_else(|| {
+                submission_page
+                    .select(text_editor_selector)
+                    .next()
+                    .and_then(|_| submission_page.select(content_form_selector).next())
+                    .and_then(|form| form.attr("action"))
+                    .map(querypath_from_href)
+            });
+        if let Some(text_querypath) = text_querypath {
+            let text = Self::get_text_editor(ilias_client, &text_querypath)?
+                .fields
+                .into_iter()
+                .find(|(name, _)| name == TEXT_FIELD)
+                .map(|(_, text)| text)
+                .filter(|text| !text.trim().is_empty());
+            debug!("Submitted text: {text:?}");
+
+            return Ok(AssignmentSubmission {
+                submissions: vec![],
+                file_comments: HashMap::new(),
+                late_file_ids: HashSet::new(),
+                upload_constraints: UploadConstraints::default(),
+                team,
+                text,
+                querypath: String::new(),
+                delete_querypath: None,
+                upload_querypath: None,
+                upload_chunk_size: None,
+                text_querypath: Some(text_querypath),
+            });
+        }
+
+        // Team assignments show the team members in a form of their own
+        let content_forms: Vec<_> = submission_page.select(content_form_selector).collect();
+        let file_form = content_forms
+            .iter()
+            .find(|form| form.select(delivered_checkbox_selector).next().is_some())
+            .or_else(|| {
+                content_forms.iter().find(|form| {
+                    !form
+                        .attr("action")
+                        .unwrap_or_default()
+                        .to_lowercase()
+                        .contains("ilexsubmissionteamgui")
+                })
+            })
+            .copied()
+            .whatever_context("Did not find deletion form")?;
+
+        let comment_column = file_form.select(file_header_selector).position(|header| {
+            let header = header.text().collect::<String>();
+            ["Kommentar", "Comment", "Feedback"]
+                .iter()
+                .any(|name| header.trim().starts_with(name))
+        });
+
+        let file_rows = file_form.select(file_row_selector);
         let mut uploaded_files = vec![];
+        let mut file_comments = HashMap::new();
+        let mut late_file_ids = HashSet::new();
         for row in file_rows.filter(|&row| row.child_elements().count() > 1) {
             let mut children = row.child_elements();
 
@@ -309,7 +873,22 @@ impl AssignmentSubmission {
                 .next()
                 .whatever_context("Did not find checkbox")?
                 .attr("value")
+                .and_then(|id| id.parse::<u64>().ok())
                 .whatever_context("Did not find id")?;
+            if let Some(comment) = comment_column
+                .and_then(|column| row.child_elements().nth(column))
+                .map(|cell| cell.text().collect::<String>().trim().to_string())
+                .filter(|comment| !comment.is_empty())
+            {
+                file_comments.insert(id, comment);
+            }
+            // Late files are marked next to their submission date
+            if row.child_elements().skip(2).any(|cell| {
+                let text = cell.text().collect::<String>().to_lowercase();
+                text.contains("verspätet") || text.contains("(late)")
+            }) {
+                late_file_ids.insert(id);
+            }
             let file_name = children
                 .next()
                 .whatever_context("Did not find second column")?
@@ -317,11 +896,15 @@ impl AssignmentSubmission {
                 .collect();
             let submission_date = loop {
                 let parsed_date = parse_date(
-                    &children
+                    children
                         .next()
                         .whatever_context("Did not find date column")?
                         .text()
-                        .collect::<String>(),
+                        .collect::<String>()
+                        .split('(')
+                        .next()
+                        .unwrap_or_default()
+                        .trim(),
                 );
                 match parsed_date {
                     Ok(date) => break date,
@@ -338,20 +921,21 @@ impl AssignmentSubmission {
                 .whatever_context("Did not find href attribute")?;
 
             let file = File {
-                id: Some(id.to_string()),
+                id: None,
+                delivered_id: Some(id),
                 name: file_name,
                 description: String::new(),
                 date: Some(submission_date),
                 download_querypath: Some(download_querypath.to_string()),
+                license: None,
+                size: None,
+                uploader: None,
             };
 
             uploaded_files.push(file);
         }
 
-        let delete_querypath = submission_page
-            .select(content_form_selector)
-            .next()
-            .whatever_context("Did not find deletion form")?
+        let delete_querypath = file_form
             .value()
             .attr("action")
             .whatever_context("Did not find action attribute for delete querypath")?
@@ -376,56 +960,442 @@ impl AssignmentSubmission {
             .whatever_context("Could not find upload querypath")?["querypath"]
             .to_string();
         debug!("Upload querypath: {}", upload_querypath);
+        let upload_chunk_size = chunk_size(&script);
+        debug!("Upload chunk size: {upload_chunk_size:?}");
+
+        let upload_constraints = UploadConstraints::from_text(
+            &upload_page
+                .root_element()
+                .text()
+                .collect::<Vec<_>>()
+                .join(" "),
+        )
+        .merge(UploadConstraints::from_text(
+            &submission_page.text().collect::<Vec<_>>().join(" "),
+        ));
+        debug!("Upload constraints: {upload_constraints:?}");
 
         Ok(AssignmentSubmission {
             submissions: uploaded_files,
-            delete_querypath,
-            upload_querypath,
+            file_comments,
+            late_file_ids,
+            upload_constraints,
+            team,
+            text: None,
+            querypath: String::new(),
+            delete_querypath: Some(delete_querypath),
+            upload_querypath: Some(upload_querypath),
+            upload_chunk_size,
+            text_querypath: None,
+        })
+    }
+}
+
+impl AssignmentSubmission {
+    fn fetch(
+        ilias_client: &IliasClient,
+        querypath: &str,
+    ) -> Result<AssignmentSubmission, Whatever> {
+        let submission_page = ilias_client
+            .get_querypath(querypath)
+            .whatever_context("Could not get submission page")?;
+        let submission = Self::parse(submission_page.root_element(), ilias_client)
+            .whatever_context("Could not parse submission page")?;
+        Ok(AssignmentSubmission {
+            querypath: querypath.to_string(),
+            ..submission
         })
     }
 
+    pub fn tutor_comment(&self, file: &File) -> Option<&str> {
+        self.file_comments
+            .get(&file.delivered_id?)
+            .map(String::as_str)
+    }
+
+    pub fn is_late(&self, file: &File) -> bool {
+        file.delivered_id
+            .is_some_and(|id| self.late_file_ids.contains(&id))
+    }
+
+    /// Deletes submitted files and returns the submission as listed afterwards
     pub fn delete_files(
         &self,
         ilias_client: &IliasClient,
         files: &[&File],
-    ) -> Result<(), Whatever> {
+    ) -> Result<AssignmentSubmission, Whatever> {
         let mut form_args = files
             .iter()
-            .map(|&file| file.id.clone().expect("Files to delete must have an id"))
-            .map(|id| ("delivered[]", id))
+            .map(|&file| {
+                let id = file
+                    .delivered_id
+                    .expect("Files to delete must have a delivered id");
+                ("delivered[]", id.to_string())
+            })
             .collect::<Vec<_>>();
         form_args.push(("cmd[deleteDelivered]", String::from("Löschen")));
 
-        ilias_client
-            .post_querypath_form(&self.delete_querypath, &form_args)
+        let response = ilias_client
+            .post_querypath_form(
+                self.delete_querypath
+                    .as_ref()
+                    .whatever_context("Files can not be deleted from a text submission")?,
+                &form_args,
+            )
             .whatever_context("Could not post assignment deletion form")?;
-        Ok(())
+        let response_page = Html::parse_document(&ilias_client.get_text(response)?);
+
+        // Some installations ask for a confirmation, whose form carries the files as hidden fields
+        let response_page = match HtmlForm::find(&response_page, |form| {
+            form.has_field("delivered[]") && form.has_button("delete")
+        }) {
+            Some(confirmation) => {
+                debug!("Confirming deletion");
+                let response = confirmation
+                    .submit(ilias_client, Some("delete"))
+                    .whatever_context("Could not confirm deletion of submitted files")?;
+                Html::parse_document(&ilias_client.get_text(response)?)
+            }
+            None => response_page,
+        };
+        if response_page.html().contains("alert-danger") {
+            whatever!("Ilias rejected the deletion of submitted files");
+        }
+
+        let mut submission = Self::fetch(ilias_client, &self.querypath)?;
+        submission.upload_constraints = submission
+            .upload_constraints
+            .merge(self.upload_constraints.clone());
+        if let Some(file) = files.iter().find(|file| {
+            submission
+                .submissions
+                .iter()
+                .any(|submitted| submitted.delivered_id == file.delivered_id)
+        }) {
+            whatever!("{} is still submitted after deleting it", file.name);
+        }
+        info!("Deleted {} submitted files", files.len());
+        Ok(submission)
     }
 
+    /// Deletes all submitted files
+    pub fn clear(&self, ilias_client: &IliasClient) -> Result<AssignmentSubmission, Whatever> {
+        let files = self.submissions.iter().collect::<Vec<_>>();
+        if files.is_empty() {
+            return Self::fetch(ilias_client, &self.querypath);
+        }
+        self.delete_files(ilias_client, &files)
+    }
+
+    /// Uploads files and returns the submission as listed afterwards, with the new file ids
     pub fn upload_files(
         &self,
         ilias_client: &IliasClient,
         files: &[NamedLocalFile],
-    ) -> Result<(), Whatever> {
-        let mut form = Form::new();
-
-        for (index, file_data) in files.iter().enumerate() {
-            form = form
-                .file_with_name(
-                    format!("deliver[{index}]"),
-                    ilias_client.construct_file_part(&file_data.path),
-                    file_data.name.clone(),
-                )?
-                .text("cmd[uploadFile]", "Hochladen")
-                .text("ilfilehash", "aaaa");
+    ) -> Result<AssignmentSubmission, Whatever> {
+        self.upload_files_with_progress(ilias_client, files, |_, _| {})
+    }
+
+    /// Uploads files while reporting the progress of each file after every chunk
+    pub fn upload_files_with_progress(
+        &self,
+        ilias_client: &IliasClient,
+        files: &[NamedLocalFile],
+        mut progress_hook: impl FnMut(&NamedLocalFile, &TransferProgress),
+    ) -> Result<AssignmentSubmission, Whatever> {
+        self.upload_constraints
+            .validate(files, self.submissions.len())
+            .whatever_context("Files do not satisfy the upload constraints")?;
+
+        let upload_querypath = self
+            .upload_querypath
+            .as_ref()
+            .whatever_context("Files can not be uploaded to a text submission")?;
+        debug!("Upload querypath: {}", upload_querypath);
+
+        let mut chunked = vec![];
+        let responses = match self.upload_chunk_size {
+            Some(chunk_size) => {
+                let mut responses = vec![];
+                for file in files {
+                    let (response, content) = upload_chunked(
+                        ilias_client,
+                        upload_querypath,
+                        file,
+                        chunk_size,
+                        |chunk| {
+                            Form::new()
+                                .part("deliver[0]", chunk)
+                                .text("cmd[uploadFile]", "Hochladen")
+                                .text("ilfilehash", "aaaa")
+                        },
+                        |transfer| progress_hook(file, transfer),
+                    )
+                    .whatever_context(format!("Could not upload {}", file.name))?;
+                    responses.push(response);
+                    chunked.push((file, content));
+                }
+                responses
+            }
+            None => {
+                let mut form = Form::new();
+                let mut progress = UploadProgress::new();
+
+                for (index, file_data) in files.iter().enumerate() {
+                    form = form
+                        .file_with_name(
+                            format!("deliver[{index}]"),
+                            ilias_client
+                                .construct_file_part_with_progress(&file_data.path, &mut progress),
+                            file_data.name.clone(),
+                        )?
+                        .text("cmd[uploadFile]", "Hochladen")
+                        .text("ilfilehash", "aaaa");
+                }
+                debug!("Form: {:?}", form);
+
+                let response = ilias_client
+                    .post_querypath_multipart_with_progress(
+                        upload_querypath,
+                        form,
+                        progress,
+                        |index, transfer| progress_hook(&files[index], transfer),
+                    )
+                    .whatever_context("Could not post assignment upload form")?;
+                vec![response]
+            }
+        };
+        let alert_selector = ALERT_SELECTOR
+            .get_or_init(|| Selector::parse(".alert-danger").expect("Could not parse selector"));
+        for response in responses {
+            let response_page = Html::parse_document(&ilias_client.get_text(response)?);
+            if let Some(alert) = response_page.select(alert_selector).next() {
+                whatever!(
+                    "Ilias rejected the upload: {}",
+                    alert.text().collect::<String>().trim()
+                );
+            }
+        }
+
+        let mut submission = Self::fetch(ilias_client, &self.querypath)?;
+        submission.upload_constraints = submission
+            .upload_constraints
+            .merge(self.upload_constraints.clone());
+        // Files of the same name may have been submitted before, so every upload has to add one
+        let count_named = |submission: &AssignmentSubmission, name: &str| {
+            submission
+                .submissions
+                .iter()
+                .filter(|submitted| submitted.name == name)
+                .count()
+        };
+        for file in files {
+            let uploaded = files.iter().filter(|other| other.name == file.name).count();
+            if count_named(&submission, &file.name) < count_named(self, &file.name) + uploaded {
+                whatever!("{} is not submitted after uploading it", file.name);
+            }
+        }
+        // Chunks may have been sent in an earlier run, so check what ILIAS assembled from them
+        for (file, content) in chunked {
+            let mut assembled = false;
+            for submitted in submission
+                .submissions
+                .iter()
+                .filter(|submitted| submitted.name == file.name)
+            {
+                if content.is_assembled_in(ilias_client, submitted)? {
+                    assembled = true;
+                    break;
+                }
+            }
+            if !assembled {
+                whatever!(
+                    "ILIAS assembled {} differently from the uploaded file",
+                    file.name
+                );
+            }
+        }
+        info!("Uploaded {} files", files.len());
+        Ok(submission)
+    }
+
+    /// Replaces the submitted files named like `files` by them, restoring the replaced files if
+    /// the upload fails
+    pub fn replace_files(
+        &self,
+        ilias_client: &IliasClient,
+        files: &[NamedLocalFile],
+    ) -> Result<AssignmentSubmission, Whatever> {
+        let replaced = self
+            .submissions
+            .iter()
+            .filter(|submitted| files.iter().any(|file| file.name == submitted.name))
+            .collect::<Vec<_>>();
+        if replaced.is_empty() {
+            return self.upload_files(ilias_client, files);
+        }
+        self.upload_constraints
+            .validate(files, self.submissions.len() - replaced.len())
+            .whatever_context("Files do not satisfy the upload constraints")?;
+
+        let backup_directory = tempfile::Builder::new()
+            .prefix("ilias-replace-")
+            .tempdir()
+            .whatever_context("Could not create directory for replaced files")?;
+        let mut backups = vec![];
+        for (index, file) in replaced.iter().enumerate() {
+            // Numbered as the same name may be submitted several times
+            let path = backup_directory
+                .path()
+                .join(format!("{index}-{}", file_name_component(&file.name)));
+            ilias_client
+                .download_file(
+                    file.download_querypath
+                        .as_ref()
+                        .whatever_context(format!("{} can not be downloaded", file.name))?,
+                    &path,
+                )
+                .whatever_context(format!("Could not back up {}", file.name))?;
+            backups.push(NamedLocalFile {
+                name: file.name.clone(),
+                path,
+            });
+        }
+
+        let remaining = self.delete_files(ilias_client, &replaced)?;
+        match remaining.upload_files(ilias_client, files) {
+            Ok(submission) => Ok(submission),
+            Err(error) => {
+                warn!("Upload failed, restoring {} replaced files", backups.len());
+                match remaining.restore(ilias_client, &backups) {
+                    Ok(_) => Err(error),
+                    Err(restore_error) => Err(restore_error).whatever_context(format!(
+                        "Could not restore replaced files after: {error}"
+                    )),
+                }
+            }
         }
-        debug!("Form: {:?}", form);
-        debug!("Upload querypath: {}", self.upload_querypath);
+    }
 
-        ilias_client
-            .post_querypath_multipart(&self.upload_querypath, form)
-            .whatever_context("Could not post assignment upload form")?;
+    /// Brings the submission back to this state with the files in `backups` added, deleting
+    /// the files a failed upload submitted in the meantime so they are not listed twice
+    fn restore(
+        &self,
+        ilias_client: &IliasClient,
+        backups: &[NamedLocalFile],
+    ) -> Result<AssignmentSubmission, Whatever> {
+        let mut current = Self::fetch(ilias_client, &self.querypath)?;
+        current.upload_constraints = current
+            .upload_constraints
+            .merge(self.upload_constraints.clone());
+        let partial = current
+            .submissions
+            .iter()
+            .filter(|file| {
+                !self
+                    .submissions
+                    .iter()
+                    .any(|kept| kept.delivered_id == file.delivered_id)
+            })
+            .collect::<Vec<_>>();
+        let current = if partial.is_empty() {
+            current
+        } else {
+            info!("Deleting {} partially uploaded files", partial.len());
+            current.delete_files(ilias_client, &partial)?
+        };
+        current.upload_files(ilias_client, backups)
+    }
+
+    /// Saves the text of a text submission, plain text is converted to paragraphs
+    pub fn submit_text(
+        &self,
+        ilias_client: &IliasClient,
+        html_or_plain: &str,
+    ) -> Result<(), Whatever> {
+        let text_querypath = self
+            .text_querypath
+            .as_ref()
+            .whatever_context("Assignment is not submitted as text")?;
+        let text = if html_or_plain.trim_start().starts_with('<') {
+            html_or_plain.to_string()
+        } else {
+            html_or_plain
+                .split("\n\n")
+                .map(|paragraph| {
+                    let paragraph = paragraph
+                        .trim()
+                        .replace('&', "&amp;")
+                        .replace('<', "&lt;")
+                        .replace('>', "&gt;")
+                        .replace('\n', "<br />");
+                    format!("<p>{paragraph}</p>")
+                })
+                .collect()
+        };
+
+        let mut form = Self::get_text_editor(ilias_client, text_querypath)?;
+        form.set(TEXT_FIELD, &text);
+        let response = form
+            .submit(ilias_client, Some("updateAssignmentText"))
+            .whatever_context("Could not submit text")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected the submitted text");
+        }
+        info!("Submitted text");
         Ok(())
-        // TODO: Maybe push files to submission here
+    }
+
+    fn get_text_editor(ilias_client: &IliasClient, querypath: &str) -> Result<HtmlForm, Whatever> {
+        let editor_page = ilias_client
+            .get_querypath(querypath)
+            .whatever_context("Could not get text editor")?;
+        HtmlForm::find(&editor_page, |form| form.has_field(TEXT_FIELD))
+            .whatever_context("Could not find text editor form")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Team {
+    /// The `team_id` ILIAS gives teams of an assignment
+    pub id: Option<u64>,
+    pub members: Vec<String>,
+}
+
+static TEAM_TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static TEAM_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+const TEAM_MEMBER_COLUMNS: [&str; 1] = ["Name"];
+
+impl Team {
+    fn fetch(ilias_client: &IliasClient, querypath: &str) -> Result<Team, Whatever> {
+        let team_table_selector = TEAM_TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let team_id_regex = TEAM_ID_REGEX
+            .get_or_init(|| Regex::new(r"team_id=(?<id>\d+)").expect("Could not parse regex"));
+
+        let team_page = ilias_client
+            .get_querypath(&querypath_from_href(querypath))
+            .whatever_context("Could not get team page")?;
+        let id = [querypath, &team_page.html()]
+            .into_iter()
+            .find_map(|text| team_id_regex.captures(text))
+            .and_then(|captures| captures["id"].parse().ok());
+
+        let mut members = vec![];
+        for table in team_page.select(team_table_selector) {
+            let table = Table::parse(table);
+            members.extend(
+                table
+                    .rows
+                    .iter()
+                    .filter_map(|row| table.cell_text(row, &TEAM_MEMBER_COLUMNS))
+                    .filter(|member| !member.is_empty()),
+            );
+        }
+
+        Ok(Team { id, members })
     }
 }
@@ -0,0 +1,129 @@
+use std::{fs, sync::OnceLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Whatever, whatever};
+
+use crate::local_file::NamedLocalFile;
+
+/// Limits ILIAS enforces on the files of a submission
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UploadConstraints {
+    pub max_files: Option<usize>,
+    /// Lowercase extensions without dot, all extensions are allowed if empty
+    pub allowed_extensions: Vec<String>,
+    /// In bytes
+    pub max_file_size: Option<u64>,
+}
+
+static MAX_FILES_REGEX: OnceLock<Regex> = OnceLock::new();
+static ALLOWED_EXTENSIONS_REGEX: OnceLock<Regex> = OnceLock::new();
+static MAX_FILE_SIZE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl UploadConstraints {
+    /// Reads the constraints from the text of an info screen or upload form
+    pub fn from_text(text: &str) -> UploadConstraints {
+        let max_files_regex = MAX_FILES_REGEX.get_or_init(|| {
+            Regex::new(
+                r"(?i)(Maximale Anzahl (an |von )?Dateien|Maximum Number of Files)\D*(?<count>\d+)",
+            )
+            .expect("Could not parse regex")
+        });
+        let allowed_extensions_regex = ALLOWED_EXTENSIONS_REGEX.get_or_init(|| {
+            Regex::new(
+                r"(?i)(Erlaubte Dateiendungen|Zulässige Dateiendungen|Allowed File Extensions|Allowed File Types)\s*:?\s*(?<extensions>[a-z0-9]+(\s*,\s*\.?[a-z0-9]+)*)",
+            )
+            .expect("Could not parse regex")
+        });
+        let max_file_size_regex = MAX_FILE_SIZE_REGEX.get_or_init(|| {
+            Regex::new(
+                r"(?i)(Maximal erlaubte Upload-Größe|Maximum Upload Size|Max\. Upload Size)\s*:?\s*(?<size>\d+([.,]\d+)?)\s*(?<unit>[KMG]i?B|B)",
+            )
+            .expect("Could not parse regex")
+        });
+
+        let max_files = max_files_regex
+            .captures(text)
+            .and_then(|captures| captures["count"].parse().ok());
+        let allowed_extensions = allowed_extensions_regex
+            .captures(text)
+            .map(|captures| {
+                captures["extensions"]
+                    .split(',')
+                    .map(|extension| extension.trim().trim_start_matches('.').to_lowercase())
+                    .filter(|extension| !extension.is_empty())
+                    .collect()
+            })
+            .unwrap_or_default();
+        let max_file_size = max_file_size_regex.captures(text).and_then(|captures| {
+            let size: f64 = captures["size"].replace(',', ".").parse().ok()?;
+            let factor = match captures["unit"].to_uppercase().chars().next()? {
+                'K' => 1024.0,
+                'M' => 1024.0 * 1024.0,
+                'G' => 1024.0 * 1024.0 * 1024.0,
+                _ => 1.0,
+            };
+            Some((size * factor) as u64)
+        });
+
+        UploadConstraints {
+            max_files,
+            allowed_extensions,
+            max_file_size,
+        }
+    }
+
+    /// Fills in the constraints `self` does not know from `other`
+    pub fn merge(self, other: UploadConstraints) -> UploadConstraints {
+        UploadConstraints {
+            max_files: self.max_files.or(other.max_files),
+            allowed_extensions: if self.allowed_extensions.is_empty() {
+                other.allowed_extensions
+            } else {
+                self.allowed_extensions
+            },
+            max_file_size: self.max_file_size.or(other.max_file_size),
+        }
+    }
+
+    /// Checks files before they are added to a submission that already holds `submitted` files
+    pub fn validate(&self, files: &[NamedLocalFile], submitted: usize) -> Result<(), Whatever> {
+        if let Some(max_files) = self.max_files
+            && submitted + files.len() > max_files
+        {
+            whatever!(
+                "At most {max_files} files can be submitted, {submitted} are submitted and {} would be added",
+                files.len()
+            );
+        }
+
+        for file in files {
+            let extension = file
+                .name
+                .rsplit_once('.')
+                .map(|(_, extension)| extension.to_lowercase())
+                .unwrap_or_default();
+            if !self.allowed_extensions.is_empty() && !self.allowed_extensions.contains(&extension)
+            {
+                whatever!(
+                    "{} does not have one of the allowed extensions {}",
+                    file.name,
+                    self.allowed_extensions.join(", ")
+                );
+            }
+
+            if let Some(max_file_size) = self.max_file_size {
+                let size = fs::metadata(&file.path)
+                    .whatever_context(format!("Could not get size of {}", file.name))?
+                    .len();
+                if size > max_file_size {
+                    whatever!(
+                        "{} has {size} bytes, but at most {max_file_size} bytes are allowed",
+                        file.name
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
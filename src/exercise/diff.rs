@@ -0,0 +1,114 @@
+use std::{env, fs, path::Path, process};
+
+use chrono::{DateTime, Local};
+use log::debug;
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use crate::{client::IliasClient, file::File, local_file::NamedLocalFile};
+
+use super::assignment::AssignmentSubmission;
+
+/// How local files differ from the files of a submission, see [`AssignmentSubmission::diff`]
+#[derive(Debug, Clone, Default)]
+pub struct SubmissionDiff {
+    /// Local files that are not submitted
+    pub missing: Vec<NamedLocalFile>,
+    /// Local files whose submitted version differs
+    pub outdated: Vec<NamedLocalFile>,
+    /// Submitted files without a local counterpart
+    pub extraneous: Vec<File>,
+}
+
+impl SubmissionDiff {
+    pub fn is_empty(&self) -> bool {
+        self.missing.is_empty() && self.outdated.is_empty() && self.extraneous.is_empty()
+    }
+
+    /// The files to upload to bring the submission up to date
+    pub fn to_upload(&self) -> Vec<NamedLocalFile> {
+        self.missing.iter().chain(&self.outdated).cloned().collect()
+    }
+}
+
+impl AssignmentSubmission {
+    /// Compares local files to the submitted ones by name.
+    ///
+    /// Submitted files are downloaded and compared byte by byte if `compare_content` is set,
+    /// otherwise files changed locally after they were submitted count as outdated.
+    pub fn diff(
+        &self,
+        ilias_client: &IliasClient,
+        files: &[NamedLocalFile],
+        compare_content: bool,
+    ) -> Result<SubmissionDiff, Whatever> {
+        let mut diff = SubmissionDiff {
+            extraneous: self
+                .submissions
+                .iter()
+                .filter(|submitted| !files.iter().any(|file| file.name == submitted.name))
+                .cloned()
+                .collect(),
+            ..Default::default()
+        };
+
+        let download_directory = env::temp_dir().join(format!("ilias-diff-{}", process::id()));
+        for file in files {
+            let Some(submitted) = self
+                .submissions
+                .iter()
+                .find(|submitted| submitted.name == file.name)
+            else {
+                diff.missing.push(file.clone());
+                continue;
+            };
+
+            let outdated = if compare_content {
+                fs::create_dir_all(&download_directory)
+                    .whatever_context("Could not create directory for submitted files")?;
+                let path = download_directory.join(&submitted.name);
+                ilias_client
+                    .download_file(
+                        submitted
+                            .download_querypath
+                            .as_ref()
+                            .whatever_context(format!("{} can not be downloaded", file.name))?,
+                        &path,
+                    )
+                    .whatever_context(format!("Could not download submitted {}", file.name))?;
+                let changed = !same_content(&file.path, &path)?;
+                let _ = fs::remove_file(&path);
+                changed
+            } else {
+                let modified: DateTime<Local> = fs::metadata(&file.path)
+                    .and_then(|metadata| metadata.modified())
+                    .whatever_context(format!("Could not get modification time of {}", file.name))?
+                    .into();
+                submitted.date.is_none_or(|submitted| modified > submitted)
+            };
+            if outdated {
+                diff.outdated.push(file.clone());
+            }
+        }
+        let _ = fs::remove_dir(&download_directory);
+        debug!("Submission diff: {diff:?}");
+
+        Ok(diff)
+    }
+}
+
+fn same_content(local: &Path, remote: &Path) -> Result<bool, Whatever> {
+    let local_size = fs::metadata(local)
+        .whatever_context(format!("Could not get size of {}", local.display()))?
+        .len();
+    let remote_size = fs::metadata(remote)
+        .whatever_context("Could not get size of submitted file")?
+        .len();
+    if local_size != remote_size {
+        return Ok(false);
+    }
+
+    Ok(
+        fs::read(local).whatever_context(format!("Could not read {}", local.display()))?
+            == fs::read(remote).whatever_context("Could not read submitted file")?,
+    )
+}
@@ -1,16 +1,20 @@
 use std::{path::Path, sync::OnceLock};
 
 use base64::Engine;
+use log::info;
 use regex::Regex;
 use scraper::{ElementRef, Html, Selector, selectable::Selectable};
-use snafu::{OptionExt, ResultExt, Whatever};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
 use submission::GradeSubmission;
 
-use crate::{IliasElement, client::IliasClient, reference::Reference};
+use crate::{IliasElement, client::IliasClient, form::HtmlForm, id::RefId, reference::Reference};
+
+use super::assignment::GradingStatus;
 
 pub mod submission;
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct Grades {
     pub assignment_grades: Vec<Reference<GradePage>>,
 }
@@ -39,7 +43,7 @@ impl Grades {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GradePage {
     pub name: String,
     ass_id: String,
@@ -47,20 +51,31 @@ pub struct GradePage {
     pub submissions: Vec<GradeSubmission>,
 }
 
+/// The result a tutor enters for a submission, see [`GradePage::grade`]
+#[derive(Debug, Clone)]
+pub struct Grade {
+    pub status: GradingStatus,
+    pub mark: Option<String>,
+    /// Feedback text shown to the participant
+    pub comment: Option<String>,
+}
+
 static SELECTED_ASSIGNMENT_DROPDOWN_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static TOOLBAR_FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static SUBMISSION_ROW_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static GRADING_TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TABLE_NAVIGATION_SELECTOR: OnceLock<Selector> = OnceLock::new();
 
 impl IliasElement for GradePage {
     fn type_identifier() -> Option<&'static str> {
         None
     }
 
-    fn querypath_from_id(_id: &str) -> Option<String> {
+    fn querypath_from_id(_id: RefId) -> Option<String> {
         None
     }
 
-    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+    fn parse(element: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever> {
         let selected_assignment_dropdown_selector = SELECTED_ASSIGNMENT_DROPDOWN_SELECTOR
             .get_or_init(|| {
                 Selector::parse(r#"select#ass_id option[selected="selected"]"#)
@@ -90,12 +105,27 @@ impl IliasElement for GradePage {
             .whatever_context("Toolbar form had no action")?
             .to_string();
 
-        let mut submissions = vec![];
-        for submission_element in element.select(submission_row_selector) {
-            if let Some(submission) = GradeSubmission::parse(submission_element)
-                .whatever_context("Could not parse submission")?
-            {
-                submissions.push(submission);
+        let table_navigation_selector = TABLE_NAVIGATION_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilTableNav a[href]").expect("Could not parse selector")
+        });
+        let mut submissions: Vec<GradeSubmission> = vec![];
+        let further_pages = ilias_client.get_linked_pages(element, table_navigation_selector)?;
+        let pages = [element]
+            .into_iter()
+            .chain(further_pages.iter().map(Html::root_element));
+        for page in pages {
+            // Every page only has the grading fields of the members listed on it
+            let grading_form = Self::grading_form(page)?;
+            for submission_element in page.select(submission_row_selector) {
+                if let Some(mut submission) = GradeSubmission::parse(submission_element)
+                    .whatever_context("Could not parse submission")?
+                    && !submissions
+                        .iter()
+                        .any(|known| known.identifier == submission.identifier)
+                {
+                    submission.grading_form = grading_form.clone();
+                    submissions.push(submission);
+                }
             }
         }
 
@@ -111,6 +141,22 @@ impl IliasElement for GradePage {
 static NOTIFICATION_ITEM_BUTTON_SELECTOR: OnceLock<Selector> = OnceLock::new();
 
 impl GradePage {
+    /// The form around the grading table of a page
+    fn grading_form(page: ElementRef) -> Result<Option<HtmlForm>, Whatever> {
+        let grading_table_selector = GRADING_TABLE_SELECTOR
+            .get_or_init(|| Selector::parse("table#exc_mem").expect("Could not parse selector"));
+        page.select(grading_table_selector)
+            .next()
+            .and_then(|table| {
+                table
+                    .ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .find(|ancestor| ancestor.value().name() == "form")
+            })
+            .map(HtmlForm::parse)
+            .transpose()
+    }
+
     pub fn download_all_submissions_zip(
         &self,
         ilias_client: &IliasClient,
@@ -152,4 +198,56 @@ impl GradePage {
 
         Ok(())
     }
+
+    /// Saves status, mark and comment of a submission through the grading table
+    pub fn grade(
+        &self,
+        ilias_client: &IliasClient,
+        submission: &GradeSubmission,
+        grade: &Grade,
+    ) -> Result<(), Whatever> {
+        let mut form = submission
+            .grading_form
+            .clone()
+            .whatever_context("Grades can not be entered on this page")?;
+        let member_id = submission
+            .member_id
+            .as_ref()
+            .whatever_context(format!("{} can not be graded", submission.identifier))?;
+
+        let status = match grade.status {
+            GradingStatus::NotGraded => "notgraded",
+            GradingStatus::Passed => "passed",
+            GradingStatus::Failed => "failed",
+        };
+        form.set(&format!("status[{member_id}]"), status);
+        if let Some(mark) = &grade.mark {
+            form.set(&format!("mark[{member_id}]"), mark);
+        }
+        if let Some(comment) = &grade.comment {
+            // Comments are named after assignment and member, e.g. `lcomment_<ass_id>_<member_id>`
+            let comment_field = format!("lcomment_{}_{member_id}", self.ass_id);
+            let member_suffix = format!("_{member_id}");
+            let comment_field = if form.has_field(&comment_field) {
+                comment_field
+            } else {
+                form.fields
+                    .iter()
+                    .map(|(name, _)| name)
+                    .find(|name| name.starts_with("lcomment") && name.ends_with(&member_suffix))
+                    .cloned()
+                    .unwrap_or(comment_field)
+            };
+            form.set(&comment_field, comment);
+        }
+
+        let response = form
+            .submit(ilias_client, Some("saveStatus"))
+            .whatever_context("Could not submit grades")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected grade for {}", submission.identifier);
+        }
+        info!("Graded {} as {status}", submission.identifier);
+        Ok(())
+    }
 }
@@ -4,25 +4,34 @@ use log::debug;
 use regex::Regex;
 use reqwest::multipart::Form;
 use scraper::{ElementRef, Selector, selectable::Selectable};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use snafu::{OptionExt, ResultExt, Whatever, whatever};
 
 use crate::{
     client::{AddFileWithFilename, IliasClient},
+    form::HtmlForm,
+    id::ObjId,
     local_file::NamedLocalFile,
 };
 
 /// A submission of a user or team for an assignment that feedback can be uploaded to.
-#[derive(Debug)]
+#[derive(Debug, Serialize, Deserialize)]
 pub struct GradeSubmission {
     pub identifier: String,
     pub file_feedback_querypath: String,
+    /// The user the grading fields of the row are named after, e.g. `mark[<id>]`. Users are
+    /// objects too, so this is their obj_id.
+    pub member_id: Option<ObjId>,
+    /// The grading form of the table page the submission is listed on
+    pub(crate) grading_form: Option<HtmlForm>,
 }
 
 static DROPDOWN_ACTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static TEAM_ID_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static SIGNIN_NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static GRADING_INPUT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static MEMBER_ID_REGEX: OnceLock<Regex> = OnceLock::new();
 
 static UPLOAD_FEEDBACK_FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static POST_UPLOAD_FEEDBACK_FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
@@ -85,9 +94,23 @@ impl GradeSubmission {
             ))?
             .to_string();
 
+        let grading_input_selector = GRADING_INPUT_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"[name^="status["], [name^="mark["]"#)
+                .expect("Could not parse selector")
+        });
+        let member_id_regex = MEMBER_ID_REGEX
+            .get_or_init(|| Regex::new(r"\[(?<id>\d+)\]").expect("Could not parse regex"));
+        let member_id = element
+            .select(grading_input_selector)
+            .filter_map(|input| input.attr("name"))
+            .find_map(|name| member_id_regex.captures(name))
+            .and_then(|captures| captures["id"].parse().ok());
+
         Ok(Some(GradeSubmission {
             identifier,
             file_feedback_querypath: feedback_querypath,
+            member_id,
+            grading_form: None,
         }))
     }
 
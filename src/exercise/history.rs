@@ -0,0 +1,93 @@
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Whatever};
+
+use super::assignment::AssignmentSubmission;
+
+/// Every file that was ever seen in the submission of an assignment.
+///
+/// ILIAS only shows the currently submitted files, so deletions are inferred by
+/// recording the submission repeatedly and persisting the history in between.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SubmissionHistory {
+    pub assignment: String,
+    pub entries: Vec<SubmissionHistoryEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmissionHistoryEntry {
+    /// The delivered id of the file, see [`File::delivered_id`](crate::file::File::delivered_id)
+    pub id: Option<u64>,
+    pub name: String,
+    pub submitted: Option<DateTime<Local>>,
+    pub first_seen: DateTime<Local>,
+    /// When the file was first noticed to be missing from the submission
+    pub deleted: Option<DateTime<Local>>,
+}
+
+impl SubmissionHistory {
+    pub fn new(assignment: &str) -> SubmissionHistory {
+        SubmissionHistory {
+            assignment: assignment.to_string(),
+            entries: vec![],
+        }
+    }
+
+    pub fn from_json(json: &str) -> Result<SubmissionHistory, Whatever> {
+        serde_json::from_str(json).whatever_context("Could not deserialize submission history")
+    }
+
+    pub fn to_json(&self) -> Result<String, Whatever> {
+        serde_json::to_string_pretty(self)
+            .whatever_context("Could not serialize submission history")
+    }
+
+    /// Merges the currently submitted files into the history
+    pub fn record(&mut self, submission: &AssignmentSubmission) {
+        let now = Local::now();
+
+        for entry in self
+            .entries
+            .iter_mut()
+            .filter(|entry| entry.deleted.is_none())
+        {
+            let still_submitted = submission.submissions.iter().any(|file| {
+                file.delivered_id == entry.id
+                    && file.name == entry.name
+                    && file.date == entry.submitted
+            });
+            if !still_submitted {
+                entry.deleted = Some(now);
+            }
+        }
+
+        for file in &submission.submissions {
+            let known = self.entries.iter().any(|entry| {
+                entry.deleted.is_none()
+                    && file.delivered_id == entry.id
+                    && file.name == entry.name
+                    && file.date == entry.submitted
+            });
+            if !known {
+                self.entries.push(SubmissionHistoryEntry {
+                    id: file.delivered_id,
+                    name: file.name.clone(),
+                    submitted: file.date,
+                    first_seen: now,
+                    deleted: None,
+                });
+            }
+        }
+
+        self.entries
+            .sort_by_key(|entry| entry.submitted.unwrap_or(entry.first_seen));
+    }
+
+    pub fn current(&self) -> impl Iterator<Item = &SubmissionHistoryEntry> {
+        self.entries.iter().filter(|entry| entry.deleted.is_none())
+    }
+
+    pub fn deleted(&self) -> impl Iterator<Item = &SubmissionHistoryEntry> {
+        self.entries.iter().filter(|entry| entry.deleted.is_some())
+    }
+}
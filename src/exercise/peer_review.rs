@@ -0,0 +1,248 @@
+use std::{path::Path, sync::OnceLock};
+
+use log::{debug, info};
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use crate::{
+    IliasElement, client::IliasClient, file::File, form::HtmlForm, id::RefId,
+    local_file::NamedLocalFile, querypath_from_href, table::Table,
+};
+
+/// The submissions of other students one has to give feedback on
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PeerReview {
+    pub peers: Vec<PeerReviewTarget>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PeerReviewTarget {
+    /// The name of the peer, or a placeholder like "Peer 1" for anonymous reviews
+    pub name: String,
+    pub peer_id: Option<String>,
+    pub submission: Vec<File>,
+    edit_querypath: Option<String>,
+}
+
+/// Feedback other students gave on one's own submission
+#[derive(Debug, Clone)]
+pub struct PeerFeedback {
+    pub reviewer: String,
+    pub text: String,
+    pub files: Vec<File>,
+}
+
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static EDIT_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DOWNLOAD_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FILE_INPUT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FEEDBACK_PANEL_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FEEDBACK_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FEEDBACK_BODY_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static PEER_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+const PEER_COLUMNS: [&str; 2] = ["Name", "Peer"];
+const SUBMISSION_COLUMNS: [&str; 3] = ["Abgabe", "Einreichung", "Submission"];
+
+/// Text criteria of the feedback form are named `prccc_text_<criterion id>`
+const TEXT_FIELD_PREFIX: &str = "prccc_text";
+
+impl IliasElement for PeerReview {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let edit_link_selector = EDIT_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#"a[href*="editPeerReviewItem"], button[data-action*="editPeerReviewItem"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+        let peer_id_regex = PEER_ID_REGEX
+            .get_or_init(|| Regex::new(r"peer_id=(?<id>\d+)").expect("Could not parse regex"));
+
+        let mut peers = vec![];
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            if table.column(&PEER_COLUMNS).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(name) = table.cell_text(row, &PEER_COLUMNS) else {
+                    continue;
+                };
+                let edit_querypath = row
+                    .iter()
+                    .flat_map(|cell| cell.select(edit_link_selector))
+                    .find_map(|link| link.attr("href").or_else(|| link.attr("data-action")))
+                    .map(querypath_from_href);
+                let submission = table
+                    .cell(row, &SUBMISSION_COLUMNS)
+                    .map(download_links)
+                    .unwrap_or_default();
+
+                peers.push(PeerReviewTarget {
+                    name,
+                    peer_id: edit_querypath
+                        .as_ref()
+                        .and_then(|querypath| peer_id_regex.captures(querypath))
+                        .map(|captures| captures["id"].to_string()),
+                    submission,
+                    edit_querypath,
+                });
+            }
+        }
+        debug!("Peer review targets: {peers:?}");
+
+        Ok(PeerReview { peers })
+    }
+}
+
+impl PeerReviewTarget {
+    /// Downloads the files the peer submitted into `directory`
+    pub fn download_submission(
+        &self,
+        ilias_client: &IliasClient,
+        directory: &Path,
+    ) -> Result<(), Whatever> {
+        for file in &self.submission {
+            let querypath = file
+                .download_querypath
+                .as_ref()
+                .whatever_context(format!("{} can not be downloaded", file.name))?;
+            ilias_client
+                .download_file(querypath, &directory.join(&file.name))
+                .whatever_context(format!("Could not download {} of {}", file.name, self.name))?;
+        }
+        Ok(())
+    }
+
+    /// Fills all text criteria of the feedback form with `text` and uploads `files`
+    pub fn give_feedback(
+        &self,
+        ilias_client: &IliasClient,
+        text: &str,
+        files: &[NamedLocalFile],
+    ) -> Result<(), Whatever> {
+        let file_input_selector = FILE_INPUT_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"input[type="file"][name]"#).expect("Could not parse selector")
+        });
+
+        let edit_querypath = self
+            .edit_querypath
+            .as_ref()
+            .whatever_context(format!("Feedback for {} can not be edited", self.name))?;
+        let page = ilias_client
+            .get_querypath(edit_querypath)
+            .whatever_context("Could not get peer feedback form")?;
+        let mut form = HtmlForm::find(&page, |form| form.has_button("updatePeerReview"))
+            .whatever_context("Could not find peer feedback form")?;
+
+        let text_fields = form
+            .fields
+            .iter()
+            .map(|(name, _)| name.clone())
+            .filter(|name| name.starts_with(TEXT_FIELD_PREFIX))
+            .collect::<Vec<_>>();
+        for field in &text_fields {
+            form.set(field, text);
+        }
+
+        let file_inputs = page
+            .select(file_input_selector)
+            .filter_map(|input| input.attr("name"))
+            .collect::<Vec<_>>();
+        if files.len() > file_inputs.len() {
+            whatever!(
+                "Feedback for {} takes {} files, got {}",
+                self.name,
+                file_inputs.len(),
+                files.len()
+            );
+        }
+        let mut file_parts = vec![];
+        for (input, file) in file_inputs.into_iter().zip(files) {
+            let part = ilias_client
+                .construct_file_part(&file.path)?
+                .file_name(file.name.clone());
+            file_parts.push((input.to_string(), part));
+        }
+
+        let response = form
+            .submit_with_files(ilias_client, Some("updatePeerReview"), file_parts)
+            .whatever_context("Could not submit peer feedback")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected feedback for {}", self.name);
+        }
+        info!("Gave feedback to {}", self.name);
+        Ok(())
+    }
+}
+
+impl PeerFeedback {
+    /// Parses the page showing the feedback received for one's own submission
+    pub(crate) fn parse_received(element: ElementRef) -> Vec<PeerFeedback> {
+        let feedback_panel_selector = FEEDBACK_PANEL_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer .panel").expect("Could not parse selector")
+        });
+        let feedback_title_selector = FEEDBACK_TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".panel-heading, h3").expect("Could not parse selector")
+        });
+        let feedback_body_selector = FEEDBACK_BODY_SELECTOR
+            .get_or_init(|| Selector::parse(".panel-body").expect("Could not parse selector"));
+
+        element
+            .select(feedback_panel_selector)
+            .filter_map(|panel| {
+                let body = panel.select(feedback_body_selector).next()?;
+                Some(PeerFeedback {
+                    reviewer: panel
+                        .select(feedback_title_selector)
+                        .next()?
+                        .text()
+                        .collect::<String>()
+                        .trim()
+                        .to_string(),
+                    text: body.text().collect::<String>().trim().to_string(),
+                    files: download_links(body),
+                })
+            })
+            .collect()
+    }
+}
+
+fn download_links(element: ElementRef) -> Vec<File> {
+    let download_link_selector = DOWNLOAD_LINK_SELECTOR.get_or_init(|| {
+        Selector::parse(r#"a[href*="download"], a[href*="deliver"]"#)
+            .expect("Could not parse selector")
+    });
+
+    element
+        .select(download_link_selector)
+        .filter_map(|link| {
+            Some(File {
+                name: link.text().collect::<String>().trim().to_string(),
+                description: String::new(),
+                date: None,
+                download_querypath: Some(querypath_from_href(link.attr("href")?)),
+                id: None,
+                delivered_id: None,
+                license: None,
+                size: None,
+                uploader: None,
+            })
+        })
+        .collect()
+}
@@ -1,15 +1,463 @@
-use std::fmt::Display;
+use std::{
+    fmt::Display,
+    fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 
 use chrono::{DateTime, Local};
+use log::debug;
+use regex::Regex;
+use reqwest::Url;
+use scraper::Selector;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use snafu::{OptionExt, ResultExt, Snafu, Whatever};
 
-#[derive(Debug, Clone)]
+use super::{
+    ILIAS_URL, IliasElement,
+    client::{DownloadReader, IliasClient},
+    hex,
+    id::RefId,
+    info_screen::InfoScreen,
+    parse_date, querypath_from_href,
+    table::Table,
+};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct File {
     pub name: String,
     pub description: String,
     pub date: Option<DateTime<Local>>,
     pub download_querypath: Option<String>,
-    pub id: Option<String>,
+    /// The ref_id of files that are objects in the repository
+    pub id: Option<RefId>,
+    /// The id of a file handed in to an assignment, which is not a repository object
+    pub delivered_id: Option<u64>,
+    /// Copyright or license information, if maintained for the file
+    pub license: Option<String>,
+    /// In bytes, as shown (rounded) in the listing or info screen
+    pub size: Option<u64>,
+    pub uploader: Option<String>,
+}
+
+/// An earlier or the current upload of a file, see [`File::versions`]
+#[derive(Debug, Clone)]
+pub struct FileVersion {
+    pub number: u32,
+    pub name: Option<String>,
+    pub date: Option<DateTime<Local>>,
+    pub uploader: Option<String>,
+    pub download_querypath: String,
+}
+
+/// A downloaded file together with what was checked about it, see [`File::download_verified`]
+#[derive(Debug, Clone)]
+pub struct VerifiedDownload {
+    pub path: PathBuf,
+    /// In bytes
+    pub size: u64,
+    /// Hex encoded SHA-256 of the content, e.g. to find the same file in several courses
+    pub sha256: String,
+}
+
+#[derive(Debug, Snafu)]
+pub enum DownloadError {
+    /// The file on disk is clearly smaller than the size ILIAS lists
+    #[snafu(display("Only {received} of about {expected} bytes of {name} were downloaded"))]
+    IncompleteDownload {
+        name: String,
+        expected: u64,
+        received: u64,
+    },
+    #[snafu(display("Could not download {name}"))]
+    DownloadFailed { name: String, source: Whatever },
+}
+
+/// A rendition ILIAS generated to preview a file, e.g. a thumbnail or a page of a PDF
+#[derive(Debug, Clone)]
+pub struct FilePreview {
+    pub url: Url,
+}
+
+static PREVIEW_IMAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static SIZE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+static VERSION_TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static VERSION_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+const VERSION_COLUMNS: [&str; 2] = ["Version", "Nr"];
+const VERSION_NAME_COLUMNS: [&str; 3] = ["Dateiname", "Filename", "Titel"];
+const VERSION_DATE_COLUMNS: [&str; 2] = ["Datum", "Date"];
+const VERSION_UPLOADER_COLUMNS: [&str; 3] = ["Benutzer", "User", "Hochgeladen von"];
+
+const LICENSE_PROPERTY_NAMES: [&str; 5] =
+    ["Urheberrecht", "Copyright", "Lizenz", "License", "Rechte"];
+const SIZE_PROPERTY_NAMES: [&str; 3] = ["Dateigröße", "File Size", "Größe"];
+const UPLOADER_PROPERTY_NAMES: [&str; 3] = ["Hochgeladen von", "Uploaded by", "Besitzer"];
+const DATE_PROPERTY_NAMES: [&str; 4] = [
+    "Letzte Änderung",
+    "Last Update",
+    "Zuletzt geändert",
+    "Last Modified",
+];
+
+impl File {
+    pub(crate) fn license_from_property(property: &str) -> Option<String> {
+        let (name, value) = property.split_once(':')?;
+        if LICENSE_PROPERTY_NAMES.contains(&name.trim()) && !value.trim().is_empty() {
+            Some(value.trim().to_string())
+        } else {
+            None
+        }
+    }
+
+    /// Parses sizes like "1,2 MB", "512 KB" or "1.234.567 Bytes"
+    pub(crate) fn size_from_property(property: &str) -> Option<u64> {
+        let size_regex = SIZE_REGEX.get_or_init(|| {
+            Regex::new(r"(?i)^\s*(?<size>\d+([.,]\d+)*)\s*(?<unit>[KMGT]i?B|Bytes?)\s*$")
+                .expect("Could not parse regex")
+        });
+
+        let captures = size_regex.captures(property)?;
+        let unit = captures["unit"].to_uppercase();
+        let size = if unit.starts_with('B') {
+            captures["size"].replace(['.', ','], "").parse().ok()?
+        } else if captures["size"].contains(',') {
+            captures["size"]
+                .replace('.', "")
+                .replace(',', ".")
+                .parse::<f64>()
+                .ok()?
+        } else {
+            captures["size"].parse::<f64>().ok()?
+        };
+        let factor = match unit.chars().next()? {
+            'K' => 1024.0,
+            'M' => 1024.0 * 1024.0,
+            'G' => 1024.0 * 1024.0 * 1024.0,
+            'T' => 1024.0 * 1024.0 * 1024.0 * 1024.0,
+            _ => 1.0,
+        };
+        Some((size * factor) as u64)
+    }
+
+    pub fn info_querypath(&self) -> Option<String> {
+        Some(format!(
+            "ilias.php?baseClass=ilrepositorygui&cmd=infoScreen&ref_id={}",
+            self.id.as_ref()?
+        ))
+    }
+
+    /// Fetches the license from the info screen if the listing did not show it
+    pub fn fetch_license(&mut self, ilias_client: &IliasClient) -> Result<Option<&str>, Whatever> {
+        if self.license.is_none()
+            && let Some(info_querypath) = self.info_querypath()
+        {
+            let info_page = ilias_client
+                .get_querypath(&info_querypath)
+                .whatever_context("Could not get info page of file")?;
+            let info = InfoScreen::parse(info_page.root_element(), ilias_client)?;
+            self.license = info
+                .property(&LICENSE_PROPERTY_NAMES)
+                .map(|property| property.value.clone())
+                .filter(|license| !license.is_empty());
+        }
+        Ok(self.license.as_deref())
+    }
+
+    /// Fills in size, uploader, date and license from the info screen where the listing
+    /// did not show them
+    pub fn fetch_metadata(&mut self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        if self.size.is_some()
+            && self.uploader.is_some()
+            && self.date.is_some()
+            && self.license.is_some()
+        {
+            return Ok(());
+        }
+        let info_querypath = self
+            .info_querypath()
+            .whatever_context(format!("{} has no info page", self.name))?;
+        let info_page = ilias_client
+            .get_querypath(&info_querypath)
+            .whatever_context("Could not get info page of file")?;
+        let info = InfoScreen::parse(info_page.root_element(), ilias_client)?;
+        let value = |names: &[&str]| {
+            info.property(names)
+                .map(|property| property.value.clone())
+                .filter(|value| !value.is_empty())
+        };
+
+        self.size = self.size.or_else(|| {
+            value(&SIZE_PROPERTY_NAMES).and_then(|size| File::size_from_property(&size))
+        });
+        self.uploader = self
+            .uploader
+            .take()
+            .or_else(|| value(&UPLOADER_PROPERTY_NAMES));
+        self.date = self
+            .date
+            .or_else(|| value(&DATE_PROPERTY_NAMES).and_then(|date| parse_date(&date).ok()));
+        self.license = self
+            .license
+            .take()
+            .or_else(|| value(&LICENSE_PROPERTY_NAMES));
+        debug!("Metadata of {}: {self:?}", self.name);
+
+        Ok(())
+    }
+
+    /// Downloads into `directory`, appending an extension matching the Content-Type if the
+    /// name has none
+    pub fn download(
+        &self,
+        ilias_client: &IliasClient,
+        directory: &Path,
+    ) -> Result<PathBuf, Whatever> {
+        let path = directory.join(&self.name);
+        let content_type = ilias_client
+            .download_file_with_content_type(self.download_querypath()?, &path)
+            .whatever_context(format!("Could not download {}", self.name))?;
+
+        let named_path =
+            directory.join(with_inferred_extension(&self.name, content_type.as_deref()));
+        if named_path != path {
+            fs::rename(&path, &named_path)
+                .whatever_context(format!("Could not rename {}", self.name))?;
+        }
+        Ok(named_path)
+    }
+
+    /// Downloads like [`File::download`] and sets the modification time of the local file to
+    /// [`File::date`], so "newer than" comparisons with the local copy keep working
+    pub fn download_with_date(
+        &self,
+        ilias_client: &IliasClient,
+        directory: &Path,
+    ) -> Result<PathBuf, Whatever> {
+        let path = self.download(ilias_client, directory)?;
+        self.restore_date(&path)?;
+        Ok(path)
+    }
+
+    /// Sets the modification time of `path` to [`File::date`], if it is known
+    pub fn restore_date(&self, path: &Path) -> Result<(), Whatever> {
+        let Some(date) = self.date else {
+            return Ok(());
+        };
+        fs::File::options()
+            .write(true)
+            .open(path)
+            .and_then(|file| file.set_modified(date.into()))
+            .whatever_context(format!("Could not set modification time of {}", self.name))
+    }
+
+    /// Downloads like [`File::download`], then hashes the content and checks its size against
+    /// the listed one.
+    ///
+    /// Listed sizes are rounded, so only a difference of more than a tenth (at least 1 KiB)
+    /// counts as incomplete.
+    pub fn download_verified(
+        &self,
+        ilias_client: &IliasClient,
+        directory: &Path,
+    ) -> Result<VerifiedDownload, DownloadError> {
+        let path = self
+            .download(ilias_client, directory)
+            .context(DownloadFailedSnafu { name: &self.name })?;
+        let (size, sha256) =
+            content_hash(&path).context(DownloadFailedSnafu { name: &self.name })?;
+
+        if let Some(expected) = self.size
+            && size + (expected / 10).max(1024) < expected
+        {
+            return IncompleteDownloadSnafu {
+                name: &self.name,
+                expected,
+                received: size,
+            }
+            .fail();
+        }
+        Ok(VerifiedDownload { path, size, sha256 })
+    }
+
+    /// Downloads the content into memory, for small files like CSV sheets that are parsed
+    /// directly
+    pub fn download_bytes(&self, ilias_client: &IliasClient) -> Result<Vec<u8>, Whatever> {
+        ilias_client
+            .download_bytes(self.download_querypath()?)
+            .whatever_context(format!("Could not download {}", self.name))
+    }
+
+    /// Streams the content instead of holding all of it in memory
+    pub fn reader<'a>(
+        &self,
+        ilias_client: &'a IliasClient,
+    ) -> Result<DownloadReader<'a>, Whatever> {
+        ilias_client
+            .download_reader(self.download_querypath()?)
+            .whatever_context(format!("Could not download {}", self.name))
+    }
+
+    fn download_querypath(&self) -> Result<&str, Whatever> {
+        self.download_querypath
+            .as_deref()
+            .whatever_context(format!("{} can not be downloaded", self.name))
+    }
+
+    /// Fetches the preview renditions, which is empty if ILIAS did not generate any
+    pub fn previews(&self, ilias_client: &IliasClient) -> Result<Vec<FilePreview>, Whatever> {
+        let preview_image_selector = PREVIEW_IMAGE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il_PreviewContent img[src], .ilPreviewList img[src]")
+                .expect("Could not parse selector")
+        });
+
+        let id = self
+            .id
+            .as_ref()
+            .whatever_context(format!("{} has no id to look up previews", self.name))?;
+        let preview_page = ilias_client
+            .get_querypath(&format!(
+                "ilias.php?baseClass=ilrepositorygui&cmdClass=ilfilepreviewgui&cmd=getPreviewHTML&cmdMode=asynch&ref_id={id}"
+            ))
+            .whatever_context("Could not get preview of file")?;
+
+        let base_url = Url::parse(ILIAS_URL).whatever_context("Could not parse ilias url")?;
+        let previews = preview_page
+            .select(preview_image_selector)
+            .filter_map(|image| base_url.join(image.attr("src")?).ok())
+            .map(|url| FilePreview { url })
+            .collect::<Vec<_>>();
+        debug!("Previews of {}: {previews:?}", self.name);
+
+        Ok(previews)
+    }
+
+    /// Lists the versions from the "Versions" tab, which is only shown to users who may edit
+    pub fn versions(&self, ilias_client: &IliasClient) -> Result<Vec<FileVersion>, Whatever> {
+        let version_table_selector = VERSION_TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let version_link_selector = VERSION_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"a[href*="hist_id"]"#).expect("Could not parse selector")
+        });
+
+        let id = self
+            .id
+            .as_ref()
+            .whatever_context(format!("{} has no id to look up versions", self.name))?;
+        let versions_page = ilias_client
+            .get_querypath(&format!(
+                "ilias.php?baseClass=ilrepositorygui&cmd=versions&ref_id={id}"
+            ))
+            .whatever_context("Could not get versions of file")?;
+
+        let mut versions = vec![];
+        for table in versions_page.select(version_table_selector) {
+            let table = Table::parse(table);
+            if table.column(&VERSION_COLUMNS).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(number) = table
+                    .cell_text(row, &VERSION_COLUMNS)
+                    .and_then(|number| number.trim_start_matches('v').parse().ok())
+                else {
+                    continue;
+                };
+                let download_querypath = row
+                    .iter()
+                    .flat_map(|cell| cell.select(version_link_selector))
+                    .find_map(|link| link.attr("href"))
+                    .map(querypath_from_href)
+                    .unwrap_or_else(|| {
+                        format!(
+                            "ilias.php?baseClass=ilrepositorygui&cmd=sendfile&ref_id={id}&hist_id={number}"
+                        )
+                    });
+
+                versions.push(FileVersion {
+                    number,
+                    name: table
+                        .cell_text(row, &VERSION_NAME_COLUMNS)
+                        .filter(|name| !name.is_empty()),
+                    date: table
+                        .cell_text(row, &VERSION_DATE_COLUMNS)
+                        .and_then(|date| parse_date(&date).ok()),
+                    uploader: table
+                        .cell_text(row, &VERSION_UPLOADER_COLUMNS)
+                        .filter(|uploader| !uploader.is_empty()),
+                    download_querypath,
+                });
+            }
+        }
+        debug!("Versions of {}: {versions:?}", self.name);
+
+        Ok(versions)
+    }
+}
+
+impl FileVersion {
+    pub fn download(&self, ilias_client: &IliasClient, to: &Path) -> Result<(), Whatever> {
+        ilias_client
+            .download_file(&self.download_querypath, to)
+            .whatever_context(format!("Could not download version {}", self.number))
+    }
+}
+
+/// The size and hex encoded SHA-256 of a local file
+pub fn content_hash(path: &Path) -> Result<(u64, String), Whatever> {
+    let mut file = fs::File::open(path)
+        .whatever_context(format!("Could not open {} to hash it", path.display()))?;
+    let mut hasher = Sha256::new();
+    let size = io::copy(&mut file, &mut hasher)
+        .whatever_context(format!("Could not read {} to hash it", path.display()))?;
+    Ok((size, hex(&hasher.finalize())))
+}
+
+/// The extension ILIAS most likely meant with a Content-Type, e.g. "pdf" for "application/pdf"
+pub fn extension_from_content_type(content_type: &str) -> Option<&'static str> {
+    let essence = content_type.split(';').next()?.trim().to_lowercase();
+    let preferred = match essence.as_str() {
+        "text/plain" => Some("txt"),
+        "image/jpeg" => Some("jpg"),
+        "text/html" => Some("html"),
+        _ => None,
+    };
+    preferred.or_else(|| {
+        mime_guess::get_mime_extensions_str(&essence)?
+            .first()
+            .copied()
+    })
+}
+
+/// Appends the extension for `content_type` to names without one, like pasted "download" links
+pub fn with_inferred_extension(name: &str, content_type: Option<&str>) -> String {
+    if Path::new(name).extension().is_some() {
+        return name.to_string();
+    }
+    match content_type.and_then(extension_from_content_type) {
+        Some(extension) => format!("{name}.{extension}"),
+        None => name.to_string(),
+    }
+}
+
+impl FilePreview {
+    pub fn download(&self, ilias_client: &IliasClient, to: &Path) -> Result<(), Whatever> {
+        ilias_client
+            .download_url(self.url.clone(), to)
+            .whatever_context("Could not download preview")
+    }
+
+    pub fn download_bytes(&self, ilias_client: &IliasClient) -> Result<Vec<u8>, Whatever> {
+        ilias_client
+            .download_url_bytes(self.url.clone())
+            .whatever_context("Could not download preview")
+    }
 }
 
 impl Display for File {
@@ -0,0 +1,72 @@
+use std::{error::Error, fs, iter, path::Path, sync::OnceLock};
+
+use chrono::Local;
+use log::{info, warn};
+use regex::Regex;
+use snafu::{ResultExt, Whatever};
+
+static EMAIL_REGEX: OnceLock<Regex> = OnceLock::new();
+static TOKEN_REGEX: OnceLock<Regex> = OnceLock::new();
+static HIDDEN_INPUT_REGEX: OnceLock<Regex> = OnceLock::new();
+static FILE_NAME_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Removes session tokens, hidden form values and email addresses from a page
+pub(crate) fn redact(html: &str) -> String {
+    let email_regex = EMAIL_REGEX
+        .get_or_init(|| Regex::new(r"[\w.+-]+@[\w-]+\.[\w.-]+").expect("Could not parse regex"));
+    let token_regex = TOKEN_REGEX.get_or_init(|| {
+        Regex::new(r"(?<name>rtoken|token|csrf\w*|PHPSESSID|ilClientId)=[^&;\x22'\s]+")
+            .expect("Could not parse regex")
+    });
+    let hidden_input_regex = HIDDEN_INPUT_REGEX.get_or_init(|| {
+        Regex::new(r#"(?<input><input[^>]*type="hidden"[^>]*value=")[^"]*"#)
+            .expect("Could not parse regex")
+    });
+
+    let html = email_regex.replace_all(html, "redacted@example.org");
+    let html = token_regex.replace_all(&html, "${name}=REDACTED");
+    hidden_input_regex
+        .replace_all(&html, "${input}REDACTED")
+        .into_owned()
+}
+
+/// Writes the redacted page and the error chain into `directory` so it can become a regression fixture
+pub(crate) fn capture(directory: &Path, querypath: &str, html: &str, error: &Whatever) {
+    if let Err(capture_error) = try_capture(directory, querypath, html, error) {
+        warn!("Could not capture fixture for {querypath}: {capture_error}");
+    }
+}
+
+fn try_capture(
+    directory: &Path,
+    querypath: &str,
+    html: &str,
+    error: &Whatever,
+) -> Result<(), Whatever> {
+    let file_name_regex =
+        FILE_NAME_REGEX.get_or_init(|| Regex::new(r"[^\w-]+").expect("Could not parse regex"));
+
+    fs::create_dir_all(directory).whatever_context("Could not create fixture directory")?;
+
+    let file_name = format!(
+        "{}_{}",
+        Local::now().format("%Y%m%d-%H%M%S"),
+        file_name_regex.replace_all(&redact(querypath), "_")
+    );
+    let html_path = directory.join(format!("{file_name}.html"));
+    let error_path = directory.join(format!("{file_name}.error.txt"));
+
+    let error_chain = iter::successors(Some(error as &dyn Error), |&error| error.source())
+        .map(|error| error.to_string())
+        .collect::<Vec<_>>()
+        .join("\nCaused by: ");
+    fs::write(&html_path, redact(html)).whatever_context("Could not write fixture html")?;
+    fs::write(
+        &error_path,
+        format!("Querypath: {}\n\n{error_chain}\n", redact(querypath)),
+    )
+    .whatever_context("Could not write fixture error")?;
+
+    info!("Captured fixture {}", html_path.display());
+    Ok(())
+}
@@ -2,17 +2,33 @@ use std::{fmt::Display, sync::OnceLock};
 
 use log::{debug, info};
 use regex::Regex;
-use reqwest::{multipart::Form, Url};
-use scraper::{element_ref::Select, selectable::Selectable, ElementRef, Selector};
+use reqwest::multipart::Form;
+use scraper::{element_ref::Select, selectable::Selectable, ElementRef, Html, Selector};
 use serde::{Deserialize, Serialize};
 use snafu::{whatever, OptionExt, ResultExt, Whatever};
 
+pub mod clipboard;
+pub mod filter;
+pub mod sorting;
+pub mod trash;
+pub mod walk;
+pub mod zip;
+
 use super::{
-    client::IliasClient, file::File, local_file::NamedLocalFile, parse_date, IliasElement,
-    Querypath,
+    IliasContainer, IliasElement, Querypath,
+    chunked_upload::{chunk_size, upload_chunked},
+    client::{IliasClient, TransferProgress, UploadProgress},
+    file::File,
+    form::HtmlForm,
+    id::RefId,
+    local_file::NamedLocalFile,
+    locator::{Ancestor, parse_locator},
+    parse_date, querypath_from_href, type_from_icon,
+    unknown::UnknownElement,
 };
+use walk::FolderWalk;
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub enum FolderElement {
     File {
@@ -22,34 +38,65 @@ pub enum FolderElement {
     Exercise {
         name: String,
         description: String,
-        id: String,
+        id: RefId,
+        querypath: String,
+        deletion_querypath: Option<String>,
+    },
+    Group {
+        name: String,
+        description: String,
+        id: RefId,
+        querypath: String,
+        deletion_querypath: Option<String>,
+    },
+    Session {
+        name: String,
+        description: String,
+        id: RefId,
         querypath: String,
         deletion_querypath: Option<String>,
     },
     Opencast {
         name: String,
         description: String,
-        id: String,
+        id: RefId,
         querypath: String,
         deletion_querypath: Option<String>,
     },
     Viewable {
         name: String,
         description: String,
-        id: String,
+        id: RefId,
         querypath: String,
+        /// From the icon, e.g. `fold` for folders
+        type_identifier: Option<String>,
+        deletion_querypath: Option<String>,
+    },
+    /// An object of a type that is not supported yet
+    Unknown {
+        element: UnknownElement,
         deletion_querypath: Option<String>,
     },
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 #[allow(dead_code)]
 pub struct Folder {
     name: String,
     description: String,
-    id: String,
+    id: RefId,
     pub elements: Vec<FolderElement>,
+    pub sections: Vec<ContainerSection>,
     upload_page_querypath: Option<String>,
+    create_folder_querypath: Option<String>,
+    locator: Vec<Ancestor>,
+}
+
+/// An item group or content block of a container, grouping some of its elements under a heading
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ContainerSection {
+    pub title: String,
+    element_indices: Vec<usize>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -63,16 +110,20 @@ static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static ID_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static UPLOAD_FILE_PAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CREATE_FOLDER_PAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
 
 static ELEMENT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static SECTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static SECTION_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static LAST_SCRIPT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PAGINATION_SELECTOR: OnceLock<Selector> = OnceLock::new();
 
 impl IliasElement for Folder {
     fn type_identifier() -> Option<&'static str> {
         Some("fold")
     }
 
-    fn querypath_from_id(id: &str) -> Option<String> {
+    fn querypath_from_id(id: RefId) -> Option<String> {
         Some(format!(
             "goto.php/{}/{}",
             Self::type_identifier().unwrap(),
@@ -92,11 +143,8 @@ impl IliasElement for Folder {
         let upload_file_page_selector = UPLOAD_FILE_PAGE_SELECTOR.get_or_init(|| {
             Selector::parse("#il-add-new-item-gl #file").expect("Could not parse selector")
         });
-
-        let element_selector = ELEMENT_SELECTOR
-            .get_or_init(|| Selector::parse(".ilObjListRow").expect("Could not parse selector"));
-        let last_script_selector = LAST_SCRIPT_SELECTOR.get_or_init(|| {
-            Selector::parse("body script:last-child").expect("Could not parse selector")
+        let create_folder_page_selector = CREATE_FOLDER_PAGE_SELECTOR.get_or_init(|| {
+            Selector::parse("#il-add-new-item-gl #fold").expect("Could not parse selector")
         });
 
         let name = element
@@ -118,35 +166,32 @@ impl IliasElement for Folder {
             .next()
             .whatever_context("Could not find link in breadcrumbs")?
             .attr("href")
-            .whatever_context("Link missing href attribute")?
-            .to_string();
+            .and_then(RefId::from_querypath)
+            .whatever_context("Could not find ref id in breadcrumbs")?;
 
-        let last_script = element
-            .select(last_script_selector)
-            .next()
-            .whatever_context("Did not find last script")?
-            .text()
-            .collect::<String>();
-
-        let mut elements: Vec<FolderElement> = vec![];
-        for element in element.select(element_selector) {
-            let folder_element = FolderElement::parse(element, &last_script, ilias_client)
-                .whatever_context("Could not parse folder element")?;
-            elements.push(folder_element);
-        }
+        let elements = parse_container_elements(element, ilias_client)?;
+        let sections = ContainerSection::parse_all(element);
 
         let upload_page_querypath = element
             .select(upload_file_page_selector)
             .next()
             .and_then(|link| link.attr("href"))
             .map(str::to_string);
+        let create_folder_querypath = element
+            .select(create_folder_page_selector)
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(str::to_string);
 
         let folder = Folder {
             name,
             description,
             id,
             elements,
+            sections,
             upload_page_querypath,
+            create_folder_querypath,
+            locator: parse_locator(element),
         };
         debug!("Folder: {:?}", folder);
 
@@ -154,95 +199,432 @@ impl IliasElement for Folder {
     }
 }
 
+/// The object rows of a container page
+fn element_selector() -> &'static Selector {
+    ELEMENT_SELECTOR.get_or_init(|| {
+        // Containers are shown as list rows or, depending on their settings, as tiles
+        Selector::parse(".ilObjListRow, .il-card").expect("Could not parse selector")
+    })
+}
+
+/// Parses the object rows of a container page (folder, course, ...).
+pub(crate) fn parse_container_elements(
+    element: ElementRef,
+    ilias_client: &IliasClient,
+) -> Result<Vec<FolderElement>, Whatever> {
+    let element_selector = element_selector();
+    let last_script_selector = LAST_SCRIPT_SELECTOR.get_or_init(|| {
+        Selector::parse("body script:last-child").expect("Could not parse selector")
+    });
+
+    let last_script = element
+        .select(last_script_selector)
+        .next()
+        .whatever_context("Did not find last script")?
+        .text()
+        .collect::<String>();
+
+    let mut elements: Vec<FolderElement> = vec![];
+    for element in element.select(element_selector) {
+        let folder_element = FolderElement::parse(element, &last_script, ilias_client)
+            .whatever_context("Could not parse folder element")?;
+        elements.push(folder_element);
+    }
+
+    // Large containers only show the first items of a block or page
+    let pagination_selector = PAGINATION_SELECTOR.get_or_init(|| {
+        Selector::parse(
+            ".ilContainerShowMore a[href], .ilContainerBlock .ilTableNav a[href], .il-viewcontrol-pagination [data-action]",
+        )
+        .expect("Could not parse selector")
+    });
+    for page in ilias_client.get_linked_pages(element, pagination_selector)? {
+        let last_script = page
+            .select(last_script_selector)
+            .next()
+            .map(|script| script.text().collect::<String>())
+            .unwrap_or_default();
+        for element in page.select(element_selector) {
+            let folder_element = FolderElement::parse(element, &last_script, ilias_client)
+                .whatever_context("Could not parse folder element of further page")?;
+            if !elements.iter().any(|known| {
+                known.id() == folder_element.id() && known.name() == folder_element.name()
+            }) {
+                elements.push(folder_element);
+            }
+        }
+    }
+    Ok(elements)
+}
+
+/// Positions in the elements of `page` of the object rows that lie within `container`
+pub(crate) fn element_indices_in(page: ElementRef, container: ElementRef) -> Vec<usize> {
+    page.select(element_selector())
+        .enumerate()
+        .filter(|(_, row)| {
+            row.ancestors()
+                .any(|ancestor| ancestor.id() == container.id())
+        })
+        .map(|(index, _)| index)
+        .collect()
+}
+
+impl IliasContainer for Folder {
+    fn children(&self, _ilias_client: &IliasClient) -> Result<Vec<FolderElement>, Whatever> {
+        Ok(self.elements.clone())
+    }
+}
+
+impl ContainerSection {
+    pub(crate) fn parse_all(element: ElementRef) -> Vec<ContainerSection> {
+        let section_selector = SECTION_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilContainerBlock").expect("Could not parse selector")
+        });
+        let section_title_selector = SECTION_TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilContainerBlockHeader, .ilBlockHeader")
+                .expect("Could not parse selector")
+        });
+
+        element
+            .select(section_selector)
+            .filter_map(|section| {
+                let title = section
+                    .select(section_title_selector)
+                    .next()?
+                    .text()
+                    .collect::<String>()
+                    .trim()
+                    .to_string();
+                Some(ContainerSection {
+                    title,
+                    element_indices: element_indices_in(element, section),
+                })
+            })
+            .filter(|section| !section.title.is_empty() && !section.element_indices.is_empty())
+            .collect()
+    }
+
+    /// The elements of a container paired with the heading of the innermost section they are in
+    pub(crate) fn attach<'a>(
+        sections: &'a [ContainerSection],
+        elements: &'a [FolderElement],
+    ) -> impl Iterator<Item = (Option<&'a str>, &'a FolderElement)> {
+        elements.iter().enumerate().map(|(index, element)| {
+            let section = sections
+                .iter()
+                .rev()
+                .find(|section| section.element_indices.contains(&index))
+                .map(|section| section.title.as_str());
+            (section, element)
+        })
+    }
+}
+
 static CONTENT_FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static CONFIRM_BUTTON_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static SCRIPT_TAG_SELECTOR: OnceLock<Selector> = OnceLock::new();
 
+static ALERT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
 impl Folder {
+    /// The containers above the folder, starting at the repository root
+    pub fn path(&self) -> &[Ancestor] {
+        &self.locator
+    }
+
+    pub fn elements_with_sections(&self) -> impl Iterator<Item = (Option<&str>, &FolderElement)> {
+        ContainerSection::attach(&self.sections, &self.elements)
+    }
+
+    /// Walks the whole subtree depth first, descending into subfolders, groups and sessions
+    pub fn walk<'a>(&self, ilias_client: &'a IliasClient) -> FolderWalk<'a> {
+        FolderWalk::new(ilias_client, self)
+    }
+
     pub fn upload_files(
         &self,
         ilias_client: &IliasClient,
         files: &[NamedLocalFile],
     ) -> Result<(), Whatever> {
-        debug!(
-            "Uploading files: {:?} to {:?}",
-            files, &self.upload_page_querypath
-        );
-        let upload_page = ilias_client.get_querypath(
-            &self
-                .upload_page_querypath
-                .clone()
-                .whatever_context("No upload available for this folder")?,
-        )?;
-        let upload_form_selector = CONTENT_FORM_SELECTOR.get_or_init(|| {
-            Selector::parse("#ilContentContainer form").expect("Could not parse scraper")
-        });
-        let script_tag_selector = SCRIPT_TAG_SELECTOR.get_or_init(|| {
-            Selector::parse("body script:not([src])").expect("Could not parse scraper")
-        });
+        self.upload_files_with_progress(ilias_client, files, |_, _| {})
+    }
 
-        let finish_upload_querypath = upload_page
-            .select(upload_form_selector)
-            .next()
-            .unwrap()
-            .value()
-            .attr("action")
-            .unwrap();
-        debug!("Finish upload querypath: {}", finish_upload_querypath);
+    /// Uploads files while reporting the progress of each file after every chunk
+    pub fn upload_files_with_progress(
+        &self,
+        ilias_client: &IliasClient,
+        files: &[NamedLocalFile],
+        progress_hook: impl FnMut(&NamedLocalFile, &TransferProgress),
+    ) -> Result<(), Whatever> {
+        let uploads = files
+            .iter()
+            .map(|file| FileUpload {
+                file,
+                title: &file.name,
+                description: "",
+            })
+            .collect::<Vec<_>>();
+        upload_to_container(
+            ilias_client,
+            self.upload_page_querypath.as_deref(),
+            &uploads,
+            progress_hook,
+        )
+    }
 
-        let relevant_script_tag = upload_page
-            .select(script_tag_selector)
-            .next()
-            .unwrap()
-            .text()
-            .collect::<String>();
-
-        let path_regex =
-            Regex::new(r".*il\.UI\.Input\.File\.init\([^']*'[^']*',[^']*'(?<querypath>[^']+)'.*")
-                .whatever_context("Could not parse cursed regex lol")?;
-        let upload_querypath = &path_regex
-            .captures(&relevant_script_tag)
-            .whatever_context("No match for upload querypath found :(")?["querypath"];
-        debug!("Upload querypath: {}", upload_querypath);
-
-        for file_data in files {
-            let form = Form::new().part(
-                "file[0]",
-                ilias_client.construct_file_part(&file_data.path)?,
-            );
-
-            let response = ilias_client.post_querypath_multipart(upload_querypath, form)?;
-            let response: IliasUploadResponse = ilias_client.get_json(response)?;
-            debug!("Upload response: {response:?}");
-            let file_id = response.file_id;
-
-            let finish_form = Form::new()
-                .text("form/input_0[input_1][]", file_data.name.clone()) // Filename
-                .text("form/input_0[input_2][]", "") // Description
-                .text("form/input_0[input_3][]", file_id) // File id
-                .text("form/input_1", "7") // License: All rights reserved
-                .percent_encode_noop();
-
-            let response =
-                ilias_client.post_querypath_multipart(finish_upload_querypath, finish_form)?;
-            debug!("Finish upload response: {:?}", response);
-            if ilias_client
-                .is_alert_response(response)
-                .whatever_context("Could not check error state of response")?
-            {
-                whatever!("Upload response has an error, please check if the file was uploaded and report")
+    pub fn ref_id(&self) -> RefId {
+        self.id
+    }
+
+    /// Creates a subfolder, only possible for users that may edit the folder
+    pub fn create_folder(
+        &self,
+        ilias_client: &IliasClient,
+        title: &str,
+        description: &str,
+    ) -> Result<FolderElement, Whatever> {
+        create_folder_in(
+            ilias_client,
+            self.create_folder_querypath.as_deref(),
+            title,
+            description,
+        )
+    }
+
+    /// Creates a file object with the given title and description, like "Add New Item → File"
+    pub fn upload_file(
+        &self,
+        ilias_client: &IliasClient,
+        file: &NamedLocalFile,
+        title: &str,
+        description: &str,
+    ) -> Result<(), Whatever> {
+        upload_to_container(
+            ilias_client,
+            self.upload_page_querypath.as_deref(),
+            &[FileUpload {
+                file,
+                title,
+                description,
+            }],
+            |_, _| {},
+        )
+    }
+}
+
+/// The ref_id in a querypath or permalink of a folder
+/// The form fields that select `elements` in the actions of a container
+fn selected_ids(elements: &[&FolderElement]) -> Result<Vec<(&'static str, RefId)>, Whatever> {
+    elements
+        .iter()
+        .map(|element| {
+            let id = element
+                .id()
+                .whatever_context(format!("{} has no ref id to select it by", element.name()))?;
+            Ok(("id[]", id))
+        })
+        .collect()
+}
+
+/// Creates a folder through the "Add New Item → Folder" form of a container and returns it as
+/// it would be listed in the container
+pub(crate) fn create_folder_in(
+    ilias_client: &IliasClient,
+    create_folder_querypath: Option<&str>,
+    title: &str,
+    description: &str,
+) -> Result<FolderElement, Whatever> {
+    let create_page = ilias_client.get_querypath(
+        create_folder_querypath.whatever_context("No folder can be created in this container")?,
+    )?;
+    let mut form = HtmlForm::find(&create_page, |form| form.has_button("save"))
+        .whatever_context("Did not find form to create folder")?;
+    if form.has_field("title") {
+        form.set("title", title);
+        form.set("desc", description);
+    } else {
+        // Newer forms only number their inputs, title and description come first
+        let inputs = form
+            .fields
+            .iter()
+            .map(|(name, _)| name.clone())
+            .filter(|name| name.starts_with("form/"))
+            .collect::<Vec<_>>();
+        let [title_input, description_input, ..] = inputs.as_slice() else {
+            whatever!("Unexpected fields in form to create folder: {inputs:?}");
+        };
+        form.set(title_input, title);
+        form.set(description_input, description);
+    }
+
+    let response = form
+        .submit(ilias_client, Some("save"))
+        .whatever_context("Could not submit form to create folder")?;
+    // ILIAS redirects to the new folder
+    let querypath = response.url().get_querypath();
+    let id = RefId::from_querypath(&querypath)
+        .whatever_context(format!("Did not get redirected to the new folder {title}"))?;
+    if ilias_client.is_alert_response(response)? {
+        whatever!("Ilias rejected creating folder {title}");
+    }
+    info!("Created folder {title} with ref id {id}");
+
+    Ok(FolderElement::Viewable {
+        name: title.to_string(),
+        description: description.to_string(),
+        querypath: Folder::querypath_from_id(id).whatever_context("Folders have querypaths")?,
+        id,
+        type_identifier: Folder::type_identifier().map(str::to_string),
+        deletion_querypath: None,
+    })
+}
+
+/// A local file to create a file object from, see [`upload_to_container`]
+#[derive(Debug)]
+pub(crate) struct FileUpload<'a> {
+    pub file: &'a NamedLocalFile,
+    pub title: &'a str,
+    pub description: &'a str,
+}
+
+/// Creates file objects in a container (folder, course, ...) through its "Add New Item → File"
+/// page, which is only linked for users that may edit the container
+pub(crate) fn upload_to_container(
+    ilias_client: &IliasClient,
+    upload_page_querypath: Option<&str>,
+    uploads: &[FileUpload],
+    mut progress_hook: impl FnMut(&NamedLocalFile, &TransferProgress),
+) -> Result<(), Whatever> {
+    debug!("Uploading files: {uploads:?} to {upload_page_querypath:?}");
+    let upload_page = ilias_client.get_querypath(
+        upload_page_querypath.whatever_context("No upload available for this container")?,
+    )?;
+    let upload_form_selector = CONTENT_FORM_SELECTOR.get_or_init(|| {
+        Selector::parse("#ilContentContainer form").expect("Could not parse scraper")
+    });
+    let script_tag_selector = SCRIPT_TAG_SELECTOR.get_or_init(|| {
+        Selector::parse("body script:not([src])").expect("Could not parse scraper")
+    });
+
+    let finish_upload_querypath = upload_page
+        .select(upload_form_selector)
+        .next()
+        .whatever_context("Did not find upload form")?
+        .value()
+        .attr("action")
+        .whatever_context("Upload form has no action")?;
+    debug!("Finish upload querypath: {}", finish_upload_querypath);
+
+    let relevant_script_tag = upload_page
+        .select(script_tag_selector)
+        .next()
+        .whatever_context("Did not find upload script")?
+        .text()
+        .collect::<String>();
+
+    let path_regex =
+        Regex::new(r".*il\.UI\.Input\.File\.init\([^']*'[^']*',[^']*'(?<querypath>[^']+)'.*")
+            .whatever_context("Could not parse cursed regex lol")?;
+    let upload_querypath = &path_regex
+        .captures(&relevant_script_tag)
+        .whatever_context("No match for upload querypath found :(")?["querypath"];
+    debug!("Upload querypath: {}", upload_querypath);
+    let chunk_size = chunk_size(&relevant_script_tag);
+    debug!("Chunk size: {chunk_size:?}");
+    let alert_selector = ALERT_SELECTOR
+        .get_or_init(|| Selector::parse(".alert-danger").expect("Could not parse selector"));
+
+    for upload in uploads {
+        let mut chunked = None;
+        let response = match chunk_size {
+            Some(chunk_size) => {
+                let (response, content) = upload_chunked(
+                    ilias_client,
+                    upload_querypath,
+                    upload.file,
+                    chunk_size,
+                    |chunk| Form::new().part("file[0]", chunk),
+                    |transfer| progress_hook(upload.file, transfer),
+                )?;
+                chunked = Some(content);
+                response
+            }
+            None => {
+                let mut progress = UploadProgress::new();
+                let form = Form::new().part(
+                    "file[0]",
+                    ilias_client
+                        .construct_file_part_with_progress(&upload.file.path, &mut progress)?,
+                );
+
+                ilias_client.post_querypath_multipart_with_progress(
+                    upload_querypath,
+                    form,
+                    progress,
+                    |_, transfer| progress_hook(upload.file, transfer),
+                )?
             }
+        };
+        let response: IliasUploadResponse = ilias_client.get_json(response)?;
+        debug!("Upload response: {response:?}");
+
+        let finish_form = Form::new()
+            .text("form/input_0[input_1][]", upload.title.to_string()) // Title
+            .text("form/input_0[input_2][]", upload.description.to_string()) // Description
+            .text("form/input_0[input_3][]", response.file_id) // File id
+            .text("form/input_1", "7") // License: All rights reserved
+            .percent_encode_noop();
+
+        let response =
+            ilias_client.post_querypath_multipart(finish_upload_querypath, finish_form)?;
+        debug!("Finish upload response: {:?}", response);
+        // ILIAS returns to the container, which lists the new file object
+        let container_page = Html::parse_document(&ilias_client.get_text(response)?);
+        if container_page.select(alert_selector).next().is_some() {
+            whatever!(
+                "Upload response has an error, please check if the file was uploaded and report"
+            )
         }
 
-        Ok(())
-        // TODO: Maybe push files to submission here
+        // Chunks may have been sent in an earlier run, so check what ILIAS assembled from them
+        if let Some(content) = chunked {
+            let elements = parse_container_elements(container_page.root_element(), ilias_client)
+                .whatever_context("Could not list container to verify the upload")?;
+            let listed = elements
+                .into_iter()
+                .filter_map(|element| match element {
+                    FolderElement::File { file, .. } if file.name == upload.title => Some(file),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            if listed.is_empty() {
+                whatever!("{} is not listed after uploading it", upload.title);
+            }
+            let mut assembled = false;
+            for file in &listed {
+                if content.is_assembled_in(ilias_client, file)? {
+                    assembled = true;
+                    break;
+                }
+            }
+            if !assembled {
+                whatever!(
+                    "ILIAS assembled {} differently from the uploaded file",
+                    upload.title
+                );
+            }
+            info!("Verified upload of {}", upload.title);
+        }
     }
+
+    Ok(())
 }
 
 static ELEMENT_NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static ELEMENT_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static ELEMENT_ACTIONS_SELECTOR: OnceLock<Selector> = OnceLock::new();
 static ELEMENT_PROPERTY_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ELEMENT_ICON_SELECTOR: OnceLock<Selector> = OnceLock::new();
 
 impl FolderElement {
     fn parse(
@@ -251,57 +633,71 @@ impl FolderElement {
         ilias_client: &IliasClient,
     ) -> Result<FolderElement, Whatever> {
         let element_name_selector = ELEMENT_NAME_SELECTOR.get_or_init(|| {
-            Selector::parse(".il_ContainerItemTitle a").expect("Could not parse selector")
+            Selector::parse(".il_ContainerItemTitle a, .card-title a, .caption a")
+                .expect("Could not parse selector")
         });
         let element_description_selector = ELEMENT_DESCRIPTION_SELECTOR
             .get_or_init(|| Selector::parse(".il_Description").expect("Could not parse selector"));
-        let element_property_selector = ELEMENT_PROPERTY_SELECTOR
-            .get_or_init(|| Selector::parse(".il_ItemProperty").expect("Could not parse selector"));
+        let element_property_selector = ELEMENT_PROPERTY_SELECTOR.get_or_init(|| {
+            Selector::parse(".il_ItemProperty, .il-item-property-value")
+                .expect("Could not parse selector")
+        });
+        let element_icon_selector = ELEMENT_ICON_SELECTOR.get_or_init(|| {
+            Selector::parse("img.ilListItemIcon, img.icon").expect("Could not parse selector")
+        });
 
         let name_element = element
             .select(element_name_selector)
             .next()
             .whatever_context("Did not find name")?;
-        let description_element = element
+        // Tiles only show a description if one is set
+        let description = element
             .select(element_description_selector)
             .next()
-            .whatever_context("Could not get description")?;
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
         let mut properties = element.select(element_property_selector);
 
         let name: String = name_element.text().collect();
         let link = name_element
             .attr("href")
             .whatever_context("Could not get link")?;
-        let description = description_element.text().collect();
-        let querypath = Url::parse(link)
-            .expect("Could not parse link")
-            .get_querypath();
-
-        let id = Regex::new(r"(ref_id=|target=file_|exc/)(?<id>\d+)")
-            .whatever_context("Could not parse regex")?
-            .captures(&querypath)
-            .and_then(|capture| capture.name("id"))
-            .whatever_context(format!(
-                "Could not get id captures from querypath {}",
-                querypath
-            ))?
-            .as_str()
-            .to_string();
+        let querypath = querypath_from_href(link);
+        let type_identifier = element
+            .select(element_icon_selector)
+            .next()
+            .and_then(|icon| icon.attr("src"))
+            .and_then(type_from_icon);
+
+        let Some(id) = RefId::from_querypath(&querypath) else {
+            debug!("No id in querypath {querypath}, keeping {name} as unknown element");
+            return Ok(FolderElement::Unknown {
+                element: UnknownElement {
+                    name,
+                    description,
+                    type_identifier,
+                    id: None,
+                    querypath,
+                },
+                deletion_querypath: None,
+            });
+        };
 
-        let deletion_querypath = Self::get_deletion_querypath(&id, folder_script, ilias_client);
+        let deletion_querypath = Self::get_deletion_querypath(id, folder_script, ilias_client);
 
         Self::extract_from_querypath(
             querypath,
             name,
             description,
             id,
+            type_identifier,
             deletion_querypath,
             &mut properties,
         )
     }
 
     fn get_deletion_querypath(
-        id: &str,
+        id: RefId,
         folder_script: &str,
         ilias_client: &IliasClient,
     ) -> Option<String> {
@@ -310,7 +706,7 @@ impl FolderElement {
 
         let regex = format!(
             r##"\$\("#ilAdvSelListAnchorText_act_{}_pref_\d+"\).click\((?:.|\n)*ajaxReplaceInner\('(?<querypath>[^']+)', 'ilAdvSelListTable_act_{}"##,
-            &id, &id
+            id, id
         );
         let actions_querypath = Regex::new(&regex)
             .ok()?
@@ -318,20 +714,19 @@ impl FolderElement {
             .and_then(|captures| Some(captures.name("querypath")?.as_str().to_string()))?;
         let actions = ilias_client.get_querypath(&actions_querypath).ok()?;
 
-        let deletion_querypath = actions
+        actions
             .select(element_actions_selector)
             .filter_map(|element| element.attr("href"))
             .find(|&href| href.contains("cmd=delete"))
-            .map(ToOwned::to_owned);
-
-        deletion_querypath
+            .map(ToOwned::to_owned)
     }
 
     fn extract_from_querypath(
         querypath: String,
         name: String,
         description: String,
-        id: String,
+        id: RefId,
+        type_identifier: Option<String>,
         deletion_querypath: Option<String>,
         properties: &mut Select<'_, '_>,
     ) -> Result<FolderElement, Whatever> {
@@ -342,21 +737,28 @@ impl FolderElement {
         {
             let extension: String = properties
                 .next()
-                .expect("Could not find file extension")
-                .text()
-                .collect::<String>()
-                .trim()
-                .to_string();
+                .map(|extension| extension.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+            let mut license = None;
+            let mut size = None;
             let date = loop {
-                let next_property = properties
-                    .next()
-                    .whatever_context("No date properties left")?;
-                let date = parse_date(&next_property.text().collect::<String>());
+                let Some(next_property) = properties.next() else {
+                    break None;
+                };
+                let next_property = next_property.text().collect::<String>();
+                license = license.or_else(|| File::license_from_property(&next_property));
+                size = size.or_else(|| File::size_from_property(&next_property));
+                let date = parse_date(&next_property);
                 match date {
                     Ok(date) => break Some(date),
                     Err(_) => continue,
                 }
             };
+            let license = license.or_else(|| {
+                properties.find_map(|property| {
+                    File::license_from_property(&property.text().collect::<String>())
+                })
+            });
 
             let name = if extension.is_empty() {
                 name
@@ -368,8 +770,12 @@ impl FolderElement {
                 name,
                 description,
                 date,
-                id: Some(id.to_string()),
+                id: Some(id),
+                delivered_id: None,
                 download_querypath: Some(querypath),
+                license,
+                size,
+                uploader: None,
             };
 
             Ok(FolderElement::File {
@@ -387,18 +793,38 @@ impl FolderElement {
                 querypath,
                 deletion_querypath,
             })
+        } else if querypath.contains("/grp/")
+            || querypath.contains("target=grp_")
+            || querypath.to_lowercase().contains("cmdclass=ilobjgroupgui")
+        {
+            Ok(FolderElement::Group {
+                name,
+                description,
+                id,
+                querypath,
+                deletion_querypath,
+            })
+        } else if querypath.contains("/sess/")
+            || querypath.contains("target=sess_")
+            || querypath
+                .to_lowercase()
+                .contains("cmdclass=ilobjsessiongui")
+        {
+            Ok(FolderElement::Session {
+                name,
+                description,
+                id,
+                querypath,
+                deletion_querypath,
+            })
         } else if querypath.contains("baseClass=ilrepositorygui") && querypath.contains("cmd=view")
         {
-            let id = Regex::new(r"ref_id=(?<id>\d+)")
-                .whatever_context("Could not parse regex")?
-                .captures(&querypath)
-                .whatever_context("Could not extract id")?["id"]
-                .to_string();
             Ok(FolderElement::Viewable {
                 name,
                 description,
                 id,
                 querypath,
+                type_identifier,
                 deletion_querypath,
             })
         } else if querypath.contains("/exc/") {
@@ -410,7 +836,16 @@ impl FolderElement {
                 deletion_querypath,
             })
         } else {
-            whatever!("Could not get folder element")
+            Ok(FolderElement::Unknown {
+                element: UnknownElement {
+                    name,
+                    description,
+                    type_identifier,
+                    id: Some(id),
+                    querypath,
+                },
+                deletion_querypath,
+            })
         }
     }
 
@@ -422,11 +857,20 @@ impl FolderElement {
             | Self::Exercise {
                 deletion_querypath, ..
             }
+            | Self::Group {
+                deletion_querypath, ..
+            }
+            | Self::Session {
+                deletion_querypath, ..
+            }
             | Self::Opencast {
                 deletion_querypath, ..
             }
             | Self::Viewable {
                 deletion_querypath, ..
+            }
+            | Self::Unknown {
+                deletion_querypath, ..
             } => deletion_querypath,
         }
         .as_ref()
@@ -442,23 +886,35 @@ impl FolderElement {
         }
     }
 
-    fn id(&self) -> &str {
+    /// The ref_id, only missing for objects whose link does not contain it
+    pub fn id(&self) -> Option<RefId> {
         match self {
-            Self::File { file, .. } => file.id.as_ref().unwrap(),
-            Self::Exercise { id, .. } | Self::Opencast { id, .. } | Self::Viewable { id, .. } => id,
+            Self::File { file, .. } => file.id,
+            Self::Exercise { id, .. }
+            | Self::Group { id, .. }
+            | Self::Session { id, .. }
+            | Self::Opencast { id, .. }
+            | Self::Viewable { id, .. } => Some(*id),
+            Self::Unknown { element, .. } => element.id,
         }
     }
 
-    fn name(&self) -> &str {
+    pub(crate) fn name(&self) -> &str {
         match self {
             Self::File { file, .. } => &file.name,
             Self::Exercise { name, .. }
+            | Self::Group { name, .. }
+            | Self::Session { name, .. }
             | Self::Opencast { name, .. }
             | Self::Viewable { name, .. } => name,
+            Self::Unknown { element, .. } => &element.name,
         }
     }
 
+    /// Deletes the element, which moves it to the trash of its container if ILIAS has the trash
+    /// enabled, see [`Folder::trash`]. Needs [`IliasClient::set_allow_deletion`].
     pub fn delete(&self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        ilias_client.ensure_deletion_allowed()?;
         let deletion_querypath = self.deletion_querypath();
         let delete_page =
             ilias_client
@@ -479,7 +935,10 @@ impl FolderElement {
             .whatever_context("Could not find action on form")?;
         debug!("Delete confirm querypath: {}", confirm_querypath);
 
-        let form_data = [("form/input_0", self.id())];
+        let id = self
+            .id()
+            .whatever_context(format!("Can not delete {} without its ref id", self.name()))?;
+        let form_data = [("form/input_0", id)];
 
         ilias_client
             .post_querypath_form(confirm_querypath, &form_data)
@@ -489,8 +948,7 @@ impl FolderElement {
             ))?;
         info!(
             "Deleted {} via deletion querypath {:?}",
-            self.id(),
-            deletion_querypath
+            id, deletion_querypath
         );
         Ok(())
     }
@@ -510,6 +968,20 @@ impl Display for FolderElement {
                 querypath: _,
                 deletion_querypath: _,
             } => write!(f, "Exercise {name}"),
+            FolderElement::Group {
+                name,
+                description: _,
+                id: _,
+                querypath: _,
+                deletion_querypath: _,
+            } => write!(f, "Group {name}"),
+            FolderElement::Session {
+                name,
+                description: _,
+                id: _,
+                querypath: _,
+                deletion_querypath: _,
+            } => write!(f, "Session {name}"),
             FolderElement::Opencast {
                 name,
                 description: _,
@@ -522,8 +994,16 @@ impl Display for FolderElement {
                 description: _,
                 id: _,
                 querypath: _,
+                type_identifier: _,
                 deletion_querypath: _,
             } => write!(f, "Folder(-like) {name}"),
+            FolderElement::Unknown {
+                element,
+                deletion_querypath: _,
+            } => match &element.type_identifier {
+                Some(type_identifier) => write!(f, "Unknown ({type_identifier}) {}", element.name),
+                None => write!(f, "Unknown {}", element.name),
+            },
         }
     }
 }
@@ -0,0 +1,145 @@
+use std::sync::OnceLock;
+
+use log::info;
+use scraper::{Html, Selector};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use crate::{client::IliasClient, course::Course, form::HtmlForm, id::RefId};
+
+use super::{Folder, FolderElement, selected_ids};
+
+impl Folder {
+    /// Puts the elements on the clipboard to move them with [`Folder::paste`] or
+    /// [`Course::paste`], only possible for users that may edit the folder
+    pub fn cut(
+        &self,
+        ilias_client: &IliasClient,
+        elements: &[&FolderElement],
+    ) -> Result<(), Whatever> {
+        cut(ilias_client, self.ref_id(), elements)
+    }
+
+    /// Moves the elements on the clipboard into this folder
+    pub fn paste(&self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        paste(ilias_client, self.ref_id())
+    }
+
+    /// Copies the elements into the container `target`
+    pub fn copy_to(
+        &self,
+        ilias_client: &IliasClient,
+        elements: &[&FolderElement],
+        target: RefId,
+    ) -> Result<(), Whatever> {
+        copy_to(ilias_client, self.ref_id(), elements, target)
+    }
+}
+
+impl Course {
+    /// Puts the elements on the clipboard to move them with [`Folder::paste`] or
+    /// [`Course::paste`], only possible for users that may edit the course
+    pub fn cut(
+        &self,
+        ilias_client: &IliasClient,
+        elements: &[&FolderElement],
+    ) -> Result<(), Whatever> {
+        cut(ilias_client, self.id, elements)
+    }
+
+    /// Moves the elements on the clipboard into this course
+    pub fn paste(&self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        paste(ilias_client, self.id)
+    }
+
+    /// Copies the elements into the container `target`
+    pub fn copy_to(
+        &self,
+        ilias_client: &IliasClient,
+        elements: &[&FolderElement],
+        target: RefId,
+    ) -> Result<(), Whatever> {
+        copy_to(ilias_client, self.id, elements, target)
+    }
+}
+
+static ALERT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+fn has_alert(page: &Html) -> bool {
+    let alert_selector = ALERT_SELECTOR
+        .get_or_init(|| Selector::parse(".alert-danger").expect("Could not parse selector"));
+    page.select(alert_selector).next().is_some()
+}
+
+fn cut(
+    ilias_client: &IliasClient,
+    source: RefId,
+    elements: &[&FolderElement],
+) -> Result<(), Whatever> {
+    let response = ilias_client
+        .post_querypath_form(
+            &format!("ilias.php?baseClass=ilrepositorygui&cmd=cut&ref_id={source}"),
+            &selected_ids(elements)?,
+        )
+        .whatever_context("Could not cut elements")?;
+    if ilias_client.is_alert_response(response)? {
+        whatever!("Ilias rejected cutting elements from {source}");
+    }
+    info!("Cut {} elements from {source}", elements.len());
+    Ok(())
+}
+
+fn paste(ilias_client: &IliasClient, target: RefId) -> Result<(), Whatever> {
+    let page = ilias_client
+        .get_querypath(&format!(
+            "ilias.php?baseClass=ilrepositorygui&cmd=paste&ref_id={target}"
+        ))
+        .whatever_context("Could not paste clipboard")?;
+    if has_alert(&page) {
+        whatever!("Ilias rejected pasting into {target}, is the clipboard empty?");
+    }
+    info!("Pasted clipboard into {target}");
+    Ok(())
+}
+
+/// Copies through the copy wizard, which asks for the target and, for containers, which of
+/// their contents to copy. All contents are copied.
+fn copy_to(
+    ilias_client: &IliasClient,
+    source: RefId,
+    elements: &[&FolderElement],
+    target: RefId,
+) -> Result<(), Whatever> {
+    let response = ilias_client
+        .post_querypath_form(
+            &format!("ilias.php?baseClass=ilrepositorygui&cmd=copy&ref_id={source}"),
+            &selected_ids(elements)?,
+        )
+        .whatever_context("Could not start copying elements")?;
+    let target_page = Html::parse_document(&ilias_client.get_text(response)?);
+    let mut target_form = HtmlForm::find(&target_page, |form| form.has_button("saveTarget"))
+        .whatever_context("Did not find copy target selection")?;
+    target_form.set("target", &target.to_string());
+    let response = target_form
+        .submit(ilias_client, Some("saveTarget"))
+        .whatever_context("Could not select copy target")?;
+
+    let options_page = Html::parse_document(&ilias_client.get_text(response)?);
+    if has_alert(&options_page) {
+        whatever!("Ilias rejected copying elements into {target}");
+    }
+    if let Some(options_form) =
+        HtmlForm::find(&options_page, |form| form.has_button("copyContainer"))
+    {
+        let response = options_form
+            .submit(ilias_client, Some("copyContainer"))
+            .whatever_context("Could not confirm copy options")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected copying elements into {target}");
+        }
+    }
+    info!(
+        "Copied {} elements from {source} into {target}",
+        elements.len()
+    );
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use chrono::{DateTime, Local};
+
+use crate::{IliasElement, file::File};
+
+use super::{Folder, FolderElement};
+
+impl FolderElement {
+    /// The object type like `file` or `exc`, if the listing shows it
+    pub fn type_identifier(&self) -> Option<&str> {
+        match self {
+            Self::File { .. } => Some("file"),
+            Self::Exercise { .. } => Some("exc"),
+            Self::Group { .. } => Some("grp"),
+            Self::Session { .. } => Some("sess"),
+            Self::Opencast { .. } => Some("xoct"),
+            Self::Viewable {
+                type_identifier, ..
+            } => type_identifier.as_deref(),
+            Self::Unknown { element, .. } => element.type_identifier.as_deref(),
+        }
+    }
+
+    /// Matches the name against a case insensitive glob with `*` and `?`, e.g. `*.pdf`
+    pub fn matches_glob(&self, pattern: &str) -> bool {
+        glob_matches(
+            &pattern.to_lowercase().chars().collect::<Vec<_>>(),
+            &self.name().to_lowercase().chars().collect::<Vec<_>>(),
+        )
+    }
+
+    /// The date shown in the listing, which is only known for files
+    pub fn date(&self) -> Option<DateTime<Local>> {
+        self.file().and_then(|file| file.date)
+    }
+}
+
+impl Folder {
+    /// The elements that resolve to `T`, e.g. `folder.children_of_type::<Exercise>()`
+    pub fn children_of_type<T: IliasElement>(&self) -> impl Iterator<Item = &FolderElement> {
+        self.elements.iter().filter(|element| {
+            T::type_identifier()
+                .is_some_and(|type_identifier| element.type_identifier() == Some(type_identifier))
+        })
+    }
+
+    pub fn files(&self) -> impl Iterator<Item = &File> {
+        self.elements.iter().filter_map(FolderElement::file)
+    }
+
+    pub fn children_matching<'a>(
+        &'a self,
+        predicate: impl Fn(&FolderElement) -> bool + 'a,
+    ) -> impl Iterator<Item = &'a FolderElement> {
+        self.elements
+            .iter()
+            .filter(move |element| predicate(element))
+    }
+
+    /// Elements whose name matches `pattern`, see [`FolderElement::matches_glob`]
+    pub fn children_matching_glob<'a>(
+        &'a self,
+        pattern: &'a str,
+    ) -> impl Iterator<Item = &'a FolderElement> {
+        self.children_matching(move |element| element.matches_glob(pattern))
+    }
+
+    /// Files changed after `date`, elements without a date are left out
+    pub fn children_changed_since(
+        &self,
+        date: DateTime<Local>,
+    ) -> impl Iterator<Item = &FolderElement> {
+        self.children_matching(move |element| element.date().is_some_and(|changed| changed > date))
+    }
+}
+
+/// Two-pointer wildcard matching that only ever retries from the most recent `*`, so it takes
+/// at most `pattern.len() * name.len()` steps
+fn glob_matches(pattern: &[char], name: &[char]) -> bool {
+    let (mut pattern_index, mut name_index) = (0, 0);
+    // Position after the last `*` and the name position it is currently matched up to
+    let mut backtrack = None;
+    while name_index < name.len() {
+        match pattern.get(pattern_index) {
+            Some('*') => {
+                pattern_index += 1;
+                backtrack = Some((pattern_index, name_index));
+            }
+            Some(&expected) if expected == '?' || expected == name[name_index] => {
+                pattern_index += 1;
+                name_index += 1;
+            }
+            _ => {
+                // Let the last `*` swallow one more character and try again
+                let Some((star_pattern_index, star_name_index)) = backtrack else {
+                    return false;
+                };
+                pattern_index = star_pattern_index;
+                name_index = star_name_index + 1;
+                backtrack = Some((star_pattern_index, name_index));
+            }
+        }
+    }
+    pattern[pattern_index..]
+        .iter()
+        .all(|&wildcard| wildcard == '*')
+}
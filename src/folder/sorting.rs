@@ -0,0 +1,120 @@
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use crate::{client::IliasClient, course::Course, form::HtmlForm, id::RefId};
+
+use super::{Folder, FolderElement};
+
+/// How a container orders its elements, as set in its settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContainerSorting {
+    Title,
+    Manual,
+    Activation,
+    CreationDate,
+    /// Uses the sorting of the parent container
+    Inherit,
+}
+
+/// The sorting of a container and whether it is reversed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SortingSettings {
+    pub sorting: ContainerSorting,
+    pub descending: bool,
+}
+
+impl ContainerSorting {
+    /// The values of the `sorting` field in the container settings
+    fn from_value(value: &str) -> Option<ContainerSorting> {
+        match value {
+            "0" => Some(ContainerSorting::Title),
+            "1" => Some(ContainerSorting::Manual),
+            "2" => Some(ContainerSorting::Activation),
+            "3" => Some(ContainerSorting::Inherit),
+            "4" => Some(ContainerSorting::CreationDate),
+            _ => None,
+        }
+    }
+}
+
+impl Folder {
+    /// Reads the sorting from the settings, which only users that may edit the folder can see
+    pub fn sorting(&self, ilias_client: &IliasClient) -> Result<SortingSettings, Whatever> {
+        fetch_sorting(ilias_client, self.ref_id())
+    }
+
+    /// The elements in the given order instead of the configured one, see [`sort_elements`]
+    pub fn elements_sorted(&self, settings: SortingSettings) -> Vec<&FolderElement> {
+        sort_elements(&self.elements, settings)
+    }
+}
+
+impl Course {
+    /// Reads the sorting from the settings, which only users that may edit the course can see
+    pub fn sorting(&self, ilias_client: &IliasClient) -> Result<SortingSettings, Whatever> {
+        fetch_sorting(ilias_client, self.id)
+    }
+
+    /// The elements in the given order instead of the configured one, see [`sort_elements`]
+    pub fn elements_sorted(&self, settings: SortingSettings) -> Vec<&FolderElement> {
+        sort_elements(&self.elements, settings)
+    }
+}
+
+fn fetch_sorting(ilias_client: &IliasClient, ref_id: RefId) -> Result<SortingSettings, Whatever> {
+    let settings_page = ilias_client
+        .get_querypath(&format!(
+            "ilias.php?baseClass=ilrepositorygui&cmd=edit&ref_id={ref_id}"
+        ))
+        .whatever_context("Could not get container settings")?;
+    let form = HtmlForm::find(&settings_page, |form| form.has_field("sorting"))
+        .whatever_context("Container settings have no sorting, is the container editable?")?;
+    let value = |name: &str| {
+        form.fields
+            .iter()
+            .find(|(field, _)| field == name)
+            .map(|(_, value)| value.as_str())
+    };
+
+    Ok(SortingSettings {
+        sorting: value("sorting")
+            .and_then(ContainerSorting::from_value)
+            .whatever_context("Unknown container sorting")?,
+        descending: value("sorting_direction") == Some("1"),
+    })
+}
+
+/// Listings already come in the configured order, so this is only needed for a different one.
+///
+/// Listings show neither the manual position nor activation or creation dates, so those keep
+/// the listed order. Only files are ordered by their date for
+/// [`ContainerSorting::CreationDate`].
+pub fn sort_elements(elements: &[FolderElement], settings: SortingSettings) -> Vec<&FolderElement> {
+    let mut sorted = elements.iter().collect::<Vec<_>>();
+    match settings.sorting {
+        ContainerSorting::Title => {
+            sorted.sort_by_key(|element| element.name().to_lowercase());
+        }
+        ContainerSorting::CreationDate => {
+            // Dated files swap places among themselves, everything else stays where it is
+            let dated_positions = sorted
+                .iter()
+                .enumerate()
+                .filter(|(_, element)| element.file().is_some_and(|file| file.date.is_some()))
+                .map(|(position, _)| position)
+                .collect::<Vec<_>>();
+            let mut dated = dated_positions
+                .iter()
+                .map(|&position| sorted[position])
+                .collect::<Vec<_>>();
+            dated.sort_by_key(|element| element.file().and_then(|file| file.date));
+            for (position, element) in dated_positions.into_iter().zip(dated) {
+                sorted[position] = element;
+            }
+        }
+        ContainerSorting::Manual | ContainerSorting::Activation | ContainerSorting::Inherit => {}
+    }
+    if settings.descending {
+        sorted.reverse();
+    }
+    sorted
+}
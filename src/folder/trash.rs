@@ -0,0 +1,135 @@
+use std::sync::OnceLock;
+
+use log::info;
+use scraper::{Html, Selector};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use crate::{
+    client::IliasClient, course::Course, form::HtmlForm, id::RefId, table::Table, type_from_icon,
+};
+
+use super::Folder;
+
+/// An object that was deleted from a container while the trash is enabled
+#[derive(Debug, Clone)]
+pub struct TrashedObject {
+    pub name: String,
+    pub type_identifier: Option<String>,
+    pub deleted_by: Option<String>,
+    pub deleted_at: Option<String>,
+    pub ref_id: RefId,
+    form: HtmlForm,
+}
+
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CHECKBOX_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ICON_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl Folder {
+    /// The deleted objects of this folder, which only users that may edit it can see
+    pub fn trash(&self, ilias_client: &IliasClient) -> Result<Vec<TrashedObject>, Whatever> {
+        fetch_trash(ilias_client, self.ref_id())
+    }
+}
+
+impl Course {
+    /// The deleted objects of this course, which only users that may edit it can see
+    pub fn trash(&self, ilias_client: &IliasClient) -> Result<Vec<TrashedObject>, Whatever> {
+        fetch_trash(ilias_client, self.id)
+    }
+}
+
+impl TrashedObject {
+    /// Moves the object back into the container it was deleted from
+    pub fn restore(&self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        let response = self
+            .selected_form()
+            .submit(ilias_client, Some("undelete"))
+            .whatever_context(format!("Could not restore {}", self.name))?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected restoring {}", self.name);
+        }
+        info!("Restored {} from trash", self.name);
+        Ok(())
+    }
+
+    /// Removes the object from the trash, after this it can not be restored anymore. Needs
+    /// [`IliasClient::set_allow_deletion`].
+    pub fn remove_permanently(&self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        ilias_client.ensure_deletion_allowed()?;
+        let response = self
+            .selected_form()
+            .submit(ilias_client, Some("removeFromSystem"))
+            .whatever_context(format!("Could not remove {} from trash", self.name))?;
+        let confirmation_page = Html::parse_document(&ilias_client.get_text(response)?);
+        let confirmation = HtmlForm::find(&confirmation_page, |form| {
+            form.has_button("confirmRemoveFromSystem")
+        })
+        .whatever_context("Did not find confirmation to remove from trash")?;
+        let response = confirmation
+            .submit(ilias_client, Some("confirmRemoveFromSystem"))
+            .whatever_context(format!("Could not confirm removing {}", self.name))?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected removing {} from trash", self.name);
+        }
+        info!("Removed {} from trash", self.name);
+        Ok(())
+    }
+
+    /// The trash form with only this object selected
+    fn selected_form(&self) -> HtmlForm {
+        let mut form = self.form.clone();
+        form.set("trash_id[]", &self.ref_id.to_string());
+        form
+    }
+}
+
+fn fetch_trash(ilias_client: &IliasClient, ref_id: RefId) -> Result<Vec<TrashedObject>, Whatever> {
+    let table_selector = TABLE_SELECTOR
+        .get_or_init(|| Selector::parse("form table").expect("Could not parse selector"));
+    let checkbox_selector = CHECKBOX_SELECTOR.get_or_init(|| {
+        Selector::parse(r#"input[name="trash_id[]"]"#).expect("Could not parse selector")
+    });
+    let icon_selector =
+        ICON_SELECTOR.get_or_init(|| Selector::parse("img").expect("Could not parse selector"));
+
+    let trash_page = ilias_client
+        .get_querypath(&format!(
+            "ilias.php?baseClass=ilrepositorygui&cmd=trash&ref_id={ref_id}"
+        ))
+        .whatever_context("Could not get trash")?;
+    // Without deleted objects there is no form at all
+    let Some(mut form) = HtmlForm::find(&trash_page, |form| form.has_button("undelete")) else {
+        return Ok(vec![]);
+    };
+    form.fields.retain(|(name, _)| name != "trash_id[]");
+
+    let mut trashed = vec![];
+    for table in trash_page.select(table_selector) {
+        let table = Table::parse(table);
+        for row in &table.rows {
+            let Some(ref_id) = row
+                .iter()
+                .find_map(|cell| cell.select(checkbox_selector).next())
+                .and_then(|checkbox| checkbox.attr("value")?.parse().ok())
+            else {
+                continue;
+            };
+            trashed.push(TrashedObject {
+                name: table
+                    .cell_text(row, &["Titel", "Title"])
+                    .unwrap_or_default(),
+                type_identifier: row
+                    .iter()
+                    .find_map(|cell| cell.select(icon_selector).next())
+                    .and_then(|icon| icon.attr("src"))
+                    .and_then(type_from_icon),
+                deleted_by: table.cell_text(row, &["Gelöscht von", "Deleted by"]),
+                deleted_at: table.cell_text(row, &["Gelöscht am", "Deleted on"]),
+                ref_id,
+                form: form.clone(),
+            });
+        }
+    }
+    Ok(trashed)
+}
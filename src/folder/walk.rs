@@ -0,0 +1,155 @@
+use log::debug;
+use snafu::{ResultExt, Whatever};
+
+use crate::{IliasElement, client::IliasClient};
+
+use super::{Folder, FolderElement, parse_container_elements};
+
+/// An element somewhere below the walked folder, see [`Folder::walk`]
+#[derive(Debug, Clone)]
+pub struct WalkEntry {
+    /// 0 for elements directly in the walked folder
+    pub depth: usize,
+    /// Names of the containers between the walked folder and the element
+    pub parent_path: Vec<String>,
+    /// The item group the element is listed under in its container
+    pub section: Option<String>,
+    pub element: FolderElement,
+}
+
+/// Depth first iterator over a folder subtree. Containers are only fetched once the walk moves
+/// past them, so [`FolderWalk::skip_children`] avoids requesting subtrees that are not needed.
+#[derive(Debug)]
+pub struct FolderWalk<'a> {
+    ilias_client: &'a IliasClient,
+    pending: Vec<WalkEntry>,
+    unexpanded: Option<WalkEntry>,
+}
+
+impl<'a> FolderWalk<'a> {
+    pub(crate) fn new(ilias_client: &'a IliasClient, folder: &Folder) -> FolderWalk<'a> {
+        Self::from_elements(
+            ilias_client,
+            folder
+                .elements_with_sections()
+                .map(|(section, element)| (section.map(str::to_string), element.clone())),
+        )
+    }
+
+    /// Walks the given elements of a container and everything below them
+    pub(crate) fn from_elements(
+        ilias_client: &'a IliasClient,
+        elements: impl IntoIterator<Item = (Option<String>, FolderElement)>,
+    ) -> FolderWalk<'a> {
+        let mut pending = elements
+            .into_iter()
+            .map(|(section, element)| WalkEntry {
+                depth: 0,
+                parent_path: vec![],
+                section,
+                element,
+            })
+            .collect::<Vec<_>>();
+        pending.reverse();
+
+        FolderWalk {
+            ilias_client,
+            pending,
+            unexpanded: None,
+        }
+    }
+
+    /// Does not descend into the element returned last
+    pub fn skip_children(&mut self) {
+        self.unexpanded = None;
+    }
+
+    fn expand(&mut self, entry: WalkEntry) -> Result<(), Whatever> {
+        let Some(children) = children(self.ilias_client, &entry.element)? else {
+            return Ok(());
+        };
+
+        let mut parent_path = entry.parent_path;
+        parent_path.push(entry.element.name().to_string());
+        self.pending.extend(
+            children
+                .into_iter()
+                .rev()
+                .map(|(section, element)| WalkEntry {
+                    depth: entry.depth + 1,
+                    parent_path: parent_path.clone(),
+                    section,
+                    element,
+                }),
+        );
+        Ok(())
+    }
+}
+
+impl Iterator for FolderWalk<'_> {
+    type Item = Result<WalkEntry, Whatever>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(entry) = self.unexpanded.take() {
+            let name = entry.element.name().to_string();
+            if let Err(error) = self.expand(entry) {
+                return Some(Err(error).whatever_context(format!("Could not walk into {name}")));
+            }
+        }
+
+        let entry = self.pending.pop()?;
+        self.unexpanded = Some(entry.clone());
+        Some(Ok(entry))
+    }
+}
+
+/// Elements of a container with the item group they are listed under
+type Children = Vec<(Option<String>, FolderElement)>;
+
+/// The elements of a container, `None` for elements without children
+fn children(
+    ilias_client: &IliasClient,
+    element: &FolderElement,
+) -> Result<Option<Children>, Whatever> {
+    match element {
+        FolderElement::Viewable {
+            querypath,
+            type_identifier,
+            ..
+        } => match type_identifier.as_deref() {
+            Some(type_identifier) if Some(type_identifier) != Folder::type_identifier() => Ok(None),
+            Some(_) => folder_children(ilias_client, querypath).map(Some),
+            // Without an icon the type is unknown, so everything that parses as folder counts
+            None => Ok(folder_children(ilias_client, querypath)
+                .inspect_err(|error| debug!("Not walking into {querypath}: {error}"))
+                .ok()),
+        },
+        FolderElement::Unknown { element, .. }
+            if element.type_identifier.as_deref() == Folder::type_identifier() =>
+        {
+            folder_children(ilias_client, &element.querypath).map(Some)
+        }
+        FolderElement::Group { querypath, .. } | FolderElement::Session { querypath, .. } => {
+            let page = ilias_client.get_querypath(querypath)?;
+            Ok(Some(
+                parse_container_elements(page.root_element(), ilias_client)?
+                    .into_iter()
+                    .map(|element| (None, element))
+                    .collect(),
+            ))
+        }
+        FolderElement::File { .. }
+        | FolderElement::Exercise { .. }
+        | FolderElement::Opencast { .. }
+        | FolderElement::Unknown { .. } => Ok(None),
+    }
+}
+
+fn folder_children(ilias_client: &IliasClient, querypath: &str) -> Result<Children, Whatever> {
+    let page = ilias_client.get_querypath(querypath)?;
+    let folder = Folder::parse(page.root_element(), ilias_client)?;
+    Ok(folder
+        .elements_with_sections()
+        .map(|(section, element)| (section.map(str::to_string), element.clone()))
+        .collect())
+}
@@ -0,0 +1,99 @@
+use std::{path::Path, sync::OnceLock, thread, time::Duration};
+
+use log::{debug, info};
+use scraper::Selector;
+use snafu::{ResultExt, Whatever, whatever};
+
+use crate::{client::IliasClient, interstitial, querypath_from_href};
+
+use super::{Folder, FolderElement, selected_ids};
+
+static TASK_DOWNLOAD_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+/// Large selections are zipped by a background task, whose download link shows up in the
+/// notifications once it is done
+const BACKGROUND_TASK_QUERYPATH: &str =
+    "ilias.php?baseClass=ilUIPluginRouterGUI&cmdClass=ilBTControllerGUI&cmd=getPopoverContent";
+const BACKGROUND_TASK_POLL_INTERVAL: Duration = Duration::from_secs(2);
+const BACKGROUND_TASK_POLL_ATTEMPTS: usize = 90;
+
+impl Folder {
+    /// Lets ILIAS zip the elements and saves the zip to `to`, which is much faster than
+    /// downloading many files one by one. Returns the size of the zip.
+    pub fn download_zip(
+        &self,
+        ilias_client: &IliasClient,
+        elements: &[&FolderElement],
+        to: &Path,
+    ) -> Result<u64, Whatever> {
+        if elements.is_empty() {
+            whatever!("No elements selected to download as zip");
+        }
+        let ref_id = self.ref_id();
+        let form_data = selected_ids(elements)?;
+        let response = ilias_client
+            .post_querypath_form(
+                &format!("ilias.php?baseClass=ilrepositorygui&cmd=download&ref_id={ref_id}"),
+                &form_data,
+            )
+            .whatever_context("Could not request zip download")?;
+
+        // Small selections are zipped right away and sent as answer
+        if !interstitial::is_candidate(&response) {
+            let size = ilias_client.save_response(response, to)?;
+            info!(
+                "Downloaded {} elements of {} as zip",
+                elements.len(),
+                self.name
+            );
+            return Ok(size);
+        }
+        if ilias_client.is_alert_response(response)? {
+            whatever!(
+                "Ilias rejected downloading elements of {} as zip",
+                self.name
+            );
+        }
+
+        let querypath = wait_for_background_task(ilias_client)?;
+        ilias_client.download_file(&querypath, to)?;
+        info!(
+            "Downloaded {} elements of {} as zip from background task",
+            elements.len(),
+            self.name
+        );
+        Ok(to
+            .metadata()
+            .map(|metadata| metadata.len())
+            .unwrap_or_default())
+    }
+
+    /// Downloads all elements of the folder as zip, see [`Folder::download_zip`]
+    pub fn download_all_zip(&self, ilias_client: &IliasClient, to: &Path) -> Result<u64, Whatever> {
+        self.download_zip(ilias_client, &self.elements.iter().collect::<Vec<_>>(), to)
+    }
+}
+
+fn wait_for_background_task(ilias_client: &IliasClient) -> Result<String, Whatever> {
+    let task_download_selector = TASK_DOWNLOAD_SELECTOR.get_or_init(|| {
+        Selector::parse(
+            r#"a[href*="ilBTControllerGUI"][href*="download"], a[href*="ilbtcontrollergui"][href*="download"]"#,
+        )
+        .expect("Could not parse selector")
+    });
+
+    for attempt in 0..BACKGROUND_TASK_POLL_ATTEMPTS {
+        let popover = ilias_client
+            .get_querypath(BACKGROUND_TASK_QUERYPATH)
+            .whatever_context("Could not get background tasks")?;
+        if let Some(href) = popover
+            .select(task_download_selector)
+            .find_map(|link| link.attr("href"))
+        {
+            return Ok(querypath_from_href(href));
+        }
+        debug!("Zip is not ready yet after {attempt} attempts");
+        thread::sleep(BACKGROUND_TASK_POLL_INTERVAL);
+    }
+    whatever!("Background task did not finish the zip in time")
+}
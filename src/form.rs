@@ -0,0 +1,156 @@
+use std::sync::OnceLock;
+
+use reqwest::{
+    Response,
+    multipart::{Form, Part},
+};
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{client::IliasClient, querypath_from_href};
+
+/// A html form with the values it would submit in a browser
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct HtmlForm {
+    pub action: String,
+    pub fields: Vec<(String, String)>,
+    pub buttons: Vec<(String, String)>,
+}
+
+static INPUT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static SELECT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static SELECTED_OPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TEXTAREA_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static BUTTON_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl HtmlForm {
+    pub fn parse(form: ElementRef) -> Result<HtmlForm, Whatever> {
+        let input_selector = INPUT_SELECTOR
+            .get_or_init(|| Selector::parse("input[name]").expect("Could not parse selector"));
+        let select_selector = SELECT_SELECTOR
+            .get_or_init(|| Selector::parse("select[name]").expect("Could not parse selector"));
+        let selected_option_selector = SELECTED_OPTION_SELECTOR
+            .get_or_init(|| Selector::parse("option[selected]").expect("Could not parse selector"));
+        let textarea_selector = TEXTAREA_SELECTOR
+            .get_or_init(|| Selector::parse("textarea[name]").expect("Could not parse selector"));
+        let button_selector = BUTTON_SELECTOR
+            .get_or_init(|| Selector::parse("button[name]").expect("Could not parse selector"));
+
+        let action =
+            querypath_from_href(form.attr("action").whatever_context("Form has no action")?);
+
+        let mut fields = vec![];
+        let mut buttons = vec![];
+        for input in form.select(input_selector) {
+            let name = input.attr("name").unwrap_or_default().to_string();
+            let value = input.attr("value").unwrap_or_default().to_string();
+            match input.attr("type").unwrap_or("text") {
+                "submit" => buttons.push((name, value)),
+                "checkbox" | "radio" if input.attr("checked").is_none() => {}
+                "file" => {}
+                _ => fields.push((name, value)),
+            }
+        }
+        for select in form.select(select_selector) {
+            if let Some(option) = select.select(selected_option_selector).next() {
+                fields.push((
+                    select.attr("name").unwrap_or_default().to_string(),
+                    option.attr("value").unwrap_or_default().to_string(),
+                ));
+            }
+        }
+        for textarea in form.select(textarea_selector) {
+            fields.push((
+                textarea.attr("name").unwrap_or_default().to_string(),
+                textarea.text().collect(),
+            ));
+        }
+        for button in form.select(button_selector) {
+            buttons.push((
+                button.attr("name").unwrap_or_default().to_string(),
+                button.attr("value").unwrap_or_default().to_string(),
+            ));
+        }
+
+        Ok(HtmlForm {
+            action,
+            fields,
+            buttons,
+        })
+    }
+
+    /// The first form of the page that satisfies `predicate`, e.g. has a certain button
+    pub fn find(page: &Html, predicate: impl Fn(&HtmlForm) -> bool) -> Option<HtmlForm> {
+        let form_selector = FORM_SELECTOR
+            .get_or_init(|| Selector::parse("form[action]").expect("Could not parse selector"));
+
+        page.select(form_selector)
+            .filter_map(|form| HtmlForm::parse(form).ok())
+            .find(predicate)
+    }
+
+    /// Replaces all values of the field with a single value
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.fields.retain(|(field_name, _)| field_name != name);
+        self.fields.push((name.to_string(), value.to_string()));
+    }
+
+    /// Adds a value without replacing existing ones, e.g. for `name[]` fields
+    pub fn add(&mut self, name: &str, value: &str) {
+        self.fields.push((name.to_string(), value.to_string()));
+    }
+
+    pub fn has_field(&self, name: &str) -> bool {
+        self.fields.iter().any(|(field_name, _)| field_name == name)
+    }
+
+    pub fn has_button(&self, button: &str) -> bool {
+        self.buttons.iter().any(|(name, _)| name.contains(button))
+    }
+
+    fn form_data(&self, button: Option<&str>) -> Result<Vec<(String, String)>, Whatever> {
+        let mut form_data = self.fields.clone();
+        if let Some(button) = button {
+            let pressed = self
+                .buttons
+                .iter()
+                .find(|(name, _)| name.contains(button))
+                .whatever_context(format!("Form has no button {button}"))?;
+            form_data.push(pressed.clone());
+        }
+        Ok(form_data)
+    }
+
+    /// Submits the form, pressing the first button whose name contains `button`
+    pub fn submit(
+        &self,
+        ilias_client: &IliasClient,
+        button: Option<&str>,
+    ) -> Result<Response, Whatever> {
+        ilias_client
+            .post_querypath_form(&self.action, &self.form_data(button)?)
+            .whatever_context("Could not submit form")
+    }
+
+    /// Submits the form as multipart form together with the given file inputs
+    pub fn submit_with_files(
+        &self,
+        ilias_client: &IliasClient,
+        button: Option<&str>,
+        files: Vec<(String, Part)>,
+    ) -> Result<Response, Whatever> {
+        let mut form = Form::new();
+        for (name, value) in self.form_data(button)? {
+            form = form.text(name, value);
+        }
+        for (name, part) in files {
+            form = form.part(name, part);
+        }
+
+        ilias_client
+            .post_querypath_multipart(&self.action, form)
+            .whatever_context("Could not submit form")
+    }
+}
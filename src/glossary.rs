@@ -0,0 +1,180 @@
+use std::{collections::HashSet, sync::OnceLock};
+
+use log::debug;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement, client::IliasClient, id::RefId, page::Page, querypath_from_href,
+    reference::Reference, table::Table,
+};
+
+/// An ILIAS glossary (`glo`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Glossary {
+    pub name: String,
+    pub description: String,
+    pub terms: Vec<GlossaryTerm>,
+    /// Letters and table pages whose terms are not in `terms` yet
+    unvisited_querypaths: Vec<String>,
+    visited_querypaths: HashSet<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GlossaryTerm {
+    pub term: String,
+    /// The definitions as shown in the term list
+    pub definition_html: String,
+    /// Sources of images and other media in the definitions
+    pub media: Vec<String>,
+    page: Reference<Page>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static NAVIGATION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static MEDIA_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+const TERM_COLUMNS: [&str; 2] = ["Begriff", "Term"];
+const DEFINITION_COLUMNS: [&str; 1] = ["Definition"];
+
+impl IliasElement for Glossary {
+    fn type_identifier() -> Option<&'static str> {
+        Some("glo")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+
+        let mut glossary = Glossary {
+            name,
+            description,
+            terms: vec![],
+            unvisited_querypaths: vec![],
+            visited_querypaths: HashSet::new(),
+        };
+        glossary.add_terms(element);
+        debug!("Glossary: {:?}", glossary);
+
+        Ok(glossary)
+    }
+}
+
+impl Glossary {
+    /// All terms of the glossary, visiting every letter and table page not loaded yet
+    pub fn get_all_terms(
+        &mut self,
+        ilias_client: &IliasClient,
+    ) -> Result<&[GlossaryTerm], Whatever> {
+        while let Some(querypath) = self.unvisited_querypaths.pop() {
+            if !self.visited_querypaths.insert(querypath.clone()) {
+                continue;
+            }
+            let page = ilias_client
+                .get_querypath(&querypath)
+                .whatever_context("Could not get glossary page")?;
+            self.add_terms(page.root_element());
+        }
+
+        Ok(&self.terms)
+    }
+
+    fn add_terms(&mut self, element: ElementRef) {
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let navigation_selector = NAVIGATION_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilLetterNavigation a[href], .ilTableNav a[href]")
+                .expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+        let media_selector = MEDIA_SELECTOR.get_or_init(|| {
+            Selector::parse("img[src], video[src], audio[src], source[src]")
+                .expect("Could not parse selector")
+        });
+
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            if table.column(&TERM_COLUMNS).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(term_cell) = table.cell(row, &TERM_COLUMNS) else {
+                    continue;
+                };
+                let term = term_cell.text().collect::<String>().trim().to_string();
+                if self.terms.iter().any(|known| known.term == term) {
+                    continue;
+                }
+                let definition = table.cell(row, &DEFINITION_COLUMNS);
+
+                self.terms.push(GlossaryTerm {
+                    term,
+                    definition_html: definition
+                        .map(|definition| definition.inner_html().trim().to_string())
+                        .unwrap_or_default(),
+                    media: definition
+                        .into_iter()
+                        .flat_map(|definition| definition.select(media_selector))
+                        .filter_map(|media| media.attr("src"))
+                        .map(str::to_string)
+                        .collect(),
+                    page: Reference::from_optional_querypath(
+                        term_cell
+                            .select(link_selector)
+                            .next()
+                            .and_then(|link| link.attr("href"))
+                            .map(querypath_from_href),
+                    ),
+                });
+            }
+        }
+
+        for link in element.select(navigation_selector) {
+            let querypath = querypath_from_href(link.attr("href").unwrap_or_default());
+            if !self.visited_querypaths.contains(&querypath) {
+                self.unvisited_querypaths.push(querypath);
+            }
+        }
+    }
+}
+
+impl GlossaryTerm {
+    /// The page of the term with all its definitions
+    pub fn get_page(&mut self, ilias_client: &IliasClient) -> Result<Option<&Page>, Whatever> {
+        self.page
+            .resolve_cached(ilias_client)
+            .whatever_context("Could not parse glossary term")
+    }
+}
@@ -0,0 +1,187 @@
+use std::sync::OnceLock;
+
+use log::{debug, info};
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use super::{
+    IliasContainer, IliasElement,
+    client::IliasClient,
+    folder::{FolderElement, parse_container_elements},
+    form::HtmlForm,
+    id::RefId,
+    info_screen::InfoScreen,
+    locator::{Ancestor, parse_locator},
+    querypath_from_href,
+};
+
+/// An ILIAS group (`grp`), e.g. a tutorial group inside a course
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Group {
+    pub name: String,
+    pub description: String,
+    pub id: RefId,
+    /// Only visible for members of the group
+    pub elements: Vec<FolderElement>,
+    pub max_members: Option<u32>,
+    pub free_places: Option<u32>,
+    join_querypath: Option<String>,
+    locator: Vec<Ancestor>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PERMALINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static JOIN_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static JOIN_FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl IliasElement for Group {
+    fn type_identifier() -> Option<&'static str> {
+        Some("grp")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    /// Parses the content page for members or the info page with the join button otherwise
+    fn parse(element: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let permalink_selector = PERMALINK_SELECTOR.get_or_init(|| {
+            Selector::parse("#current_perma_link").expect("Could not parse selector")
+        });
+        let join_link_selector = JOIN_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"#ilContentContainer a[href*="ilgroupregistrationgui"], #ilContentContainer a[href*="cmd=join"]"#)
+                .expect("Could not parse selector")
+        });
+        let id_regex = ID_REGEX.get_or_init(|| {
+            Regex::new(r"(ref_id=|grp/|grp_)(?<id>\d+)").expect("Could not parse regex")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+        let permalink = element
+            .select(permalink_selector)
+            .next()
+            .and_then(|permalink| permalink.attr("value"))
+            .whatever_context("Could not find permalink")?;
+        let id = id_regex
+            .captures(permalink)
+            .whatever_context(format!("Could not find id in {permalink}"))?["id"]
+            .parse()?;
+
+        let join_querypath = element
+            .select(join_link_selector)
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(querypath_from_href);
+        // Non-members only get to see the info page without any content
+        let elements = if join_querypath.is_none() {
+            parse_container_elements(element, ilias_client).unwrap_or_default()
+        } else {
+            vec![]
+        };
+
+        let info = InfoScreen::parse(element, ilias_client)?;
+        let number_of = |names: &[&str]| {
+            info.property(names)
+                .and_then(|property| property.value.trim().parse().ok())
+        };
+
+        let group = Group {
+            name,
+            description,
+            id,
+            elements,
+            max_members: number_of(&[
+                "Max. Mitglieder",
+                "Maximale Anzahl Mitglieder",
+                "Max. Members",
+                "Maximum Number of Members",
+            ]),
+            free_places: number_of(&["Freie Plätze", "Free Places", "Available Places"]),
+            join_querypath,
+            locator: parse_locator(element),
+        };
+        debug!("Group: {:?}", group);
+
+        Ok(group)
+    }
+}
+
+impl IliasContainer for Group {
+    fn children(&self, _ilias_client: &IliasClient) -> Result<Vec<FolderElement>, Whatever> {
+        Ok(self.elements.clone())
+    }
+}
+
+impl Group {
+    /// The containers above the group, starting at the repository root
+    pub fn path(&self) -> &[Ancestor] {
+        &self.locator
+    }
+
+    /// Whether the group offers to join it, i.e. the user is not a member yet
+    pub fn can_join(&self) -> bool {
+        self.join_querypath.is_some()
+    }
+
+    pub fn has_free_places(&self) -> bool {
+        self.free_places.is_none_or(|free_places| free_places > 0)
+    }
+
+    pub fn join(&mut self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        let join_form_selector = JOIN_FORM_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer form[action]").expect("Could not parse selector")
+        });
+
+        let join_page = ilias_client.get_querypath(
+            self.join_querypath
+                .as_ref()
+                .whatever_context(format!("Can not join group {}", self.name))?,
+        )?;
+        let form = join_page
+            .select(join_form_selector)
+            .map(HtmlForm::parse)
+            .filter_map(Result::ok)
+            .find(|form| form.buttons.iter().any(|(name, _)| name.contains("join")))
+            .whatever_context("Could not find registration form")?;
+
+        let response = form
+            .submit(ilias_client, Some("join"))
+            .whatever_context("Could not submit registration")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected joining group {}", self.name);
+        }
+
+        info!("Joined group {}", self.name);
+        self.join_querypath = None;
+        self.free_places = self
+            .free_places
+            .map(|free_places| free_places.saturating_sub(1));
+        Ok(())
+    }
+}
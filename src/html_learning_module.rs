@@ -0,0 +1,135 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use log::debug;
+use reqwest::Url;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    ILIAS_URL, IliasElement, client::IliasClient, id::RefId, local_file::unpack_zip,
+    mirror::mirror, querypath_from_href,
+};
+
+/// An ILIAS HTML learning module (`htlm`), a set of uploaded HTML pages shown in a frame
+#[derive(Debug, Serialize, Deserialize)]
+pub struct HtmlLearningModule {
+    pub name: String,
+    /// The zip of the module files, only offered if downloads are enabled for the module
+    download_querypath: Option<String>,
+    /// The start page of the module, usually below `data/<client>/lm_data`
+    start_querypath: Option<String>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DOWNLOAD_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FRAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for HtmlLearningModule {
+    fn type_identifier() -> Option<&'static str> {
+        Some("htlm")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header, title").expect("Could not parse selector")
+        });
+        let download_link_selector = DOWNLOAD_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#"a[href*="cmd=downloadFile"], a[href*="downloadExportFile"], a[href*="exportHTML"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+        let frame_selector = FRAME_SELECTOR.get_or_init(|| {
+            Selector::parse("frame[src], iframe[src]").expect("Could not parse selector")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let download_querypath = element
+            .select(download_link_selector)
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(querypath_from_href);
+        let frames = element
+            .select(frame_selector)
+            .filter_map(|frame| frame.attr("src"))
+            .collect::<Vec<_>>();
+        let start_querypath = frames
+            .iter()
+            .find(|src| src.contains("lm_data"))
+            .or(frames.first())
+            .map(|src| querypath_from_href(src));
+
+        let html_learning_module = HtmlLearningModule {
+            name,
+            download_querypath,
+            start_querypath,
+        };
+        debug!("HTML learning module: {:?}", html_learning_module);
+
+        Ok(html_learning_module)
+    }
+}
+
+impl HtmlLearningModule {
+    pub fn has_download(&self) -> bool {
+        self.download_querypath.is_some()
+    }
+
+    /// Downloads the zip export of the module
+    pub fn download(&self, ilias_client: &IliasClient, to: &Path) -> Result<(), Whatever> {
+        ilias_client.download_file(
+            self.download_querypath
+                .as_ref()
+                .whatever_context(format!("{} offers no download", self.name))?,
+            to,
+        )
+    }
+
+    /// Puts the module into `directory`, from its export if it offers one and otherwise by
+    /// mirroring its pages with links rewritten to the local copies.
+    ///
+    /// Returns the local path of the start page if the module was mirrored.
+    pub fn archive(
+        &self,
+        ilias_client: &IliasClient,
+        directory: &Path,
+    ) -> Result<Option<PathBuf>, Whatever> {
+        fs::create_dir_all(directory).whatever_context("Could not create archive directory")?;
+        if self.has_download() {
+            let export = directory.join("export.zip");
+            self.download(ilias_client, &export)?;
+            unpack_zip(&export, directory)?;
+            return Ok(None);
+        }
+
+        let start_querypath = self
+            .start_querypath
+            .as_ref()
+            .whatever_context(format!("Could not find start page of {}", self.name))?;
+        let start = Url::parse(ILIAS_URL)
+            .whatever_context("Could not parse ilias url")?
+            .join(start_querypath)
+            .whatever_context("Could not resolve start page")?;
+        mirror(ilias_client, &start, directory).map(Some)
+    }
+}
@@ -0,0 +1,97 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::{debug, warn};
+use reqwest::{
+    Url,
+    header::{ETAG, HeaderMap, HeaderValue, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED},
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use snafu::{ResultExt, Whatever};
+
+use crate::{client::write_private, hex};
+
+/// On-disk cache of pages, shared by all clients that point at the same directory, see
+/// [`IliasClient::set_cache_directory`](crate::client::IliasClient::set_cache_directory).
+///
+/// Only pages the server sent an `ETag` or `Last-Modified` for are kept, and they are only
+/// reused after the server answered a conditional request with `304 Not Modified`, so the cache
+/// saves transfers but never returns outdated content.
+#[derive(Debug)]
+pub(crate) struct HttpCache {
+    directory: PathBuf,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct CacheEntry {
+    etag: Option<String>,
+    last_modified: Option<String>,
+    pub body: String,
+}
+
+impl HttpCache {
+    pub fn new(directory: &Path) -> Result<HttpCache, Whatever> {
+        fs::create_dir_all(directory).whatever_context("Could not create cache directory")?;
+        Ok(HttpCache {
+            directory: directory.to_path_buf(),
+        })
+    }
+
+    pub fn lookup(&self, url: &Url) -> Option<CacheEntry> {
+        let entry = fs::read(self.entry_path(url)).ok()?;
+        serde_json::from_slice(&entry).ok()
+    }
+
+    /// Headers asking the server to only send the page if it changed since it was cached
+    pub fn conditional_headers(entry: &CacheEntry) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        let validators = [
+            (IF_NONE_MATCH, &entry.etag),
+            (IF_MODIFIED_SINCE, &entry.last_modified),
+        ];
+        for (name, value) in validators {
+            if let Some(value) = value
+                && let Ok(value) = HeaderValue::from_str(value)
+            {
+                headers.insert(name, value);
+            }
+        }
+        headers
+    }
+
+    /// Keeps the page if the response carries validators. Other clients may read the entry at
+    /// any time, which is safe as entries are replaced atomically.
+    pub fn store(&self, url: &Url, headers: &HeaderMap, body: &str) {
+        let header = |name| {
+            headers
+                .get(name)
+                .and_then(|value: &HeaderValue| value.to_str().ok())
+                .map(str::to_string)
+        };
+        let entry = CacheEntry {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+            body: body.to_string(),
+        };
+        if entry.etag.is_none() && entry.last_modified.is_none() {
+            return;
+        }
+
+        let path = self.entry_path(url);
+        let result = serde_json::to_vec(&entry)
+            .map_err(std::io::Error::other)
+            .and_then(|entry| write_private(&path, &entry));
+        match result {
+            Ok(()) => debug!("Cached {url}"),
+            Err(error) => warn!("Could not cache {url}: {error}"),
+        }
+    }
+
+    fn entry_path(&self, url: &Url) -> PathBuf {
+        let name = hex(&Sha256::digest(url.as_str()));
+        self.directory.join(format!("{name}.json"))
+    }
+}
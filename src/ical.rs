@@ -0,0 +1,159 @@
+use chrono::{DateTime, Duration, Local, Utc};
+
+use super::{calendar::Appointment, exercise::assignment::Assignment};
+
+/// An RFC 5545 calendar built from assignment deadlines and appointments
+#[derive(Debug, Clone)]
+pub struct ICalendar {
+    pub name: String,
+    /// Adds a reminder this long before every event
+    pub alarm: Option<Duration>,
+    events: Vec<ICalEvent>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ICalEvent {
+    pub uid: String,
+    pub summary: String,
+    pub description: String,
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    pub location: Option<String>,
+}
+
+impl ICalendar {
+    pub fn new(name: &str) -> ICalendar {
+        ICalendar {
+            name: name.to_string(),
+            alarm: None,
+            events: vec![],
+        }
+    }
+
+    pub fn add_event(&mut self, event: ICalEvent) {
+        self.events.push(event);
+    }
+
+    /// Adds an event at the deadline of every assignment that has one
+    pub fn add_assignments<'a>(
+        &mut self,
+        exercise_name: &str,
+        assignments: impl IntoIterator<Item = &'a Assignment>,
+    ) {
+        for assignment in assignments {
+            let Some(deadline) = assignment.deadline() else {
+                continue;
+            };
+            self.events.push(ICalEvent {
+                uid: uid(&format!("{exercise_name}/{}", assignment.name)),
+                summary: format!("{exercise_name}: {}", assignment.name),
+                description: assignment.instructions.clone().unwrap_or_default(),
+                start: deadline,
+                end: None,
+                location: None,
+            });
+        }
+    }
+
+    pub fn add_appointments<'a>(
+        &mut self,
+        appointments: impl IntoIterator<Item = &'a Appointment>,
+    ) {
+        for appointment in appointments {
+            self.events.push(ICalEvent {
+                uid: uid(&format!(
+                    "{}/{}",
+                    appointment.title,
+                    appointment.start.timestamp()
+                )),
+                summary: appointment.title.clone(),
+                description: appointment.description.clone(),
+                start: appointment.start,
+                end: appointment.end,
+                location: appointment.location.clone(),
+            });
+        }
+    }
+
+    pub fn to_ics(&self) -> String {
+        let now = format_datetime(Local::now());
+        let mut lines = vec![
+            "BEGIN:VCALENDAR".to_string(),
+            "VERSION:2.0".to_string(),
+            "PRODID:-//ilias-rs//ilias-rs//EN".to_string(),
+            "CALSCALE:GREGORIAN".to_string(),
+            format!("X-WR-CALNAME:{}", escape(&self.name)),
+        ];
+
+        for event in &self.events {
+            lines.push("BEGIN:VEVENT".to_string());
+            lines.push(format!("UID:{}", event.uid));
+            lines.push(format!("DTSTAMP:{now}"));
+            lines.push(format!("DTSTART:{}", format_datetime(event.start)));
+            lines.push(format!(
+                "DTEND:{}",
+                format_datetime(event.end.unwrap_or(event.start))
+            ));
+            lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+            if !event.description.is_empty() {
+                lines.push(format!("DESCRIPTION:{}", escape(&event.description)));
+            }
+            if let Some(location) = &event.location {
+                lines.push(format!("LOCATION:{}", escape(location)));
+            }
+            if let Some(alarm) = self.alarm {
+                lines.push("BEGIN:VALARM".to_string());
+                lines.push("ACTION:DISPLAY".to_string());
+                lines.push(format!("DESCRIPTION:{}", escape(&event.summary)));
+                lines.push(format!("TRIGGER:-PT{}M", alarm.num_minutes()));
+                lines.push("END:VALARM".to_string());
+            }
+            lines.push("END:VEVENT".to_string());
+        }
+        lines.push("END:VCALENDAR".to_string());
+
+        lines
+            .iter()
+            .map(|line| fold(line))
+            .collect::<Vec<_>>()
+            .join("\r\n")
+            + "\r\n"
+    }
+}
+
+fn format_datetime(date: DateTime<Local>) -> String {
+    date.with_timezone(&Utc)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+/// Folds content lines longer than 75 octets as required by RFC 5545
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut line_length = 0;
+    for character in line.chars() {
+        if line_length + character.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            line_length = 1;
+        }
+        folded.push(character);
+        line_length += character.len_utf8();
+    }
+    folded
+}
+
+/// A uid that stays the same across exports so calendar apps update instead of duplicating events
+fn uid(key: &str) -> String {
+    let hash = key.bytes().fold(0xcbf29ce484222325u64, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+    });
+    format!("{hash:016x}@ilias-rs")
+}
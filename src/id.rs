@@ -0,0 +1,85 @@
+use std::{fmt::Display, str::FromStr, sync::OnceLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use snafu::{ResultExt, Whatever};
+
+/// Identifies a place of an object in the repository. Objects that are linked into several
+/// containers have one ref_id per place but share their [`ObjId`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct RefId(pub u64);
+
+/// Identifies an object itself, regardless of where it is linked in the repository. Entries
+/// outside of the repository like mails, bookmarks or news are identified the same way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ObjId(pub u64);
+
+static REF_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+static OBJ_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl RefId {
+    /// Finds the ref_id in a querypath or permalink, e.g. `ref_id=123`, `goto.php/fold/123`,
+    /// `target=fold_123` or `goto_produktiv_fold_123.html`
+    pub fn from_querypath(querypath: &str) -> Option<RefId> {
+        let ref_id_regex = REF_ID_REGEX.get_or_init(|| {
+            Regex::new(
+                r"(ref_id=|target=[a-z]+_|(?:goto\.php|\bgo)/[a-z]+/|goto_[a-z0-9]+_[a-z]+_)(?<id>\d+)",
+            )
+            .expect("Could not parse regex")
+        });
+        ref_id_regex
+            .captures(querypath)
+            .and_then(|captures| captures["id"].parse().ok())
+            .map(RefId)
+    }
+}
+
+impl ObjId {
+    /// Finds the obj_id in a querypath, e.g. `obj_id=123`
+    pub fn from_querypath(querypath: &str) -> Option<ObjId> {
+        let obj_id_regex = OBJ_ID_REGEX
+            .get_or_init(|| Regex::new(r"obj_id=(?<id>\d+)").expect("Could not parse regex"));
+        obj_id_regex
+            .captures(querypath)
+            .and_then(|captures| captures["id"].parse().ok())
+            .map(ObjId)
+    }
+}
+
+impl Display for RefId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl Display for ObjId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for RefId {
+    type Err = Whatever;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Ok(RefId(
+            id.trim()
+                .parse()
+                .whatever_context(format!("Not a ref_id: {id}"))?,
+        ))
+    }
+}
+
+impl FromStr for ObjId {
+    type Err = Whatever;
+
+    fn from_str(id: &str) -> Result<Self, Self::Err> {
+        Ok(ObjId(
+            id.trim()
+                .parse()
+                .whatever_context(format!("Not an obj_id: {id}"))?,
+        ))
+    }
+}
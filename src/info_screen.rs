@@ -0,0 +1,128 @@
+use std::sync::OnceLock;
+
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::Whatever;
+
+use super::{IliasElement, client::IliasClient, id::RefId, querypath_from_href};
+
+/// The "Info" tab that most repository objects provide
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfoScreen {
+    pub sections: Vec<InfoSection>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfoSection {
+    pub title: String,
+    pub properties: Vec<InfoProperty>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfoProperty {
+    pub name: String,
+    pub value: String,
+    pub links: Vec<InfoLink>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfoLink {
+    pub text: String,
+    pub querypath: String,
+}
+
+static SECTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static SECTION_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PROPERTY_ROW_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PROPERTY_NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PROPERTY_VALUE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for InfoScreen {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let section_selector = SECTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilInfoScreenSec").expect("Could not parse selector"));
+        let section_title_selector = SECTION_TITLE_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeader").expect("Could not parse selector"));
+        let property_row_selector = PROPERTY_ROW_SELECTOR
+            .get_or_init(|| Selector::parse(".form-group").expect("Could not parse selector"));
+        let property_name_selector = PROPERTY_NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il_InfoScreenProperty").expect("Could not parse selector")
+        });
+        let property_value_selector = PROPERTY_VALUE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il_InfoScreenPropertyValue").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        let mut sections = vec![];
+        for section in element.select(section_selector) {
+            let title = section
+                .select(section_title_selector)
+                .next()
+                .map(|title| title.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let mut properties = vec![];
+            for row in section.select(property_row_selector) {
+                let (Some(name), Some(value)) = (
+                    row.select(property_name_selector).next(),
+                    row.select(property_value_selector).next(),
+                ) else {
+                    continue;
+                };
+
+                let links = value
+                    .select(link_selector)
+                    .filter_map(|link| {
+                        Some(InfoLink {
+                            text: link.text().collect::<String>().trim().to_string(),
+                            querypath: querypath_from_href(link.attr("href")?),
+                        })
+                    })
+                    .collect();
+
+                properties.push(InfoProperty {
+                    name: name.text().collect::<String>().trim().to_string(),
+                    value: value.text().collect::<String>().trim().to_string(),
+                    links,
+                });
+            }
+
+            sections.push(InfoSection { title, properties });
+        }
+
+        Ok(InfoScreen { sections })
+    }
+}
+
+impl InfoScreen {
+    pub fn section(&self, titles: &[&str]) -> Option<&InfoSection> {
+        self.sections
+            .iter()
+            .find(|section| titles.contains(&section.title.as_str()))
+    }
+
+    pub fn property(&self, names: &[&str]) -> Option<&InfoProperty> {
+        self.sections
+            .iter()
+            .flat_map(|section| &section.properties)
+            .find(|property| names.contains(&property.name.as_str()))
+    }
+}
+
+impl InfoSection {
+    pub fn property(&self, names: &[&str]) -> Option<&InfoProperty> {
+        self.properties
+            .iter()
+            .find(|property| names.contains(&property.name.as_str()))
+    }
+}
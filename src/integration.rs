@@ -0,0 +1,75 @@
+use std::env;
+
+use reqwest::Url;
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement,
+    client::{IliasClient, LoggedOut},
+    folder::Folder,
+    group::Group,
+    id::RefId,
+    reference::Reference,
+};
+
+/// A local ILIAS installation to run flows against, e.g. the one of
+/// `integration/docker-compose.yml`.
+///
+/// Configured through the environment:
+/// - `ILIAS_TEST_URL`, defaults to `http://localhost:8080`
+/// - `ILIAS_TEST_USER` and `ILIAS_TEST_PASSWORD`, default to the ILIAS setup account `root`/`homer`
+/// - `ILIAS_TEST_FOLDER`, the ref_id of a seeded folder the user may upload to and delete in
+/// - `ILIAS_TEST_GROUP`, the ref_id of a seeded group the user is not a member of yet
+#[derive(Debug, Clone)]
+pub struct TestInstance {
+    pub base_url: Url,
+    pub username: String,
+    pub password: String,
+    pub folder_id: Option<RefId>,
+    pub group_id: Option<RefId>,
+}
+
+impl TestInstance {
+    pub fn from_env() -> Result<TestInstance, Whatever> {
+        let variable = |name: &str| env::var(name).ok().filter(|value| !value.is_empty());
+
+        Ok(TestInstance {
+            base_url: Url::parse(
+                &variable("ILIAS_TEST_URL").unwrap_or("http://localhost:8080".to_string()),
+            )
+            .whatever_context("Could not parse ILIAS_TEST_URL")?,
+            username: variable("ILIAS_TEST_USER").unwrap_or("root".to_string()),
+            password: variable("ILIAS_TEST_PASSWORD").unwrap_or("homer".to_string()),
+            folder_id: variable("ILIAS_TEST_FOLDER")
+                .map(|id| id.parse())
+                .transpose()
+                .whatever_context("Could not parse ILIAS_TEST_FOLDER")?,
+            group_id: variable("ILIAS_TEST_GROUP")
+                .map(|id| id.parse())
+                .transpose()
+                .whatever_context("Could not parse ILIAS_TEST_GROUP")?,
+        })
+    }
+
+    /// Logs in with the local account of the instance
+    pub fn login(&self) -> Result<IliasClient, Whatever> {
+        IliasClient::<LoggedOut>::new(self.base_url.clone())?
+            .login_local(&self.username, &self.password)
+    }
+
+    pub fn folder(&self, ilias_client: &IliasClient) -> Result<Folder, Whatever> {
+        let id = self
+            .folder_id
+            .whatever_context("ILIAS_TEST_FOLDER is not set")?;
+        Reference::<Folder>::from_optional_querypath(Folder::querypath_from_id(id))
+            .resolve(ilias_client)
+    }
+
+    pub fn group(&self, ilias_client: &IliasClient) -> Result<Group, Whatever> {
+        let id = self
+            .group_id
+            .whatever_context("ILIAS_TEST_GROUP is not set")?;
+        Reference::<Group>::from_optional_querypath(Group::querypath_from_id(id))
+            .resolve(ilias_client)
+    }
+}
@@ -0,0 +1,74 @@
+use std::sync::OnceLock;
+
+use reqwest::{
+    Response,
+    header::{CONTENT_DISPOSITION, CONTENT_TYPE},
+};
+use scraper::{Html, Selector};
+
+use super::{form::HtmlForm, querypath_from_href};
+
+/// How to get from an info or confirmation page to the file it stands in front of
+#[derive(Debug)]
+pub(crate) enum Interstitial {
+    Link(String),
+    Form {
+        form: HtmlForm,
+        button: Option<(String, String)>,
+    },
+}
+
+static DOWNLOAD_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+/// Names of buttons that start the download on a confirmation page
+const DOWNLOAD_BUTTONS: [&str; 4] = ["download", "deliver", "sendfile", "herunterladen"];
+
+/// Downloads that ILIAS answers with a page instead of the file. Files that are html themselves
+/// are sent as attachment, so they are not mistaken for one.
+pub(crate) fn is_candidate(response: &Response) -> bool {
+    let is_html = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|content_type| content_type.to_str().ok())
+        .is_some_and(|content_type| content_type.starts_with("text/html"));
+    let is_attachment = response
+        .headers()
+        .get(CONTENT_DISPOSITION)
+        .and_then(|disposition| disposition.to_str().ok())
+        .is_some_and(|disposition| disposition.starts_with("attachment"));
+    is_html && !is_attachment
+}
+
+/// Finds the link or form on `page` that leads to the actual file
+pub(crate) fn find(page: &Html) -> Option<Interstitial> {
+    let download_link_selector = DOWNLOAD_LINK_SELECTOR.get_or_init(|| {
+        Selector::parse(
+            r#"#ilContentContainer a[href*="cmd=sendfile"], #ilContentContainer a[href*="cmd=download"], #ilContentContainer a[href*="deliverFile"]"#,
+        )
+        .expect("Could not parse selector")
+    });
+
+    if let Some(href) = page
+        .select(download_link_selector)
+        .find_map(|link| link.attr("href"))
+    {
+        return Some(Interstitial::Link(querypath_from_href(href)));
+    }
+
+    let form = HtmlForm::find(page, |form| {
+        form.buttons
+            .iter()
+            .any(|(name, _)| is_download_button(name))
+    })?;
+    let button = form
+        .buttons
+        .iter()
+        .find(|(name, _)| is_download_button(name))
+        .cloned();
+    Some(Interstitial::Form { form, button })
+}
+
+fn is_download_button(name: &str) -> bool {
+    let name = name.to_lowercase();
+    DOWNLOAD_BUTTONS.iter().any(|button| name.contains(button))
+}
@@ -0,0 +1,156 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::Whatever;
+
+use super::{
+    IliasElement, client::IliasClient, id::RefId, querypath_from_href, table::Table, type_from_icon,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LearningProgress {
+    NotAttempted,
+    InProgress,
+    Completed,
+    Failed,
+}
+
+/// The learning progress of all objects in a course, from its "Learning Progress" tab
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LearningProgressOverview {
+    pub objects: Vec<ObjectProgress>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ObjectProgress {
+    pub title: String,
+    pub type_identifier: Option<String>,
+    pub querypath: Option<String>,
+    pub status: Option<LearningProgress>,
+}
+
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ICON_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+const TITLE_COLUMNS: [&str; 2] = ["Titel", "Title"];
+const STATUS_COLUMNS: [&str; 3] = ["Status", "Lernfortschritt", "Learning Progress"];
+
+impl LearningProgress {
+    pub fn from_text(text: &str) -> Option<LearningProgress> {
+        let text = text.trim().to_lowercase();
+        if text.contains("nicht begonnen") || text.contains("not attempted") {
+            Some(LearningProgress::NotAttempted)
+        } else if text.contains("nicht bestanden") || text.contains("failed") {
+            Some(LearningProgress::Failed)
+        } else if text.contains("in bearbeitung") || text.contains("in progress") {
+            Some(LearningProgress::InProgress)
+        } else if text.contains("bearbeitet")
+            || text.contains("bestanden")
+            || text.contains("completed")
+            || text.contains("passed")
+        {
+            Some(LearningProgress::Completed)
+        } else {
+            None
+        }
+    }
+
+    /// Status icons are named after the status, e.g. `learning_progress/in_progress.svg`
+    pub fn from_icon(src: &str) -> Option<LearningProgress> {
+        let file_name = src.rsplit('/').next()?;
+        match file_name.split('.').next()? {
+            "not_attempted" | "not_started" => Some(LearningProgress::NotAttempted),
+            "in_progress" | "incomplete" => Some(LearningProgress::InProgress),
+            "completed" | "passed" => Some(LearningProgress::Completed),
+            "failed" => Some(LearningProgress::Failed),
+            _ => None,
+        }
+    }
+
+    /// Reads the status from a table cell or item property showing it as icon or text
+    pub(crate) fn from_element(element: ElementRef) -> Option<LearningProgress> {
+        let icon_selector = ICON_SELECTOR
+            .get_or_init(|| Selector::parse("img[src]").expect("Could not parse selector"));
+
+        element
+            .select(icon_selector)
+            .find_map(|icon| {
+                icon.attr("src")
+                    .and_then(Self::from_icon)
+                    .or_else(|| icon.attr("alt").and_then(Self::from_text))
+            })
+            .or_else(|| Self::from_text(&element.text().collect::<String>()))
+    }
+}
+
+impl IliasElement for LearningProgressOverview {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+        let icon_selector = ICON_SELECTOR
+            .get_or_init(|| Selector::parse("img[src]").expect("Could not parse selector"));
+
+        let mut objects = vec![];
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            if table.column(&TITLE_COLUMNS).is_none() || table.column(&STATUS_COLUMNS).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(title_cell) = table.cell(row, &TITLE_COLUMNS) else {
+                    continue;
+                };
+                objects.push(ObjectProgress {
+                    title: title_cell.text().collect::<String>().trim().to_string(),
+                    type_identifier: row
+                        .iter()
+                        .flat_map(|cell| cell.select(icon_selector))
+                        .find_map(|icon| type_from_icon(icon.attr("src")?)),
+                    querypath: title_cell
+                        .select(link_selector)
+                        .next()
+                        .and_then(|link| link.attr("href"))
+                        .map(querypath_from_href),
+                    status: table
+                        .cell(row, &STATUS_COLUMNS)
+                        .and_then(LearningProgress::from_element),
+                });
+            }
+        }
+        debug!("Learning progress: {:?}", objects);
+
+        Ok(LearningProgressOverview { objects })
+    }
+}
+
+impl LearningProgressOverview {
+    pub fn completed(&self) -> impl Iterator<Item = &ObjectProgress> {
+        self.objects
+            .iter()
+            .filter(|object| object.status == Some(LearningProgress::Completed))
+    }
+
+    pub fn open(&self) -> impl Iterator<Item = &ObjectProgress> {
+        self.objects.iter().filter(|object| {
+            matches!(
+                object.status,
+                Some(LearningProgress::NotAttempted | LearningProgress::InProgress)
+            )
+        })
+    }
+}
@@ -0,0 +1,171 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement, client::IliasClient, id::RefId, learning_progress::LearningProgress,
+    querypath_from_href, reference::Reference,
+};
+
+/// An ILIAS learning sequence (`lso`), objects that have to be worked through in order
+#[derive(Debug, Serialize, Deserialize)]
+pub struct LearningSequence {
+    pub name: String,
+    pub description: String,
+    pub steps: Vec<LearningSequenceStep>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LearningSequenceStep {
+    pub title: String,
+    pub description: String,
+    pub status: Option<LearningProgress>,
+    /// Whether the step can be opened, later steps are locked until the previous are completed
+    pub available: bool,
+    /// The ref_id of the underlying object
+    pub ref_id: Option<RefId>,
+    /// The step in the player of the sequence, only present if the step is available
+    pub querypath: Option<String>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static STEP_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static STEP_LABEL_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static STEP_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static ITEM_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl IliasElement for LearningSequence {
+    fn type_identifier() -> Option<&'static str> {
+        Some("lso")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let step_selector = STEP_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-workflow-step").expect("Could not parse selector")
+        });
+        let step_label_selector = STEP_LABEL_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-workflow-step-label").expect("Could not parse selector")
+        });
+        let step_description_selector = STEP_DESCRIPTION_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-workflow-step-description").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR.get_or_init(|| {
+            Selector::parse("a[href], button[data-action]").expect("Could not parse selector")
+        });
+        let item_id_regex = ITEM_ID_REGEX
+            .get_or_init(|| Regex::new(r"lsitemid=(?<id>\d+)").expect("Could not parse regex"));
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+
+        let steps = element
+            .select(step_selector)
+            .filter_map(|step| {
+                let label = step.select(step_label_selector).next()?;
+                let has_class = |name: &str| {
+                    step.value()
+                        .has_class(name, scraper::CaseSensitivity::AsciiCaseInsensitive)
+                };
+                let querypath = label
+                    .select(link_selector)
+                    .next()
+                    .and_then(|link| link.attr("href").or_else(|| link.attr("data-action")))
+                    .map(querypath_from_href);
+
+                Some(LearningSequenceStep {
+                    title: label.text().collect::<String>().trim().to_string(),
+                    description: step
+                        .select(step_description_selector)
+                        .next()
+                        .map(|description| {
+                            description.text().collect::<String>().trim().to_string()
+                        })
+                        .unwrap_or_default(),
+                    status: if has_class("completed_successfully") {
+                        Some(LearningProgress::Completed)
+                    } else if has_class("completed_unsuccessfully") {
+                        Some(LearningProgress::Failed)
+                    } else if has_class("in_progress") {
+                        Some(LearningProgress::InProgress)
+                    } else if has_class("not_started") {
+                        Some(LearningProgress::NotAttempted)
+                    } else {
+                        LearningProgress::from_element(step)
+                    },
+                    available: querypath.is_some()
+                        && !has_class("not_available")
+                        && !has_class("no_longer_available"),
+                    ref_id: querypath
+                        .as_ref()
+                        .and_then(|querypath| item_id_regex.captures(querypath))
+                        .and_then(|captures| captures["id"].parse().ok()),
+                    querypath,
+                })
+            })
+            .collect();
+
+        let learning_sequence = LearningSequence {
+            name,
+            description,
+            steps,
+        };
+        debug!("Learning sequence: {:?}", learning_sequence);
+
+        Ok(learning_sequence)
+    }
+}
+
+impl LearningSequence {
+    pub fn available_steps(&self) -> impl Iterator<Item = &LearningSequenceStep> {
+        self.steps.iter().filter(|step| step.available)
+    }
+
+    /// The first step that is not completed yet, where working through the sequence continues
+    pub fn next_step(&self) -> Option<&LearningSequenceStep> {
+        self.steps
+            .iter()
+            .find(|step| step.status != Some(LearningProgress::Completed))
+    }
+}
+
+impl LearningSequenceStep {
+    /// Opens the step in the player and parses the object shown there, e.g. as `ContentPage`.
+    ///
+    /// Opening a step counts as visiting it, which may unlock the following steps.
+    pub fn resolve<T: IliasElement>(&self, ilias_client: &IliasClient) -> Result<T, Whatever> {
+        Reference::<T>::from_optional_querypath(self.querypath.clone())
+            .resolve(ilias_client)
+            .whatever_context(format!("Could not open step {}", self.title))
+    }
+}
@@ -1,26 +1,111 @@
 use chrono::{DateTime, Days, Local, NaiveTime, TimeZone};
 use client::IliasClient;
+use folder::FolderElement;
+use id::RefId;
 use regex::Regex;
 use reqwest::Url;
 use scraper::ElementRef;
 use snafu::{OptionExt, ResultExt, Whatever};
 
+pub mod achievements;
+pub mod agreement;
+pub mod blog;
+pub mod booking;
+pub mod bookmark;
+pub mod calendar;
+pub mod category;
+mod chunked_upload;
 pub mod client;
+pub mod confirmation;
+pub mod content_page;
+pub mod course;
+pub mod dashboard;
+pub mod data_collection;
+#[cfg(feature = "encryption")]
+pub mod encryption;
 pub mod exercise;
 pub mod file;
+mod fixture;
 pub mod folder;
+mod form;
+pub mod glossary;
+pub mod group;
+pub mod html_learning_module;
+mod http_cache;
+pub mod ical;
+pub mod id;
+pub mod info_screen;
+#[cfg(feature = "integration")]
+pub mod integration;
+mod interstitial;
+pub mod learning_progress;
+pub mod learning_sequence;
 pub mod local_file;
+pub mod locator;
+pub mod lockfile;
+pub mod mail;
+pub mod media_pool;
+pub mod mediacast;
+pub mod membership;
+mod mirror;
+pub mod news;
+pub mod opencast;
+pub mod overview;
+pub mod page;
+pub mod politeness;
+pub mod poll;
+pub mod portfolio;
 pub mod reference;
+pub mod schedule;
+pub mod scorm;
+pub mod search;
+pub mod session;
+pub mod survey;
+pub mod sync;
+mod table;
+pub mod test;
+pub mod unknown;
+pub mod weblink;
 
 pub const ILIAS_URL: &str = "https://ilias.studium.kit.edu";
 
 pub trait IliasElement: Sized {
     fn type_identifier() -> Option<&'static str>;
-    fn querypath_from_id(id: &str) -> Option<String>;
+    fn querypath_from_id(id: RefId) -> Option<String>;
 
     fn parse(element: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever>;
 }
 
+/// Objects that list other objects, so traversal code does not need to match on their type
+pub trait IliasContainer: IliasElement {
+    /// The objects directly inside the container, as listed when it was parsed
+    fn children(&self, ilias_client: &IliasClient) -> Result<Vec<FolderElement>, Whatever>;
+}
+
+const MONTHS: [&[&str]; 12] = [
+    &["Jan"],
+    &["Feb"],
+    &["Mär", "Mar"],
+    &["Apr"],
+    &["Mai", "May"],
+    &["Jun"],
+    &["Jul"],
+    &["Aug"],
+    &["Sep"],
+    &["Okt", "Oct"],
+    &["Nov"],
+    &["Dez", "Dec"],
+];
+
+/// Parses abbreviated or full month names ("Mär", "March", "Oktober") to their number
+fn parse_month(name: &str) -> Option<u32> {
+    let abbreviation: String = name.chars().take(3).collect();
+    MONTHS
+        .iter()
+        .position(|names| names.contains(&abbreviation.as_str()))
+        .map(|index| index as u32 + 1)
+}
+
 fn parse_date(date_string: &str) -> Result<DateTime<Local>, Whatever> {
     let (date, time) = date_string.split_once(',').whatever_context(format!(
         "Could not separate date and time in {}",
@@ -39,21 +124,6 @@ fn parse_date(date_string: &str) -> Result<DateTime<Local>, Whatever> {
     } else if ["Morgen", "Tomorrow"].contains(&date) {
         Local::now() + Days::new(1)
     } else {
-        let months: [&[&str]; 12] = [
-            &["Jan"],
-            &["Feb"],
-            &["Mär", "Mar"],
-            &["Apr"],
-            &["Mai", "May"],
-            &["Jun"],
-            &["Jul"],
-            &["Aug"],
-            &["Sep"],
-            &["Okt", "Oct"],
-            &["Nov"],
-            &["Dez", "Dec"],
-        ];
-
         let date_regex = Regex::new(r"^(?<day>\d+)\. (?<month>\w+) (?<year>\w+)$")
             .whatever_context("Could not parse regex")?;
         let date_split = date_regex
@@ -67,17 +137,8 @@ fn parse_date(date_string: &str) -> Result<DateTime<Local>, Whatever> {
         let day: u32 = day
             .parse()
             .whatever_context(format!("Could not parse day: {day}"))?;
-        let month = months
-            .iter()
-            .enumerate()
-            .find_map(|(index, &names)| {
-                if names.contains(&month) {
-                    Some(index as u32 + 1)
-                } else {
-                    None
-                }
-            })
-            .whatever_context(format!("Could not parse month {}", month))?;
+        let month =
+            parse_month(month).whatever_context(format!("Could not parse month {}", month))?;
         let year: i32 = year
             .parse()
             .whatever_context(format!("Could not parse year: {year}"))?;
@@ -95,6 +156,35 @@ fn parse_date(date_string: &str) -> Result<DateTime<Local>, Whatever> {
     Ok(datetime)
 }
 
+/// Parses localized numbers like "12,5" or "60,00 %"
+fn parse_number(number: &str) -> Option<f64> {
+    let number = number.trim().trim_end_matches('%').trim();
+    if number.contains(',') {
+        number.replace('.', "").replace(',', ".").parse().ok()
+    } else {
+        number.parse().ok()
+    }
+}
+
+/// Object types can be recognized by their icons, e.g. `icon_crs.svg`
+fn type_from_icon(src: &str) -> Option<String> {
+    let file_name = src.rsplit('/').next()?;
+    let type_identifier = file_name.strip_prefix("icon_")?.split('.').next()?;
+    Some(type_identifier.to_string())
+}
+
+/// Links in ILIAS pages are sometimes absolute, sometimes relative to the installation
+fn querypath_from_href(href: &str) -> String {
+    Url::parse(href)
+        .map(|url| url.get_querypath())
+        .unwrap_or_else(|_| href.to_string())
+}
+
+/// Lowercase hex, e.g. for hashes
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
 pub trait Querypath {
     fn get_querypath(&self) -> String;
     fn set_querypath(&mut self, querypath: &str);
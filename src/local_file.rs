@@ -1,4 +1,10 @@
-use std::path::PathBuf;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use snafu::{ResultExt, Whatever};
+use zip::ZipArchive;
 
 /// A file on the local file system and the name it should have when uploaded
 #[derive(Debug, Clone)]
@@ -6,3 +12,46 @@ pub struct NamedLocalFile {
     pub name: String,
     pub path: PathBuf,
 }
+
+/// Makes a name from ILIAS usable as a single path component, so that names like `../x` or
+/// `a/b` can not point outside the directory they are joined to
+pub fn file_name_component(name: &str) -> String {
+    let component = name.replace(['/', '\\', '\0'], "_");
+    match component.as_str() {
+        "" | "." | ".." => "_".to_string(),
+        _ => component,
+    }
+}
+
+/// The paths of all files below `directory`, relative to it
+pub fn files_below(directory: &Path) -> Result<Vec<PathBuf>, Whatever> {
+    let mut files = vec![];
+    let mut unvisited = vec![PathBuf::new()];
+    while let Some(relative) = unvisited.pop() {
+        let entries = fs::read_dir(directory.join(&relative))
+            .whatever_context(format!("Could not list {}", relative.display()))?;
+        for entry in entries {
+            let entry = entry.whatever_context("Could not read directory entry")?;
+            let path = relative.join(entry.file_name());
+            let file_type = entry
+                .file_type()
+                .whatever_context(format!("Could not read type of {}", path.display()))?;
+            if file_type.is_dir() {
+                unvisited.push(path);
+            } else if file_type.is_file() {
+                files.push(path);
+            }
+        }
+    }
+    Ok(files)
+}
+
+/// Unpacks a zip archive, e.g. an exported learning module, into `directory`
+pub fn unpack_zip(archive: &Path, directory: &Path) -> Result<(), Whatever> {
+    let file = fs::File::open(archive).whatever_context("Could not open archive")?;
+    let mut archive = ZipArchive::new(file).whatever_context("Could not read archive")?;
+    fs::create_dir_all(directory).whatever_context("Could not create unpack directory")?;
+    archive
+        .extract(directory)
+        .whatever_context("Could not unpack archive")
+}
@@ -0,0 +1,46 @@
+use std::{path::PathBuf, sync::OnceLock};
+
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+
+use super::{id::RefId, querypath_from_href};
+
+/// A container above an object, as listed in the breadcrumbs (the "locator") of its page
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ancestor {
+    pub title: String,
+    pub ref_id: Option<RefId>,
+    pub querypath: String,
+}
+
+static LOCATOR_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+/// The ancestors from the repository root down to the parent. The object itself is the last
+/// breadcrumb, so it is left out.
+pub(crate) fn parse_locator(element: ElementRef) -> Vec<Ancestor> {
+    let locator_selector = LOCATOR_SELECTOR.get_or_init(|| {
+        Selector::parse(".breadcrumbs a[href], .ilLocator a[href]")
+            .expect("Could not parse selector")
+    });
+
+    let mut ancestors = element
+        .select(locator_selector)
+        .filter_map(|link| {
+            let querypath = querypath_from_href(link.attr("href")?);
+            Some(Ancestor {
+                title: link.text().collect::<String>().trim().to_string(),
+                ref_id: RefId::from_querypath(&querypath),
+                querypath,
+            })
+        })
+        .collect::<Vec<_>>();
+    ancestors.pop();
+    ancestors
+}
+
+/// The directory that mirrors the hierarchy of `path` locally, e.g. `Course/Folder`
+pub fn local_directory(path: &[Ancestor]) -> PathBuf {
+    path.iter()
+        .map(|ancestor| ancestor.title.replace(['/', '\\'], "_"))
+        .collect()
+}
@@ -0,0 +1,91 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, SystemTime},
+};
+
+use log::{info, warn};
+use snafu::{ResultExt, Whatever, whatever};
+
+/// Exclusive lock on a sync target directory, released when dropped.
+///
+/// Keeps overlapping syncs (e.g. from cron) from writing into the same directory at once.
+#[derive(Debug)]
+pub struct DirectoryLock {
+    path: PathBuf,
+}
+
+impl DirectoryLock {
+    pub const FILE_NAME: &str = ".ilias-sync.lock";
+
+    /// Takes the lock of `directory`.
+    ///
+    /// A lock that was not refreshed for longer than `stale_after` is assumed to be left behind
+    /// by a crashed sync and taken over.
+    pub fn acquire(directory: &Path, stale_after: Duration) -> Result<DirectoryLock, Whatever> {
+        fs::create_dir_all(directory).whatever_context("Could not create sync directory")?;
+        let path = directory.join(Self::FILE_NAME);
+
+        for _ in 0..2 {
+            match OpenOptions::new().write(true).create_new(true).open(&path) {
+                Ok(mut file) => {
+                    writeln!(file, "{}", process::id())
+                        .whatever_context("Could not write lockfile")?;
+                    return Ok(DirectoryLock { path });
+                }
+                Err(error) if error.kind() == ErrorKind::AlreadyExists => {
+                    let age = fs::metadata(&path)
+                        .and_then(|metadata| metadata.modified())
+                        .ok()
+                        .and_then(|modified| SystemTime::now().duration_since(modified).ok());
+                    let owner = fs::read_to_string(&path).unwrap_or_default();
+                    match age {
+                        Some(age) if age > stale_after => {
+                            warn!(
+                                "Removing stale lock of process {} on {}",
+                                owner.trim(),
+                                directory.display()
+                            );
+                            fs::remove_file(&path)
+                                .or_else(|error| match error.kind() {
+                                    ErrorKind::NotFound => Ok(()),
+                                    _ => Err(error),
+                                })
+                                .whatever_context("Could not remove stale lockfile")?;
+                        }
+                        _ => whatever!(
+                            "{} is locked by process {}",
+                            directory.display(),
+                            owner.trim()
+                        ),
+                    }
+                }
+                Err(error) => {
+                    return Err(error).whatever_context("Could not create lockfile");
+                }
+            }
+        }
+
+        whatever!("Could not take over the lock of {}", directory.display())
+    }
+
+    /// Marks the lock as still in use, long syncs should call this more often than `stale_after`
+    pub fn refresh(&self) -> Result<(), Whatever> {
+        OpenOptions::new()
+            .write(true)
+            .open(&self.path)
+            .and_then(|file| file.set_modified(SystemTime::now()))
+            .whatever_context("Could not refresh lockfile")
+    }
+}
+
+impl Drop for DirectoryLock {
+    fn drop(&mut self) {
+        match fs::remove_file(&self.path) {
+            Ok(()) => info!("Released {}", self.path.display()),
+            Err(error) => warn!("Could not remove {}: {error}", self.path.display()),
+        }
+    }
+}
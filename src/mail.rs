@@ -0,0 +1,330 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+use log::{debug, info};
+use regex::Regex;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use super::{
+    IliasElement,
+    client::IliasClient,
+    file::File,
+    form::HtmlForm,
+    id::{ObjId, RefId},
+    local_file::NamedLocalFile,
+    parse_date, querypath_from_href,
+    reference::Reference,
+    table::Table,
+};
+
+/// The inbox of the internal ILIAS mail system
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Mailbox {
+    pub mails: Vec<MailSummary>,
+}
+
+/// A row of the inbox, the message itself is fetched on demand
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MailSummary {
+    pub id: ObjId,
+    pub sender: String,
+    pub subject: String,
+    pub date: Option<DateTime<Local>>,
+    pub read: bool,
+    message: Reference<Mail>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Mail {
+    pub sender: String,
+    pub recipients: Vec<String>,
+    pub subject: String,
+    pub date: Option<DateTime<Local>>,
+    pub body: String,
+    pub attachments: Vec<File>,
+}
+
+/// A mail to compose and send, recipients are given by their login
+#[derive(Debug, Clone, Default)]
+pub struct NewMail {
+    pub to: Vec<String>,
+    pub cc: Vec<String>,
+    pub subject: String,
+    pub body: String,
+    pub attachments: Vec<NamedLocalFile>,
+}
+
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static UNREAD_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FORM_GROUP_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FORM_LABEL_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ATTACHMENT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static MAIL_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+const SENDER_COLUMNS: [&str; 4] = ["Von", "Absender", "From", "Sender"];
+const SUBJECT_COLUMNS: [&str; 2] = ["Betreff", "Subject"];
+const DATE_COLUMNS: [&str; 2] = ["Datum", "Date"];
+
+impl IliasElement for Mailbox {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+        let unread_selector = UNREAD_SELECTOR
+            .get_or_init(|| Selector::parse(".mailunread").expect("Could not parse selector"));
+        let mail_id_regex = MAIL_ID_REGEX
+            .get_or_init(|| Regex::new(r"mail_id=(?<id>\d+)").expect("Could not parse regex"));
+
+        let mut mails = vec![];
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            if table.column(&SUBJECT_COLUMNS).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(subject_cell) = table.cell(row, &SUBJECT_COLUMNS) else {
+                    continue;
+                };
+                let Some(querypath) = subject_cell
+                    .select(link_selector)
+                    .next()
+                    .and_then(|link| link.attr("href"))
+                    .map(querypath_from_href)
+                else {
+                    continue;
+                };
+                let Some(id) = mail_id_regex
+                    .captures(&querypath)
+                    .and_then(|captures| captures["id"].parse().ok())
+                else {
+                    continue;
+                };
+                let unread = subject_cell.select(unread_selector).next().is_some()
+                    || subject_cell
+                        .parent()
+                        .and_then(ElementRef::wrap)
+                        .is_some_and(|row| {
+                            row.value().classes().any(|class| class == "mailunread")
+                        });
+
+                mails.push(MailSummary {
+                    id,
+                    sender: table.cell_text(row, &SENDER_COLUMNS).unwrap_or_default(),
+                    subject: subject_cell.text().collect::<String>().trim().to_string(),
+                    date: table
+                        .cell_text(row, &DATE_COLUMNS)
+                        .and_then(|date| parse_date(&date).ok()),
+                    read: !unread,
+                    message: Reference::Unresolved(querypath),
+                });
+            }
+        }
+        debug!("Mails: {:?}", mails);
+
+        Ok(Mailbox { mails })
+    }
+}
+
+impl Mailbox {
+    pub const QUERYPATH: &str = "ilias.php?baseClass=ilMailGUI";
+
+    pub fn fetch(ilias_client: &IliasClient) -> Result<Mailbox, Whatever> {
+        let page = ilias_client
+            .get_querypath(Self::QUERYPATH)
+            .whatever_context("Could not get mail inbox")?;
+        Mailbox::parse(page.root_element(), ilias_client)
+    }
+
+    pub fn unread(&self) -> impl Iterator<Item = &MailSummary> {
+        self.mails.iter().filter(|mail| !mail.read)
+    }
+}
+
+impl MailSummary {
+    /// Fetches the message, which also marks it as read in ILIAS
+    pub fn get_message(&mut self, ilias_client: &IliasClient) -> Result<Option<&Mail>, Whatever> {
+        let message = self
+            .message
+            .resolve_cached_with(ilias_client, |_, _| self.read = true)
+            .whatever_context("Could not parse mail")?;
+        Ok(message.map(|message| &*message))
+    }
+}
+
+impl IliasElement for Mail {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let form_group_selector = FORM_GROUP_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer .form-group").expect("Could not parse selector")
+        });
+        let form_label_selector = FORM_LABEL_SELECTOR
+            .get_or_init(|| Selector::parse("label").expect("Could not parse selector"));
+        let attachment_selector = ATTACHMENT_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"a[href*="cmd=deliverFile"], a[href*="deliverAttachment"]"#)
+                .expect("Could not parse selector")
+        });
+
+        let properties = element
+            .select(form_group_selector)
+            .filter_map(|group| {
+                let label = group.select(form_label_selector).next()?;
+                // The value is the last column of the form row
+                let value = group
+                    .children()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|child| child.value().name() == "div")
+                    .last()?;
+                Some((label.text().collect::<String>().trim().to_string(), value))
+            })
+            .collect::<Vec<_>>();
+        let property = |names: &[&str]| {
+            properties
+                .iter()
+                .find(|(name, _)| names.contains(&name.as_str()))
+                .map(|(_, value)| value.text().collect::<String>().trim().to_string())
+        };
+
+        let subject = property(&["Betreff", "Subject"]).whatever_context("Mail has no subject")?;
+        let date = property(&["Datum", "Date"]).and_then(|date| parse_date(&date).ok());
+        let attachments = element
+            .select(attachment_selector)
+            .map(|link| File {
+                name: link.text().collect::<String>().trim().to_string(),
+                description: String::new(),
+                date,
+                download_querypath: link.attr("href").map(querypath_from_href),
+                id: None,
+                delivered_id: None,
+                license: None,
+                size: None,
+                uploader: None,
+            })
+            .collect();
+
+        let mail = Mail {
+            sender: property(&["Von", "Absender", "From", "Sender"]).unwrap_or_default(),
+            recipients: property(&["An", "To", "Empfänger", "Recipient"])
+                .map(|recipients| {
+                    recipients
+                        .split([',', ';'])
+                        .map(|recipient| recipient.trim().to_string())
+                        .filter(|recipient| !recipient.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            subject,
+            date,
+            body: property(&["Nachricht", "Message"]).unwrap_or_default(),
+            attachments,
+        };
+        debug!("Mail: {:?}", mail);
+
+        Ok(mail)
+    }
+}
+
+impl NewMail {
+    pub const COMPOSE_QUERYPATH: &str = "ilias.php?baseClass=ilMailGUI&type=new";
+
+    pub fn new(to: &[&str], subject: &str, body: &str) -> NewMail {
+        NewMail {
+            to: to.iter().map(|login| login.to_string()).collect(),
+            subject: subject.to_string(),
+            body: body.to_string(),
+            ..Default::default()
+        }
+    }
+
+    pub fn send(&self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        let page = ilias_client
+            .get_querypath(Self::COMPOSE_QUERYPATH)
+            .whatever_context("Could not get mail form")?;
+        let mut form = Self::compose_form(&page)?;
+        self.fill(&mut form);
+
+        if !self.attachments.is_empty() {
+            let page = self.attach_files(ilias_client, &form)?;
+            form = Self::compose_form(&page)?;
+            self.fill(&mut form);
+        }
+
+        let response = form
+            .submit(ilias_client, Some("sendMessage"))
+            .whatever_context("Could not send mail")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected the mail {}", self.subject);
+        }
+
+        info!("Sent mail {} to {:?}", self.subject, self.to);
+        Ok(())
+    }
+
+    fn fill(&self, form: &mut HtmlForm) {
+        form.set("rcp_to", &self.to.join(", "));
+        form.set("rcp_cc", &self.cc.join(", "));
+        form.set("m_subject", &self.subject);
+        form.set("m_message", &self.body);
+    }
+
+    /// Uploads the attachments to the mail file storage and selects them for this mail,
+    /// returning the mail form page ILIAS redirects back to
+    fn attach_files(&self, ilias_client: &IliasClient, form: &HtmlForm) -> Result<Html, Whatever> {
+        let response = form
+            .submit(ilias_client, Some("editAttachments"))
+            .whatever_context("Could not open attachments of mail")?;
+        let mut page = Html::parse_document(&ilias_client.get_text(response)?);
+
+        for attachment in &self.attachments {
+            let upload_form = HtmlForm::find(&page, |form| form.has_button("uploadFile"))
+                .whatever_context("Could not find attachment upload form")?;
+            let file_part = ilias_client
+                .construct_file_part(&attachment.path)?
+                .file_name(attachment.name.clone());
+            let response = upload_form
+                .submit_with_files(
+                    ilias_client,
+                    Some("uploadFile"),
+                    vec![("userfile".to_string(), file_part)],
+                )
+                .whatever_context(format!("Could not upload attachment {}", attachment.name))?;
+            page = Html::parse_document(&ilias_client.get_text(response)?);
+        }
+
+        let mut selection_form = HtmlForm::find(&page, |form| form.has_button("saveAttachments"))
+            .whatever_context("Could not find attachment selection form")?;
+        for attachment in &self.attachments {
+            selection_form.add("filename[]", &attachment.name);
+        }
+        let response = selection_form
+            .submit(ilias_client, Some("saveAttachments"))
+            .whatever_context("Could not select attachments")?;
+        Ok(Html::parse_document(&ilias_client.get_text(response)?))
+    }
+
+    fn compose_form(page: &Html) -> Result<HtmlForm, Whatever> {
+        HtmlForm::find(page, |form| form.has_field("m_subject"))
+            .whatever_context("Could not find mail form")
+    }
+}
@@ -0,0 +1,214 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement, client::IliasClient, file::File, id::RefId, querypath_from_href, table::Table,
+    type_from_icon,
+};
+
+/// An ILIAS media pool (`mep`), the images and videos that learning modules and pages embed
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MediaPool {
+    pub name: String,
+    pub description: String,
+    /// Subfolders of the currently listed folder, see [`MediaPool::get_folder`]
+    pub folders: Vec<MediaPoolFolder>,
+    pub media: Vec<MediaObject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaPoolFolder {
+    pub title: String,
+    pub querypath: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediaObject {
+    pub title: String,
+    pub item_id: Option<String>,
+    /// The thumbnail shown in the listing
+    pub preview_src: Option<String>,
+    /// The page showing the media object, see [`MediaObject::get_file`]
+    pub querypath: Option<String>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ICON_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PREVIEW_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static MEDIA_SOURCE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static ITEM_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+const TITLE_COLUMNS: [&str; 2] = ["Titel", "Title"];
+
+impl IliasElement for MediaPool {
+    fn type_identifier() -> Option<&'static str> {
+        Some("mep")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+        let icon_selector = ICON_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"img[src*="icon_"]"#).expect("Could not parse selector")
+        });
+        let preview_selector = PREVIEW_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"img[src*="mobs/"]"#).expect("Could not parse selector")
+        });
+        let item_id_regex = ITEM_ID_REGEX
+            .get_or_init(|| Regex::new(r"mepitem_id=(?<id>\d+)").expect("Could not parse regex"));
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+
+        let mut folders = vec![];
+        let mut media = vec![];
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            if table.column(&TITLE_COLUMNS).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(title_cell) = table.cell(row, &TITLE_COLUMNS) else {
+                    continue;
+                };
+                let link = title_cell.select(link_selector).next();
+                let title = link
+                    .unwrap_or(title_cell)
+                    .text()
+                    .collect::<String>()
+                    .trim()
+                    .to_string();
+                let querypath = link
+                    .and_then(|link| link.attr("href"))
+                    .map(querypath_from_href);
+                let type_identifier = row
+                    .iter()
+                    .flat_map(|cell| cell.select(icon_selector))
+                    .find_map(|icon| icon.attr("src").and_then(type_from_icon));
+
+                if type_identifier.as_deref() == Some("fold") {
+                    if let Some(querypath) = querypath {
+                        folders.push(MediaPoolFolder { title, querypath });
+                    }
+                    continue;
+                }
+                media.push(MediaObject {
+                    title,
+                    item_id: querypath
+                        .as_ref()
+                        .and_then(|querypath| item_id_regex.captures(querypath))
+                        .map(|captures| captures["id"].to_string()),
+                    preview_src: row
+                        .iter()
+                        .flat_map(|cell| cell.select(preview_selector))
+                        .find_map(|preview| preview.attr("src"))
+                        .map(str::to_string),
+                    querypath,
+                });
+            }
+        }
+
+        let media_pool = MediaPool {
+            name,
+            description,
+            folders,
+            media,
+        };
+        debug!("Media pool: {:?}", media_pool);
+
+        Ok(media_pool)
+    }
+}
+
+impl MediaPool {
+    /// Lists the content of a subfolder
+    pub fn get_folder(
+        &self,
+        ilias_client: &IliasClient,
+        folder: &MediaPoolFolder,
+    ) -> Result<MediaPool, Whatever> {
+        let page = ilias_client
+            .get_querypath(&folder.querypath)
+            .whatever_context(format!("Could not get media pool folder {}", folder.title))?;
+        MediaPool::parse(page.root_element(), ilias_client)
+    }
+}
+
+impl MediaObject {
+    /// Looks up the media file shown on the page of the object
+    pub fn get_file(&self, ilias_client: &IliasClient) -> Result<File, Whatever> {
+        let media_source_selector = MEDIA_SOURCE_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#"video source[src], audio source[src], video[src], audio[src], img[src*="mobs/mm_"], a[href*="mobs/mm_"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+
+        let page = ilias_client
+            .get_querypath(
+                self.querypath
+                    .as_ref()
+                    .whatever_context(format!("{} can not be opened", self.title))?,
+            )
+            .whatever_context(format!("Could not get media object {}", self.title))?;
+        let source = page
+            .select(media_source_selector)
+            .find_map(|source| source.attr("src").or_else(|| source.attr("href")))
+            .whatever_context(format!("Could not find media of {}", self.title))?;
+
+        Ok(File {
+            name: source
+                .split(['?', '#'])
+                .next()
+                .and_then(|path| path.rsplit('/').next())
+                .filter(|name| !name.is_empty())
+                .unwrap_or(&self.title)
+                .to_string(),
+            description: String::new(),
+            date: None,
+            download_querypath: Some(querypath_from_href(source)),
+            id: None,
+            delivered_id: None,
+            license: None,
+            size: None,
+            uploader: None,
+        })
+    }
+}
@@ -0,0 +1,212 @@
+use std::{sync::OnceLock, time::Duration};
+
+use chrono::{DateTime, Local};
+use log::debug;
+use reqwest::Url;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, Whatever};
+
+use super::{
+    ILIAS_URL, IliasElement, client::IliasClient, file::File, id::RefId, parse_date,
+    querypath_from_href,
+};
+
+/// An ILIAS mediacast (`mcst`), usually holding recorded lectures
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Mediacast {
+    pub name: String,
+    pub description: String,
+    pub episodes: Vec<MediacastEpisode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MediacastEpisode {
+    pub title: String,
+    pub description: String,
+    pub date: Option<DateTime<Local>>,
+    pub duration: Option<Duration>,
+    pub download_querypath: Option<String>,
+    pub stream: Option<StreamSource>,
+}
+
+/// Where an episode is streamed from, recordings are often hosted outside of ILIAS
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum StreamSource {
+    Querypath(String),
+    External(Url),
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static EPISODE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static EPISODE_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static EPISODE_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PROPERTY_NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PROPERTY_VALUE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static MEDIA_SOURCE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for Mediacast {
+    fn type_identifier() -> Option<&'static str> {
+        Some("mcst")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let episode_selector = EPISODE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer .il-item").expect("Could not parse selector")
+        });
+        let episode_title_selector = EPISODE_TITLE_SELECTOR
+            .get_or_init(|| Selector::parse(".il-item-title").expect("Could not parse selector"));
+        let episode_description_selector = EPISODE_DESCRIPTION_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-description").expect("Could not parse selector")
+        });
+        let property_name_selector = PROPERTY_NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-name").expect("Could not parse selector")
+        });
+        let property_value_selector = PROPERTY_VALUE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-value").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+        let media_source_selector = MEDIA_SOURCE_SELECTOR.get_or_init(|| {
+            Selector::parse("video source[src], audio source[src], video[src], audio[src]")
+                .expect("Could not parse selector")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+
+        let mut episodes = vec![];
+        for episode in element.select(episode_selector) {
+            let title = episode
+                .select(episode_title_selector)
+                .next()
+                .whatever_context("Episode without title")?
+                .text()
+                .collect::<String>()
+                .trim()
+                .to_string();
+            let description = episode
+                .select(episode_description_selector)
+                .next()
+                .map(|description| description.text().collect::<String>().trim().to_string())
+                .unwrap_or_default();
+
+            let properties = episode
+                .select(property_name_selector)
+                .map(|name| name.text().collect::<String>().trim().to_string())
+                .zip(
+                    episode
+                        .select(property_value_selector)
+                        .map(|value| value.text().collect::<String>().trim().to_string()),
+                )
+                .collect::<Vec<_>>();
+            let property = |names: &[&str]| {
+                properties
+                    .iter()
+                    .find(|(name, _)| names.contains(&name.as_str()))
+                    .map(|(_, value)| value.as_str())
+            };
+
+            let download_querypath = episode
+                .select(link_selector)
+                .filter_map(|link| link.attr("href"))
+                .find(|href| href.contains("cmd=downloadItem"))
+                .map(querypath_from_href);
+            let stream = episode
+                .select(media_source_selector)
+                .filter_map(|source| source.attr("src"))
+                .next()
+                .map(StreamSource::from_src);
+
+            episodes.push(MediacastEpisode {
+                title,
+                description,
+                date: property(&["Datum", "Date", "Erstellt am", "Created On"])
+                    .and_then(|date| parse_date(date).ok()),
+                duration: property(&["Dauer", "Duration", "Spieldauer", "Play Time"])
+                    .and_then(parse_duration),
+                download_querypath,
+                stream,
+            });
+        }
+        debug!("Mediacast episodes: {:?}", episodes);
+
+        Ok(Mediacast {
+            name,
+            description,
+            episodes,
+        })
+    }
+}
+
+impl MediacastEpisode {
+    /// The episode as a downloadable file, preferring the download over the stream source.
+    /// External streams are not downloaded through ILIAS and left out.
+    pub fn file(&self) -> File {
+        File {
+            name: self.title.clone(),
+            description: self.description.clone(),
+            date: self.date,
+            download_querypath: self
+                .download_querypath
+                .clone()
+                .or_else(|| match &self.stream {
+                    Some(StreamSource::Querypath(querypath)) => Some(querypath.clone()),
+                    _ => None,
+                }),
+            id: None,
+            delivered_id: None,
+            license: None,
+            size: None,
+            uploader: None,
+        }
+    }
+}
+
+impl StreamSource {
+    /// Links to the ILIAS installation become querypaths, all others stay urls
+    fn from_src(src: &str) -> StreamSource {
+        match Url::parse(src) {
+            Ok(url)
+                if Url::parse(ILIAS_URL).is_ok_and(|ilias| ilias.host_str() != url.host_str()) =>
+            {
+                StreamSource::External(url)
+            }
+            _ => StreamSource::Querypath(querypath_from_href(src)),
+        }
+    }
+}
+
+/// Parses "hh:mm:ss" or "mm:ss" durations
+fn parse_duration(duration: &str) -> Option<Duration> {
+    let seconds = duration.trim().split(':').try_fold(0u64, |seconds, part| {
+        Some(seconds * 60 + part.parse::<u64>().ok()?)
+    })?;
+    Some(Duration::from_secs(seconds))
+}
@@ -0,0 +1,128 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+
+use super::{
+    IliasElement, course::Course, group::Group, id::RefId, querypath_from_href,
+    reference::Reference, type_from_icon,
+};
+
+/// A course or group listed in the "My Courses and Groups" overview
+#[derive(Debug)]
+pub enum Membership {
+    Course {
+        name: String,
+        description: String,
+        id: RefId,
+        course: Reference<Course>,
+    },
+    Group {
+        name: String,
+        description: String,
+        id: RefId,
+        group: Reference<Group>,
+    },
+}
+
+static ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_ICON_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl Membership {
+    pub const OVERVIEW_QUERYPATH: &str = "ilias.php?baseClass=ilmembershipoverviewgui&cmd=show";
+
+    pub(crate) fn parse_overview(element: ElementRef) -> Vec<Membership> {
+        let item_selector = ITEM_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item, .il_ContainerListItem").expect("Could not parse selector")
+        });
+        let item_title_selector = ITEM_TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-title a[href], .il_ContainerItemTitle a[href]")
+                .expect("Could not parse selector")
+        });
+        let item_description_selector = ITEM_DESCRIPTION_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-description, .il_Description")
+                .expect("Could not parse selector")
+        });
+        let item_icon_selector = ITEM_ICON_SELECTOR.get_or_init(|| {
+            Selector::parse("img.icon, img.ilListItemIcon").expect("Could not parse selector")
+        });
+        let id_regex = ID_REGEX.get_or_init(|| {
+            Regex::new(r"(ref_id=|crs/|grp/|crs_|grp_)(?<id>\d+)").expect("Could not parse regex")
+        });
+
+        let memberships = element
+            .select(item_selector)
+            .filter_map(|item| {
+                let title = item.select(item_title_selector).next()?;
+                let querypath = querypath_from_href(title.attr("href")?);
+                let id: RefId = id_regex.captures(&querypath)?["id"].parse().ok()?;
+                let name = title.text().collect::<String>().trim().to_string();
+                let description = item
+                    .select(item_description_selector)
+                    .next()
+                    .map(|description| description.text().collect::<String>().trim().to_string())
+                    .unwrap_or_default();
+                let type_identifier = item
+                    .select(item_icon_selector)
+                    .next()
+                    .and_then(|icon| icon.attr("src"))
+                    .and_then(type_from_icon);
+
+                let is_group = match type_identifier.as_deref() {
+                    Some(type_identifier) => Some(type_identifier) == Group::type_identifier(),
+                    None => querypath.contains("grp"),
+                };
+                Some(if is_group {
+                    Membership::Group {
+                        name,
+                        description,
+                        group: Reference::from_optional_querypath(Group::querypath_from_id(id)),
+                        id,
+                    }
+                } else {
+                    Membership::Course {
+                        name,
+                        description,
+                        course: Reference::from_optional_querypath(Course::querypath_from_id(id)),
+                        id,
+                    }
+                })
+            })
+            .collect();
+        debug!("Memberships: {:?}", memberships);
+
+        memberships
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Course { name, .. } | Self::Group { name, .. } => name,
+        }
+    }
+
+    /// The ref_id of the course or group
+    pub fn id(&self) -> RefId {
+        match self {
+            Self::Course { id, .. } | Self::Group { id, .. } => *id,
+        }
+    }
+
+    pub fn course(&self) -> Option<&Reference<Course>> {
+        match self {
+            Self::Course { course, .. } => Some(course),
+            Self::Group { .. } => None,
+        }
+    }
+
+    pub fn group(&self) -> Option<&Reference<Group>> {
+        match self {
+            Self::Group { group, .. } => Some(group),
+            Self::Course { .. } => None,
+        }
+    }
+}
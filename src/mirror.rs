@@ -0,0 +1,126 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::OnceLock,
+};
+
+use log::{debug, warn};
+use reqwest::Url;
+use scraper::Selector;
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    Querypath,
+    client::IliasClient,
+    page::{escape_attribute, relative_path},
+};
+
+static REFERENCE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+/// Saves the HTML pages below the directory of `start` into `directory`, following links between
+/// them and downloading the files they reference. References that point into the mirrored
+/// directory are made relative so the copy works offline.
+///
+/// Returns the local path of the start page.
+pub(crate) fn mirror(
+    ilias_client: &IliasClient,
+    start: &Url,
+    directory: &Path,
+) -> Result<PathBuf, Whatever> {
+    let reference_selector = REFERENCE_SELECTOR
+        .get_or_init(|| Selector::parse("[src], [href]").expect("Could not parse selector"));
+
+    let root = start
+        .join("./")
+        .whatever_context("Could not get mirror root")?;
+    let start_path =
+        local_path(&root, start).whatever_context(format!("Could not mirror {start}"))?;
+
+    let mut seen = HashSet::from([start_path.clone()]);
+    let mut unvisited = VecDeque::from([start.clone()]);
+    while let Some(url) = unvisited.pop_front() {
+        let Some(page_path) = local_path(&root, &url) else {
+            continue;
+        };
+        let page = ilias_client
+            .get_querypath(&url.get_querypath())
+            .whatever_context(format!("Could not get {url}"))?;
+        let page_directory = page_path.parent().unwrap_or(Path::new(""));
+
+        let mut html = page.html();
+        for element in page.select(reference_selector) {
+            let is_link = matches!(element.value().name(), "a" | "area");
+            let Some(reference) = element.attr("href").or_else(|| element.attr("src")) else {
+                continue;
+            };
+            let Ok(target) = url.join(reference) else {
+                continue;
+            };
+            let Some(target_path) = local_path(&root, &target) else {
+                continue;
+            };
+
+            if seen.insert(target_path.clone()) {
+                if is_link && is_html(&target_path) {
+                    unvisited.push_back(target.clone());
+                } else {
+                    let to = directory.join(&target_path);
+                    if let Some(parent) = to.parent() {
+                        fs::create_dir_all(parent)
+                            .whatever_context("Could not create mirror directory")?;
+                    }
+                    if let Err(error) = ilias_client.download_url(target.clone(), &to) {
+                        warn!("Could not download {reference}: {error}");
+                    }
+                }
+            }
+
+            if Url::parse(reference).is_ok() || reference.starts_with('/') {
+                let mut relative = relative_path(page_directory, &target_path)
+                    .to_string_lossy()
+                    .replace('\\', "/");
+                if let Some(fragment) = target.fragment() {
+                    relative = format!("{relative}#{fragment}");
+                }
+                html = html.replace(
+                    &format!(r#"="{}""#, escape_attribute(reference)),
+                    &format!(r#"="{}""#, escape_attribute(&relative)),
+                );
+            }
+        }
+
+        let to = directory.join(&page_path);
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent).whatever_context("Could not create mirror directory")?;
+        }
+        fs::write(&to, html).whatever_context("Could not write mirrored page")?;
+        debug!("Mirrored {url} to {to:?}");
+    }
+
+    Ok(directory.join(start_path))
+}
+
+/// Where `url` ends up in a mirror of `root`, if it lies below it
+fn local_path(root: &Url, url: &Url) -> Option<PathBuf> {
+    let mut url = url.clone();
+    url.set_query(None);
+    url.set_fragment(None);
+    let relative = url.as_str().strip_prefix(root.as_str())?;
+    let relative = if relative.is_empty() || relative.ends_with('/') {
+        format!("{relative}index.html")
+    } else {
+        relative.to_string()
+    };
+
+    let path = PathBuf::from(relative);
+    path.components()
+        .all(|component| matches!(component, Component::Normal(_)))
+        .then_some(path)
+}
+
+fn is_html(path: &Path) -> bool {
+    path.extension().is_some_and(|extension| {
+        extension.eq_ignore_ascii_case("html") || extension.eq_ignore_ascii_case("htm")
+    })
+}
@@ -0,0 +1,226 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+use log::debug;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement,
+    client::IliasClient,
+    file::File,
+    id::{ObjId, RefId},
+    parse_date, querypath_from_href,
+};
+
+/// The news timeline of a course or group, where lecturers post announcements
+#[derive(Debug, Serialize, Deserialize)]
+pub struct NewsTimeline {
+    pub items: Vec<NewsItem>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NewsItem {
+    pub id: Option<ObjId>,
+    pub title: String,
+    pub author: Option<String>,
+    pub date: Option<DateTime<Local>>,
+    /// Empty for items from a news block until [`NewsItem::load`] is called
+    pub body_html: String,
+    pub files: Vec<File>,
+    pub querypath: Option<String>,
+}
+
+static ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static AUTHOR_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DATE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static BODY_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ATTACHMENT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static BLOCK_ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static BLOCK_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static BLOCK_PROPERTY_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static NEWS_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl IliasElement for NewsTimeline {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let items = element
+            .select(item_selector())
+            .filter_map(NewsItem::parse_full)
+            .collect::<Vec<_>>();
+        debug!("News timeline: {:?}", items);
+
+        Ok(NewsTimeline { items })
+    }
+}
+
+impl NewsItem {
+    /// Parses an item showing the whole announcement, as on the timeline or the news page
+    fn parse_full(item: ElementRef) -> Option<NewsItem> {
+        let title_selector = TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilNewsTimelineTitle, .il-item-title, .ilNewsTitle, h3")
+                .expect("Could not parse selector")
+        });
+        let author_selector = AUTHOR_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilNewsTimelineUser, .ilNewsAuthor, .il-item-property-value.author")
+                .expect("Could not parse selector")
+        });
+        let date_selector = DATE_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilNewsTimelineDate, .ilNewsDate, .il-item-property-value")
+                .expect("Could not parse selector")
+        });
+        let body_selector = BODY_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilNewsTimelineContent, .ilNewsContent, .il-item-description")
+                .expect("Could not parse selector")
+        });
+        let attachment_selector = ATTACHMENT_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"a[href*="downloadMob"], a[href*="deliverFile"]"#)
+                .expect("Could not parse selector")
+        });
+
+        let title = item
+            .select(title_selector)
+            .next()?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let files = item
+            .select(attachment_selector)
+            .filter_map(|link| {
+                Some(File {
+                    name: link.text().collect::<String>().trim().to_string(),
+                    description: String::new(),
+                    date: None,
+                    download_querypath: Some(querypath_from_href(link.attr("href")?)),
+                    id: None,
+                    delivered_id: None,
+                    license: None,
+                    size: None,
+                    uploader: None,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Some(NewsItem {
+            // Items carry their id in the element id (e.g. `news_123`) or in attachment links
+            id: item
+                .attr("id")
+                .and_then(|id| id.rsplit('_').next())
+                .and_then(|id| id.parse().ok())
+                .or_else(|| {
+                    files
+                        .iter()
+                        .filter_map(|file| file.download_querypath.as_ref())
+                        .find_map(|querypath| news_id_regex().captures(querypath))
+                        .and_then(|captures| captures["id"].parse().ok())
+                }),
+            title,
+            author: item
+                .select(author_selector)
+                .next()
+                .map(|author| author.text().collect::<String>().trim().to_string())
+                .filter(|author| !author.is_empty()),
+            date: item
+                .select(date_selector)
+                .find_map(|date| parse_date(date.text().collect::<String>().trim()).ok()),
+            body_html: item
+                .select(body_selector)
+                .next()
+                .map(|body| body.inner_html().trim().to_string())
+                .unwrap_or_default(),
+            files,
+            querypath: None,
+        })
+    }
+
+    /// Parses the entries of a news block, e.g. on a course page, which only show title and date
+    pub(crate) fn parse_block(block: ElementRef) -> Vec<NewsItem> {
+        let block_item_selector = BLOCK_ITEM_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item, .il_ContainerListItem").expect("Could not parse selector")
+        });
+        let block_title_selector = BLOCK_TITLE_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-title, .il_ContainerItemTitle")
+                .expect("Could not parse selector")
+        });
+        let block_property_selector = BLOCK_PROPERTY_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-item-property-value, .il_ItemProperty")
+                .expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        block
+            .select(block_item_selector)
+            .filter_map(|item| {
+                let title = item.select(block_title_selector).next()?;
+                let querypath = title
+                    .select(link_selector)
+                    .next()
+                    .or(Some(title).filter(|title| title.attr("href").is_some()))
+                    .and_then(|link| link.attr("href"))
+                    .map(querypath_from_href);
+
+                Some(NewsItem {
+                    id: querypath
+                        .as_ref()
+                        .and_then(|querypath| news_id_regex().captures(querypath))
+                        .and_then(|captures| captures["id"].parse().ok()),
+                    title: title.text().collect::<String>().trim().to_string(),
+                    author: None,
+                    date: item.select(block_property_selector).find_map(|property| {
+                        parse_date(property.text().collect::<String>().trim()).ok()
+                    }),
+                    body_html: String::new(),
+                    files: vec![],
+                    querypath,
+                })
+            })
+            .collect()
+    }
+
+    /// Fetches the full announcement of an item from a news block
+    pub fn load(&mut self, ilias_client: &IliasClient) -> Result<(), Whatever> {
+        let page = ilias_client
+            .get_querypath(
+                self.querypath
+                    .as_ref()
+                    .whatever_context(format!("News {} can not be opened", self.title))?,
+            )
+            .whatever_context(format!("Could not get news {}", self.title))?;
+        let full = page
+            .select(item_selector())
+            .filter_map(NewsItem::parse_full)
+            .next()
+            .whatever_context(format!("Could not find news {}", self.title))?;
+
+        self.author = full.author;
+        self.date = full.date.or(self.date);
+        self.body_html = full.body_html;
+        self.files = full.files;
+        Ok(())
+    }
+}
+
+fn item_selector() -> &'static Selector {
+    ITEM_SELECTOR.get_or_init(|| {
+        Selector::parse(".ilNewsTimelineItem, .il-news-item, .ilNewsItem")
+            .expect("Could not parse selector")
+    })
+}
+
+fn news_id_regex() -> &'static Regex {
+    NEWS_ID_REGEX.get_or_init(|| Regex::new(r"news_id=(?<id>\d+)").expect("Could not parse regex"))
+}
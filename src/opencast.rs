@@ -0,0 +1,191 @@
+use std::{path::Path, sync::OnceLock};
+
+use chrono::{DateTime, Local};
+use log::debug;
+use regex::Regex;
+use reqwest::Url;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement, client::IliasClient, id::RefId, parse_date, querypath_from_href, table::Table,
+};
+
+/// A series of the OpenCast plugin (`xoct`) that hosts lecture recordings
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Opencast {
+    pub name: String,
+    pub episodes: Vec<OpencastEpisode>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpencastEpisode {
+    pub title: String,
+    pub date: Option<DateTime<Local>>,
+    pub player_querypath: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    Mp4,
+    Hls,
+}
+
+/// A media source of an episode as delivered to the player
+#[derive(Debug, Clone)]
+pub struct OpencastStream {
+    pub format: StreamFormat,
+    pub url: String,
+    pub mimetype: Option<String>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PLAYER_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static STREAMS_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl IliasElement for Opencast {
+    fn type_identifier() -> Option<&'static str> {
+        Some("xoct")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "ilias.php?baseClass=ilObjPluginDispatchGUI&cmd=forward&ref_id={id}&forwardCmd=showContent"
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let player_link_selector = PLAYER_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"a[href*="cmd=streamVideo"]"#).expect("Could not parse selector")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+
+        let mut episodes = vec![];
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            for row in &table.rows {
+                let Some(player_querypath) = row
+                    .iter()
+                    .flat_map(|cell| cell.select(player_link_selector))
+                    .find_map(|link| link.attr("href"))
+                    .map(querypath_from_href)
+                else {
+                    continue;
+                };
+                let title = table
+                    .cell_text(row, &["Titel", "Title"])
+                    .whatever_context("Episode without title")?;
+                let date = table
+                    .cell_text(row, &["Datum", "Date", "Startzeit", "Start"])
+                    .and_then(|date| parse_date(&date.replace(" - ", ", ")).ok());
+
+                episodes.push(OpencastEpisode {
+                    title,
+                    date,
+                    player_querypath,
+                });
+            }
+        }
+        debug!("Opencast episodes: {:?}", episodes);
+
+        Ok(Opencast { name, episodes })
+    }
+}
+
+#[derive(Deserialize)]
+struct PlayerData {
+    streams: Vec<PlayerStream>,
+}
+
+#[derive(Deserialize)]
+struct PlayerStream {
+    sources: PlayerSources,
+}
+
+#[derive(Deserialize)]
+struct PlayerSources {
+    #[serde(default)]
+    mp4: Vec<PlayerSource>,
+    #[serde(default)]
+    hls: Vec<PlayerSource>,
+}
+
+#[derive(Deserialize)]
+struct PlayerSource {
+    src: String,
+    mimetype: Option<String>,
+    res: Option<PlayerResolution>,
+}
+
+#[derive(Deserialize)]
+struct PlayerResolution {
+    w: u32,
+    h: u32,
+}
+
+impl OpencastEpisode {
+    /// Reads the stream sources the player page hands to the video player
+    pub fn get_streams(&self, ilias_client: &IliasClient) -> Result<Vec<OpencastStream>, Whatever> {
+        let streams_regex = STREAMS_REGEX.get_or_init(|| {
+            Regex::new(r#"(?s)(\{"streams".+?),\s*\{"paella_config_file"#)
+                .expect("Could not parse regex")
+        });
+
+        let player_page = ilias_client.get_querypath(&self.player_querypath)?;
+        let html = player_page.html();
+        let player_data = &streams_regex
+            .captures(&html)
+            .whatever_context("Did not find stream data on player page")?[1];
+        let player_data: PlayerData = serde_json::from_str(player_data)
+            .whatever_context("Could not deserialize stream data")?;
+
+        let mut streams = vec![];
+        for stream in player_data.streams {
+            for (format, sources) in [
+                (StreamFormat::Mp4, stream.sources.mp4),
+                (StreamFormat::Hls, stream.sources.hls),
+            ] {
+                streams.extend(sources.into_iter().map(|source| OpencastStream {
+                    format,
+                    url: source.src,
+                    mimetype: source.mimetype,
+                    width: source.res.as_ref().map(|res| res.w),
+                    height: source.res.as_ref().map(|res| res.h),
+                }));
+            }
+        }
+        Ok(streams)
+    }
+
+    /// Downloads the mp4 stream with the highest resolution
+    pub fn download(&self, ilias_client: &IliasClient, to: &Path) -> Result<(), Whatever> {
+        let stream = self
+            .get_streams(ilias_client)?
+            .into_iter()
+            .filter(|stream| stream.format == StreamFormat::Mp4)
+            .max_by_key(|stream| stream.width.unwrap_or(0) * stream.height.unwrap_or(0))
+            .whatever_context(format!("No mp4 stream available for {}", self.title))?;
+        let url = Url::parse(&stream.url).whatever_context("Invalid stream url")?;
+
+        ilias_client.download_url(url, to)
+    }
+}
@@ -0,0 +1,396 @@
+use std::{collections::HashSet, sync::OnceLock};
+
+use chrono::{DateTime, Local, NaiveTime, TimeZone};
+use log::{debug, warn};
+use regex::Regex;
+use scraper::Html;
+use snafu::{ResultExt, Whatever};
+
+use super::{
+    IliasElement,
+    client::IliasClient,
+    exercise::{Exercise, assignment::DeadlineState},
+    folder::{FolderElement, walk::FolderWalk},
+    info_screen::InfoScreen,
+    membership::Membership,
+    news::NewsItem,
+    parse_month,
+    session::Session,
+    survey::Survey,
+    test::Test,
+};
+
+/// How many memberships are visited at the same time
+const PARALLELISM: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DeadlineKind {
+    Assignment,
+    Exam,
+    Survey,
+    /// The end of the access period of a test
+    Test,
+    Session,
+}
+
+/// A dated event that a user has to act on or attend
+#[derive(Debug, Clone)]
+pub struct Deadline {
+    pub kind: DeadlineKind,
+    pub title: String,
+    pub date: DateTime<Local>,
+    /// Name of the course or object the deadline was found in
+    pub source: String,
+    pub querypath: Option<String>,
+    /// Whether submissions are still possible, only known for assignments
+    pub state: Option<DeadlineState>,
+}
+
+/// Heuristically finds exam dates in free text like course info pages, sessions or news
+#[derive(Debug, Clone)]
+pub struct ExamDateExtractor {
+    keywords: Vec<String>,
+}
+
+static NUMERIC_DATE_REGEX: OnceLock<Regex> = OnceLock::new();
+static WRITTEN_DATE_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl Default for ExamDateExtractor {
+    fn default() -> Self {
+        Self::new(&["Klausur", "Prüfung", "Nachklausur", "Exam", "Examination"])
+    }
+}
+
+impl ExamDateExtractor {
+    pub fn new<T: AsRef<str>>(keywords: &[T]) -> ExamDateExtractor {
+        ExamDateExtractor {
+            keywords: keywords
+                .iter()
+                .map(|keyword| keyword.as_ref().to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// Returns a candidate exam date for every date mentioned in a line that contains a keyword
+    pub fn extract(&self, source: &str, text: &str) -> Vec<Deadline> {
+        let numeric_date_regex = NUMERIC_DATE_REGEX.get_or_init(|| {
+            Regex::new(r"(?<day>\d{1,2})\.(?<month>\d{1,2})\.(?<year>\d{2,4})(?:,?\s*(?:um\s+|at\s+)?(?<hour>\d{1,2}):(?<minute>\d{2}))?")
+                .expect("Could not parse regex")
+        });
+        let written_date_regex = WRITTEN_DATE_REGEX.get_or_init(|| {
+            Regex::new(r"(?<day>\d{1,2})\.\s*(?<month>[[:alpha:]ä]{3,})\.?\s+(?<year>\d{4})(?:,?\s*(?:um\s+|at\s+)?(?<hour>\d{1,2}):(?<minute>\d{2}))?")
+                .expect("Could not parse regex")
+        });
+
+        let mut deadlines = vec![];
+        for line in text.lines().map(str::trim) {
+            if !self.mentions_exam(line) {
+                continue;
+            }
+
+            let numeric_dates = numeric_date_regex.captures_iter(line).map(|captures| {
+                (
+                    captures["day"].parse().ok(),
+                    captures["month"].parse().ok(),
+                    captures["year"].parse().ok(),
+                    captures
+                        .name("hour")
+                        .and_then(|hour| hour.as_str().parse().ok()),
+                    captures
+                        .name("minute")
+                        .and_then(|minute| minute.as_str().parse().ok()),
+                )
+            });
+            let written_dates = written_date_regex.captures_iter(line).map(|captures| {
+                (
+                    captures["day"].parse().ok(),
+                    parse_month(&captures["month"]),
+                    captures["year"].parse().ok(),
+                    captures
+                        .name("hour")
+                        .and_then(|hour| hour.as_str().parse().ok()),
+                    captures
+                        .name("minute")
+                        .and_then(|minute| minute.as_str().parse().ok()),
+                )
+            });
+
+            for (day, month, year, hour, minute) in numeric_dates.chain(written_dates) {
+                let (Some(day), Some(month), Some(year)) = (day, month, year) else {
+                    continue;
+                };
+                let year: i32 = if year < 100 { 2000 + year } else { year };
+                let time = NaiveTime::from_hms_opt(hour.unwrap_or(0), minute.unwrap_or(0), 0)
+                    .unwrap_or_default();
+                let Some(date) = Local
+                    .with_ymd_and_hms(year, month, day, 0, 0, 0)
+                    .earliest()
+                    .and_then(|date| date.with_time(time).earliest())
+                else {
+                    continue;
+                };
+
+                deadlines.push(Deadline {
+                    kind: DeadlineKind::Exam,
+                    title: line.to_string(),
+                    date,
+                    source: source.to_string(),
+                    querypath: None,
+                    state: None,
+                });
+            }
+        }
+
+        deadlines
+    }
+
+    /// Whether the text contains one of the keywords
+    pub fn mentions_exam(&self, text: &str) -> bool {
+        let text = text.to_lowercase();
+        self.keywords.iter().any(|keyword| text.contains(keyword))
+    }
+
+    pub fn extract_from_info_screen(&self, source: &str, info: &InfoScreen) -> Vec<Deadline> {
+        info.sections
+            .iter()
+            .flat_map(|section| &section.properties)
+            .flat_map(|property| {
+                self.extract(source, &format!("{}: {}", property.name, property.value))
+            })
+            .collect()
+    }
+
+    /// Looks at the title and text of announcements
+    pub fn extract_from_news(&self, source: &str, items: &[NewsItem]) -> Vec<Deadline> {
+        items
+            .iter()
+            .flat_map(|item| {
+                let body = Html::parse_fragment(&item.body_html)
+                    .root_element()
+                    .text()
+                    .collect::<String>();
+                self.extract(source, &format!("{}\n{body}", item.title))
+            })
+            .collect()
+    }
+}
+
+/// Collects the upcoming assignment, test and survey deadlines, and optionally sessions, of all
+/// courses and groups the user is a member of, including objects nested in folders, sorted by
+/// date.
+///
+/// With `exam_dates`, exam dates are also looked for on the course info pages, in course
+/// news, in session descriptions and in sessions named like an exam.
+///
+/// A few memberships are visited concurrently, limited by the politeness profile of the client.
+pub fn upcoming_deadlines(
+    ilias_client: &IliasClient,
+    include_sessions: bool,
+    exam_dates: Option<&ExamDateExtractor>,
+) -> Result<Vec<Deadline>, Whatever> {
+    let memberships = ilias_client
+        .get_memberships()
+        .whatever_context("Could not get memberships")?;
+
+    let mut deadlines = ilias_client
+        .map_concurrently(
+            &memberships,
+            PARALLELISM,
+            |membership| match membership_deadlines(
+                ilias_client,
+                membership,
+                include_sessions,
+                exam_dates,
+            ) {
+                Ok(deadlines) => deadlines,
+                Err(error) => {
+                    warn!("Could not collect deadlines of {membership:?}: {error}");
+                    vec![]
+                }
+            },
+        )
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    // Groups inside of courses are memberships of their own as well
+    let mut seen = HashSet::new();
+    deadlines.retain(|deadline| {
+        seen.insert((
+            deadline.kind,
+            deadline.title.clone(),
+            deadline.date,
+            deadline.querypath.clone(),
+        ))
+    });
+    deadlines.sort_by_key(|deadline| deadline.date);
+    debug!("Upcoming deadlines: {deadlines:?}");
+
+    Ok(deadlines)
+}
+
+fn membership_deadlines(
+    ilias_client: &IliasClient,
+    membership: &Membership,
+    include_sessions: bool,
+    exam_dates: Option<&ExamDateExtractor>,
+) -> Result<Vec<Deadline>, Whatever> {
+    let mut deadlines = vec![];
+    let (source, elements) = match membership {
+        Membership::Course { name, course, .. } => {
+            let mut course = course.resolve(ilias_client)?;
+            if let Some(exam_dates) = exam_dates {
+                match course.get_info(ilias_client) {
+                    Ok(Some(info)) => {
+                        deadlines.extend(upcoming(exam_dates.extract_from_info_screen(name, info)))
+                    }
+                    Ok(None) => {}
+                    Err(error) => warn!("Could not look for exam dates of {name}: {error}"),
+                }
+                match course.timeline(ilias_client) {
+                    Ok(Some(timeline)) => deadlines.extend(upcoming(
+                        exam_dates.extract_from_news(name, &timeline.items),
+                    )),
+                    Ok(None) => {}
+                    Err(error) => warn!("Could not look for exam dates in news of {name}: {error}"),
+                }
+            }
+            (name, course.elements)
+        }
+        Membership::Group { name, group, .. } => (name, group.resolve(ilias_client)?.elements),
+    };
+
+    let walk = FolderWalk::from_elements(
+        ilias_client,
+        elements.into_iter().map(|element| (None, element)),
+    );
+    for entry in walk {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                warn!("Could not visit everything in {source}: {error}");
+                continue;
+            }
+        };
+        match element_deadlines(
+            ilias_client,
+            source,
+            &entry.element,
+            include_sessions,
+            exam_dates,
+        ) {
+            Ok(element_deadlines) => deadlines.extend(element_deadlines),
+            Err(error) => warn!(
+                "Could not collect deadlines of {} in {source}: {error}",
+                entry.element.name()
+            ),
+        }
+    }
+
+    Ok(deadlines)
+}
+
+fn element_deadlines(
+    ilias_client: &IliasClient,
+    source: &str,
+    element: &FolderElement,
+    include_sessions: bool,
+    exam_dates: Option<&ExamDateExtractor>,
+) -> Result<Vec<Deadline>, Whatever> {
+    let now = Local::now();
+    let with_source = |deadline: Deadline| Deadline {
+        source: source.to_string(),
+        ..deadline
+    };
+
+    match element {
+        FolderElement::Exercise { querypath, .. } => {
+            let page = ilias_client.get_querypath(querypath)?;
+            let exercise = Exercise::parse(page.root_element(), ilias_client)?;
+            Ok(exercise
+                .assignments
+                .iter()
+                .filter_map(|assignment| {
+                    let date = assignment.deadline().filter(|date| *date >= now)?;
+                    Some(Deadline {
+                        kind: DeadlineKind::Assignment,
+                        title: format!("{}: {}", exercise.name, assignment.name),
+                        date,
+                        source: source.to_string(),
+                        querypath: assignment.permalink(),
+                        state: Some(assignment.deadline_state()),
+                    })
+                })
+                .collect())
+        }
+        FolderElement::Session {
+            name, querypath, ..
+        } if include_sessions || exam_dates.is_some() => {
+            let page = ilias_client.get_querypath(querypath)?;
+            let session = Session::parse(page.root_element(), ilias_client)?;
+            let start = session.start.filter(|date| *date >= now);
+
+            let mut deadlines = vec![];
+            let mut push_start = |kind| {
+                if let Some(date) = start {
+                    deadlines.push(Deadline {
+                        kind,
+                        title: name.clone(),
+                        date,
+                        source: source.to_string(),
+                        querypath: Some(querypath.clone()),
+                        state: None,
+                    });
+                }
+            };
+            if include_sessions {
+                push_start(DeadlineKind::Session);
+            }
+            if let Some(exam_dates) = exam_dates {
+                if exam_dates.mentions_exam(name) {
+                    push_start(DeadlineKind::Exam);
+                }
+                deadlines.extend(upcoming(exam_dates.extract(source, &session.description)));
+            }
+            Ok(deadlines)
+        }
+        _ => match typed_querypath(element) {
+            Some((type_identifier, querypath))
+                if Some(type_identifier) == Test::type_identifier() =>
+            {
+                let page = ilias_client.get_querypath(querypath)?;
+                let test = Test::parse(page.root_element(), ilias_client)?;
+                Ok(test.deadline().map(with_source).into_iter().collect())
+            }
+            Some((type_identifier, querypath))
+                if Some(type_identifier) == Survey::type_identifier() =>
+            {
+                let page = ilias_client.get_querypath(querypath)?;
+                let survey = Survey::parse(page.root_element(), ilias_client)?;
+                Ok(survey.deadline().map(with_source).into_iter().collect())
+            }
+            _ => Ok(vec![]),
+        },
+    }
+}
+
+fn upcoming(deadlines: Vec<Deadline>) -> impl Iterator<Item = Deadline> {
+    let now = Local::now();
+    deadlines
+        .into_iter()
+        .filter(move |deadline| deadline.date >= now)
+}
+
+/// The type and querypath of objects that are not parsed into their own kind of element
+fn typed_querypath(element: &FolderElement) -> Option<(&str, &str)> {
+    match element {
+        FolderElement::Viewable {
+            type_identifier,
+            querypath,
+            ..
+        } => Some((type_identifier.as_deref()?, querypath)),
+        FolderElement::Unknown { element, .. } => {
+            Some((element.type_identifier.as_deref()?, &element.querypath))
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,357 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Component, Path, PathBuf},
+    sync::OnceLock,
+};
+
+use log::{debug, warn};
+use regex::Regex;
+use reqwest::Url;
+use scraper::{ElementRef, Html, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    ILIAS_URL, IliasElement, client::IliasClient, file::File, id::RefId, querypath_from_href,
+};
+
+/// The editable page content that content pages, wikis, learning modules and others are built of
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page {
+    pub title: String,
+    pub html: String,
+    pub images: Vec<PageImage>,
+    /// Targets of all links in the page as they appear in `html`
+    pub links: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PageImage {
+    /// The source as found in the page, usually relative to the ILIAS installation
+    pub src: String,
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+    /// How the image tag appears in `Page::html`
+    html: String,
+}
+
+/// A piece of page content in reading order, see [`Page::blocks`]
+#[derive(Debug, Clone)]
+pub enum ContentBlock {
+    Heading { level: u8, text: String },
+    Paragraph(String),
+    ListItem(String),
+    Image(PageImage),
+    FileLink(File),
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CONTENT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static IMAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CAPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static BLOCK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static LINK_ID_REGEX: OnceLock<Regex> = OnceLock::new();
+
+/// Where exported objects ended up locally, used to make links between exports work offline
+#[derive(Debug, Clone, Default)]
+pub struct ExportedLinks {
+    paths: HashMap<String, PathBuf>,
+}
+
+impl IliasElement for Page {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let content_selector = CONTENT_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilc_page_Page, #ilContentContainer")
+                .expect("Could not parse selector")
+        });
+
+        let title = element
+            .select(name_selector)
+            .next()
+            .map(|title| title.text().collect::<String>().trim().to_string())
+            .unwrap_or_default();
+        let content = element
+            .select(content_selector)
+            .next()
+            .whatever_context("Could not find page content")?;
+
+        Ok(Page::from_content(title, content))
+    }
+}
+
+impl Page {
+    /// A page of some content that is not a page of its own, e.g. the instructions of an assignment
+    pub(crate) fn from_content(title: String, content: ElementRef) -> Page {
+        let image_selector = IMAGE_SELECTOR
+            .get_or_init(|| Selector::parse("img[src]").expect("Could not parse selector"));
+        let caption_selector = CAPTION_SELECTOR.get_or_init(|| {
+            Selector::parse("figcaption, .ilc_media_caption_MediaCaption")
+                .expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        let images = content
+            .select(image_selector)
+            .filter_map(|image| {
+                let caption = image
+                    .ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .find(|ancestor| {
+                        ancestor.value().name() == "figure"
+                            || ancestor
+                                .value()
+                                .classes()
+                                .any(|class| class.contains("MediaContainer"))
+                    })
+                    .and_then(|container| container.select(caption_selector).next())
+                    .map(|caption| caption.text().collect::<String>().trim().to_string())
+                    .filter(|caption| !caption.is_empty());
+                Some(PageImage {
+                    src: image.attr("src")?.to_string(),
+                    alt: image
+                        .attr("alt")
+                        .map(|alt| alt.trim().to_string())
+                        .filter(|alt| !alt.is_empty()),
+                    caption,
+                    html: image.html(),
+                })
+            })
+            .collect();
+
+        let mut links = vec![];
+        let mut seen_links = HashSet::new();
+        for link in content.select(link_selector) {
+            let href = link.attr("href").unwrap_or_default();
+            if seen_links.insert(href) {
+                links.push(href.to_string());
+            }
+        }
+
+        let page = Page {
+            title,
+            html: content.html(),
+            images,
+            links,
+        };
+        debug!("Page images: {:?}", page.images);
+
+        page
+    }
+
+    /// The headings, text, images and file links of the page in the order they appear
+    pub fn blocks(&self) -> Vec<ContentBlock> {
+        let block_selector = BLOCK_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                "h1, h2, h3, h4, h5, h6, .ilc_Paragraph, p, li, img[src], .ilc_flist a[href], a.ilc_flist_a_FileListItemLink",
+            )
+            .expect("Could not parse selector")
+        });
+
+        let fragment = Html::parse_fragment(&self.html);
+        let mut blocks = vec![];
+        for element in fragment.select(block_selector) {
+            let name = element.value().name();
+            let text = || element.text().collect::<String>().trim().to_string();
+            match name {
+                "img" => {
+                    let src = element.attr("src").unwrap_or_default();
+                    if let Some(image) = self.images.iter().find(|image| image.src == src) {
+                        blocks.push(ContentBlock::Image(image.clone()));
+                    }
+                }
+                "a" => blocks.push(ContentBlock::FileLink(File {
+                    name: text(),
+                    description: String::new(),
+                    date: None,
+                    download_querypath: element.attr("href").map(querypath_from_href),
+                    id: None,
+                    delivered_id: None,
+                    license: None,
+                    size: None,
+                    uploader: None,
+                })),
+                // Text nested in another text block is part of that block
+                _ if element
+                    .ancestors()
+                    .filter_map(ElementRef::wrap)
+                    .any(is_text_block) => {}
+                _ => {
+                    let text = text();
+                    if text.is_empty() {
+                        continue;
+                    }
+                    blocks.push(match name {
+                        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => ContentBlock::Heading {
+                            level: name[1..].parse().unwrap_or(1),
+                            text,
+                        },
+                        "li" => ContentBlock::ListItem(text),
+                        _ => ContentBlock::Paragraph(text),
+                    });
+                }
+            }
+        }
+
+        blocks
+    }
+
+    /// Writes the page as `index.html` into `directory` and downloads its images next to it.
+    ///
+    /// Images without a visible caption get their alt text as caption so it is not lost offline.
+    /// Links to objects in `exported` point to their local copies, other ILIAS links are made
+    /// absolute and external links stay as they are.
+    pub fn export(
+        &self,
+        ilias_client: &IliasClient,
+        directory: &Path,
+        exported: &ExportedLinks,
+    ) -> Result<PathBuf, Whatever> {
+        let image_directory = directory.join("images");
+        fs::create_dir_all(&image_directory)
+            .whatever_context("Could not create export directory")?;
+
+        let base_url = Url::parse(ILIAS_URL).whatever_context("Could not parse ilias url")?;
+        let mut html = self.html.clone();
+        for (index, image) in self.images.iter().enumerate() {
+            let Ok(url) = base_url.join(&image.src) else {
+                warn!("Could not resolve image {}", image.src);
+                continue;
+            };
+            let file_name = format!(
+                "{index}_{}",
+                url.path_segments()
+                    .and_then(|mut segments| segments.next_back())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or("image")
+            );
+            if let Err(error) = ilias_client.download_url(url, &image_directory.join(&file_name)) {
+                warn!("Could not download image {}: {error}", image.src);
+                continue;
+            }
+
+            let local_image = image.html.replace(
+                &format!(r#"src="{}""#, escape_attribute(&image.src)),
+                &format!(r#"src="images/{}""#, escape_attribute(&file_name)),
+            );
+            let local_image = match (&image.caption, &image.alt) {
+                (None, Some(alt)) => format!(
+                    "<figure>{local_image}<figcaption>{}</figcaption></figure>",
+                    escape_text(alt)
+                ),
+                _ => local_image,
+            };
+            html = html.replacen(&image.html, &local_image, 1);
+        }
+
+        for link in &self.links {
+            let Some(rewritten) = exported.rewrite(link, directory, &base_url) else {
+                continue;
+            };
+            html = html.replace(
+                &format!(r#"href="{}""#, escape_attribute(link)),
+                &format!(r#"href="{}""#, escape_attribute(&rewritten)),
+            );
+        }
+
+        let index_path = directory.join("index.html");
+        fs::write(
+            &index_path,
+            format!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{html}\n</body>\n</html>\n",
+                escape_text(&self.title)
+            ),
+        )
+        .whatever_context("Could not write exported page")?;
+
+        Ok(index_path)
+    }
+}
+
+impl ExportedLinks {
+    pub fn new() -> ExportedLinks {
+        ExportedLinks::default()
+    }
+
+    /// Registers the local file (e.g. an exported `index.html`) of the object with the ref_id
+    pub fn insert(&mut self, ref_id: &str, path: PathBuf) {
+        self.paths.insert(ref_id.to_string(), path);
+    }
+
+    /// The replacement for a link of a page exported to `directory`, if it needs one
+    fn rewrite(&self, href: &str, directory: &Path, base_url: &Url) -> Option<String> {
+        let link_id_regex = LINK_ID_REGEX.get_or_init(|| {
+            Regex::new(r"(ref_id=|target=[a-z]+_|goto\.php/[a-z]+/)(?<id>\d+)")
+                .expect("Could not parse regex")
+        });
+
+        if href.starts_with('#') || href.starts_with("mailto:") {
+            return None;
+        }
+        let url = base_url.join(href).ok()?;
+        if url.host_str() != base_url.host_str() {
+            return None;
+        }
+
+        let local_path = link_id_regex
+            .captures(&url.as_str().to_lowercase())
+            .and_then(|captures| self.paths.get(&captures["id"]))
+            .map(|path| relative_path(directory, path));
+        Some(match local_path {
+            Some(path) => path.to_string_lossy().replace('\\', "/"),
+            None => url.to_string(),
+        })
+    }
+}
+
+fn is_text_block(element: ElementRef) -> bool {
+    matches!(
+        element.value().name(),
+        "h1" | "h2" | "h3" | "h4" | "h5" | "h6" | "p" | "li"
+    ) || element
+        .value()
+        .has_class("ilc_Paragraph", scraper::CaseSensitivity::CaseSensitive)
+}
+
+/// The path to `to` as seen from the directory `from`
+pub(crate) fn relative_path(from: &Path, to: &Path) -> PathBuf {
+    let from = from.components().collect::<Vec<_>>();
+    let to = to.components().collect::<Vec<_>>();
+    let common = from
+        .iter()
+        .zip(&to)
+        .take_while(|(from, to)| from == to)
+        .count();
+
+    from[common..]
+        .iter()
+        .filter(|component| !matches!(component, Component::CurDir))
+        .map(|_| Component::ParentDir)
+        .chain(to[common..].iter().copied())
+        .collect()
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Attribute values are serialized with `&` and `"` escaped
+pub(crate) fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
@@ -0,0 +1,106 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local, Timelike};
+use tokio::{
+    sync::{Semaphore, SemaphorePermit},
+    time,
+};
+
+/// Limits for automated access as institutions may require them, see
+/// [`IliasClient::set_politeness`](crate::client::IliasClient::set_politeness)
+#[derive(Debug, Clone)]
+pub struct PolitenessProfile {
+    /// Requests that may be in flight at the same time
+    pub max_concurrency: usize,
+    /// Minimum time between the start of two requests
+    pub delay: Duration,
+    /// Hours of the day (start inclusive, end exclusive, may wrap around midnight) in which
+    /// bulk work like full syncs should preferably be scheduled
+    pub off_peak_hours: Option<(u32, u32)>,
+    /// Sent as `User-Agent`, should name the tool
+    pub user_agent: String,
+    /// Sent as `From`, a contact address of the operator
+    pub contact: Option<String>,
+}
+
+impl PolitenessProfile {
+    /// One request at a time with a second in between, bulk work during the night
+    pub fn new(user_agent: &str, contact: Option<&str>) -> PolitenessProfile {
+        PolitenessProfile {
+            max_concurrency: 1,
+            delay: Duration::from_secs(1),
+            off_peak_hours: Some((20, 6)),
+            user_agent: user_agent.to_string(),
+            contact: contact.map(str::to_string),
+        }
+    }
+
+    pub fn is_off_peak(&self, time: DateTime<Local>) -> bool {
+        let Some((start, end)) = self.off_peak_hours else {
+            return true;
+        };
+        let hour = time.hour();
+        if start <= end {
+            (start..end).contains(&hour)
+        } else {
+            hour >= start || hour < end
+        }
+    }
+}
+
+/// Enforces the concurrency and delay of a profile for all requests of a client
+#[derive(Debug)]
+pub(crate) struct Throttle {
+    profile: PolitenessProfile,
+    slots: Semaphore,
+    last_start: Mutex<Option<Instant>>,
+}
+
+/// Keeps a request slot taken until dropped
+#[derive(Debug)]
+pub(crate) struct ThrottlePermit<'a> {
+    _slot: SemaphorePermit<'a>,
+}
+
+impl Throttle {
+    pub fn new(profile: PolitenessProfile) -> Throttle {
+        Throttle {
+            slots: Semaphore::new(profile.max_concurrency.max(1)),
+            profile,
+            last_start: Mutex::default(),
+        }
+    }
+
+    pub fn profile(&self) -> &PolitenessProfile {
+        &self.profile
+    }
+
+    /// Waits until a request may be started
+    pub async fn acquire(&self) -> ThrottlePermit<'_> {
+        let slot = self
+            .slots
+            .acquire()
+            .await
+            .expect("Throttle semaphore is never closed");
+        loop {
+            let delay = {
+                let mut last_start = self.last_start.lock().expect("Throttle lock poisoned");
+                let delay = last_start
+                    .map(|last_start| self.profile.delay.saturating_sub(last_start.elapsed()))
+                    .unwrap_or_default();
+                if delay.is_zero() {
+                    *last_start = Some(Instant::now());
+                }
+                delay
+            };
+            if delay.is_zero() {
+                return ThrottlePermit { _slot: slot };
+            }
+            // Another request holding a slot may start meanwhile, so the delay is checked again
+            time::sleep(delay).await;
+        }
+    }
+}
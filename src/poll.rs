@@ -0,0 +1,227 @@
+use std::sync::OnceLock;
+
+use log::{debug, info};
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use super::{
+    IliasElement,
+    client::IliasClient,
+    form::HtmlForm,
+    id::{ObjId, RefId},
+    parse_number,
+};
+
+/// An ILIAS poll (`poll`) with a single question
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Poll {
+    pub name: String,
+    pub question: String,
+    pub options: Vec<PollOption>,
+    /// Whether the user already voted, polls only accept one vote per user
+    pub voted: bool,
+    vote_form: Option<HtmlForm>,
+    answer_field: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PollOption {
+    /// Only known while the poll can still be voted on
+    pub id: Option<ObjId>,
+    pub text: String,
+    /// Results are only visible if the poll is configured to show them
+    pub votes: Option<u32>,
+    pub percentage: Option<f64>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static QUESTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FORM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ANSWER_INPUT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LABEL_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static RESULT_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static RESULT_BAR_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static MESSAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static VOTES_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl IliasElement for Poll {
+    fn type_identifier() -> Option<&'static str> {
+        Some("poll")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let question_selector = QUESTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilPollQuestion").expect("Could not parse selector"));
+        let form_selector = FORM_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer form[action]").expect("Could not parse selector")
+        });
+        let answer_input_selector = ANSWER_INPUT_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"input[type="radio"][name], input[type="checkbox"][name]"#)
+                .expect("Could not parse selector")
+        });
+        let label_selector = LABEL_SELECTOR
+            .get_or_init(|| Selector::parse("label[for]").expect("Could not parse selector"));
+        let result_selector = RESULT_SELECTOR.get_or_init(|| {
+            Selector::parse(".ilPollAnswer, .ilPollResult").expect("Could not parse selector")
+        });
+        let result_bar_selector = RESULT_BAR_SELECTOR
+            .get_or_init(|| Selector::parse(".progress-bar").expect("Could not parse selector"));
+        let message_selector = MESSAGE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer .alert").expect("Could not parse selector")
+        });
+        let votes_regex = VOTES_REGEX
+            .get_or_init(|| Regex::new(r"\((?<votes>\d+)\)").expect("Could not parse regex"));
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let question = element
+            .select(question_selector)
+            .next()
+            .map(|question| question.text().collect::<String>().trim().to_string())
+            .unwrap_or_else(|| name.clone());
+
+        let mut options = vec![];
+        let mut vote_form = None;
+        let mut answer_field = None;
+        for form in element.select(form_selector) {
+            let inputs = form.select(answer_input_selector).collect::<Vec<_>>();
+            if inputs.is_empty() {
+                continue;
+            }
+            answer_field = inputs[0].attr("name").map(str::to_string);
+            for input in inputs {
+                let text = input
+                    .attr("id")
+                    .and_then(|input_id| {
+                        form.select(label_selector)
+                            .find(|label| label.attr("for") == Some(input_id))
+                    })
+                    .or_else(|| input.parent().and_then(ElementRef::wrap))
+                    .map(|label| label.text().collect::<String>().trim().to_string())
+                    .unwrap_or_default();
+                options.push(PollOption {
+                    id: input.attr("value").and_then(|id| id.parse().ok()),
+                    text,
+                    votes: None,
+                    percentage: None,
+                });
+            }
+            vote_form = Some(HtmlForm::parse(form)?);
+            break;
+        }
+
+        for result in element.select(result_selector) {
+            let text = result.text().collect::<String>();
+            let bar = result.select(result_bar_selector).next();
+            let votes = votes_regex
+                .captures(&text)
+                .and_then(|captures| captures["votes"].parse().ok());
+            let percentage = bar.and_then(|bar| {
+                bar.attr("aria-valuenow")
+                    .and_then(parse_number)
+                    .or_else(|| parse_number(&bar.text().collect::<String>()))
+            });
+            let answer = bar
+                .map(|bar| text.replace(&bar.text().collect::<String>(), ""))
+                .unwrap_or(text);
+            let answer = votes_regex.replace(&answer, "").trim().to_string();
+
+            match options.iter_mut().find(|option| option.text == answer) {
+                Some(option) => {
+                    option.votes = votes;
+                    option.percentage = percentage;
+                }
+                None => options.push(PollOption {
+                    id: None,
+                    text: answer,
+                    votes,
+                    percentage,
+                }),
+            }
+        }
+
+        let messages = element
+            .select(message_selector)
+            .map(|message| message.text().collect::<String>().to_lowercase())
+            .collect::<Vec<_>>();
+        let voted = messages.iter().any(|message| {
+            message.contains("bereits abgestimmt")
+                || message.contains("already voted")
+                || message.contains("stimme wurde")
+        }) || (vote_form.is_none() && !options.is_empty());
+
+        let poll = Poll {
+            name,
+            question,
+            options,
+            voted,
+            vote_form,
+            answer_field,
+        };
+        debug!("Poll: {:?}", poll);
+
+        Ok(poll)
+    }
+}
+
+impl Poll {
+    pub fn can_vote(&self) -> bool {
+        !self.voted && self.vote_form.is_some()
+    }
+
+    /// Votes for the options with the given ids, polls may allow more than one answer
+    pub fn vote(
+        &mut self,
+        ilias_client: &IliasClient,
+        option_ids: &[&str],
+    ) -> Result<(), Whatever> {
+        if self.voted {
+            whatever!("Already voted in poll {}", self.name);
+        }
+        let mut form = self
+            .vote_form
+            .clone()
+            .whatever_context(format!("Poll {} can not be voted on", self.name))?;
+        let answer_field = self
+            .answer_field
+            .as_ref()
+            .whatever_context("Poll has no answers")?;
+        for id in option_ids {
+            form.add(answer_field, id);
+        }
+
+        let response = form
+            .submit(
+                ilias_client,
+                form.buttons.first().map(|(name, _)| name.as_str()),
+            )
+            .whatever_context("Could not submit vote")?;
+        if ilias_client.is_alert_response(response)? {
+            whatever!("Ilias rejected the vote in poll {}", self.name);
+        }
+
+        info!("Voted in poll {}", self.name);
+        self.voted = true;
+        Ok(())
+    }
+}
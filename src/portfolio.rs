@@ -0,0 +1,188 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement, client::IliasClient, id::RefId, page::Page, querypath_from_href,
+    reference::Reference, table::Table,
+};
+
+/// An ILIAS portfolio (`prtf`) or portfolio template (`prtt`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Portfolio {
+    pub name: String,
+    pub pages: Vec<PortfolioPage>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortfolioPage {
+    pub title: String,
+    content: Reference<Page>,
+}
+
+/// The portfolios of the user with their sharing state, from the dashboard
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortfolioOverview {
+    pub portfolios: Vec<PortfolioSummary>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PortfolioSummary {
+    pub title: String,
+    pub online: Option<bool>,
+    /// Whether the portfolio is shared with other users or publicly
+    pub shared: Option<bool>,
+    pub default: bool,
+    pub portfolio: Reference<Portfolio>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PAGE_TAB_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+const TITLE_COLUMNS: [&str; 2] = ["Titel", "Title"];
+
+impl IliasElement for Portfolio {
+    fn type_identifier() -> Option<&'static str> {
+        Some("prtf")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header, .ilHeader").expect("Could not parse selector")
+        });
+        let page_tab_selector = PAGE_TAB_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilTab a[href], .ilTabs a[href]").expect("Could not parse selector")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+
+        // Every portfolio page is a tab, the first one is shown right away
+        let mut pages = element
+            .select(page_tab_selector)
+            .filter_map(|tab| {
+                let querypath = querypath_from_href(tab.attr("href")?);
+                if !(querypath.contains("user_page=") || querypath.contains("ppage=")) {
+                    return None;
+                }
+                Some(PortfolioPage {
+                    title: tab.text().collect::<String>().trim().to_string(),
+                    content: Reference::Unresolved(querypath),
+                })
+            })
+            .collect::<Vec<_>>();
+        if pages.is_empty() {
+            pages.push(PortfolioPage {
+                title: name.clone(),
+                content: Reference::Resolved(Page::parse(element, ilias_client)?),
+            });
+        }
+
+        let portfolio = Portfolio { name, pages };
+        debug!("Portfolio: {:?}", portfolio);
+
+        Ok(portfolio)
+    }
+}
+
+impl Portfolio {
+    pub fn template_querypath_from_id(id: &str) -> String {
+        format!("goto.php/prtt/{id}")
+    }
+}
+
+impl PortfolioPage {
+    pub fn get_content(&mut self, ilias_client: &IliasClient) -> Result<Option<&Page>, Whatever> {
+        self.content
+            .resolve_cached(ilias_client)
+            .whatever_context("Could not parse portfolio page")
+    }
+}
+
+impl IliasElement for PortfolioOverview {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let table_selector = TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        let mut portfolios = vec![];
+        for table in element.select(table_selector) {
+            let table = Table::parse(table);
+            if table.column(&TITLE_COLUMNS).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(title_cell) = table.cell(row, &TITLE_COLUMNS) else {
+                    continue;
+                };
+                let yes_no = |names: &[&str]| {
+                    table.cell_text(row, names).map(|value| {
+                        let value = value.trim().to_lowercase();
+                        !(value.is_empty()
+                            || ["nein", "no", "offline", "-"].contains(&value.as_str())
+                            || value.contains("nicht")
+                            || value.contains("not "))
+                    })
+                };
+
+                portfolios.push(PortfolioSummary {
+                    title: title_cell.text().collect::<String>().trim().to_string(),
+                    online: yes_no(&["Online", "Status"]),
+                    shared: yes_no(&["Freigabe", "Shared", "Share"]),
+                    default: yes_no(&["Standard", "Default"]).unwrap_or(false),
+                    portfolio: Reference::from_optional_querypath(
+                        title_cell
+                            .select(link_selector)
+                            .next()
+                            .and_then(|link| link.attr("href"))
+                            .map(querypath_from_href),
+                    ),
+                });
+            }
+        }
+        debug!("Portfolios: {:?}", portfolios);
+
+        Ok(PortfolioOverview { portfolios })
+    }
+}
+
+impl PortfolioOverview {
+    pub const QUERYPATH: &str = "ilias.php?baseClass=ilDashboardGUI&cmd=jumpToPortfolio";
+
+    pub fn fetch(ilias_client: &IliasClient) -> Result<PortfolioOverview, Whatever> {
+        let page = ilias_client
+            .get_querypath(Self::QUERYPATH)
+            .whatever_context("Could not get portfolio overview")?;
+        PortfolioOverview::parse(page.root_element(), ilias_client)
+    }
+}
@@ -1,8 +1,12 @@
+use scraper::Html;
+use serde::{Deserialize, Serialize};
 use snafu::{whatever, ResultExt, Whatever};
 
-use crate::{client::IliasClient, IliasElement};
+use crate::{agreement::Agreement, client::IliasClient, IliasElement};
 
-#[derive(Debug)]
+/// A linked page that is only fetched when needed. Serializes to the parsed element if it is
+/// resolved and to the querypath otherwise, so cached structures can still be resolved later.
+#[derive(Debug, Serialize, Deserialize)]
 pub enum Reference<T> {
     Unavailable,
     Unresolved(String),
@@ -27,15 +31,123 @@ impl<T> Reference<T> {
 
 impl<T: IliasElement> Reference<T> {
     pub fn resolve(&self, ilias_client: &IliasClient) -> Result<T, Whatever> {
-        let querypath = match self {
-            Self::Unavailable => whatever!("Reference unavailable"),
-            Self::Resolved(_) => whatever!("Already resolved"),
-            Self::Unresolved(querypath) => querypath,
-        };
-
+        let querypath = self.unresolved_querypath()?;
         let element = ilias_client
             .get_querypath(querypath)
             .whatever_context("Could not get querypath from element")?;
-        T::parse(element.root_element(), ilias_client)
+        parse_page(ilias_client, querypath, &element)
+    }
+
+    /// Fetches the page without blocking. Parsing happens in the awaiting task, so this only
+    /// works for types that do not make further requests while parsing, see
+    /// [`Reference::resolve_all`] for the others.
+    pub async fn resolve_async(&self, ilias_client: &IliasClient) -> Result<T, Whatever> {
+        let querypath = self.unresolved_querypath()?;
+        let element = ilias_client
+            .get_querypath_async(querypath)
+            .await
+            .whatever_context("Could not get querypath from element")?;
+        parse_page(ilias_client, querypath, &element)
+    }
+
+    /// Resolves the reference on first use and keeps the result, so later calls do not fetch
+    /// the page again. `None` if the reference is unavailable.
+    pub fn resolve_cached(&mut self, ilias_client: &IliasClient) -> Result<Option<&T>, Whatever> {
+        Ok(self
+            .resolve_cached_mut(ilias_client)?
+            .map(|element| &*element))
+    }
+
+    pub fn resolve_cached_mut(
+        &mut self,
+        ilias_client: &IliasClient,
+    ) -> Result<Option<&mut T>, Whatever> {
+        self.resolve_cached_with(ilias_client, |_, _| {})
+    }
+
+    /// Like [`Reference::resolve_cached_mut`], but lets `after_resolve` complete the element
+    /// with what its page does not show, e.g. details of the object that links to it. It gets
+    /// the querypath the element was parsed from and is only called on first use.
+    pub fn resolve_cached_with(
+        &mut self,
+        ilias_client: &IliasClient,
+        after_resolve: impl FnOnce(&mut T, &str),
+    ) -> Result<Option<&mut T>, Whatever> {
+        if let Self::Unresolved(querypath) = &*self {
+            let mut element = self.resolve(ilias_client)?;
+            after_resolve(&mut element, querypath);
+            *self = Self::Resolved(element);
+        }
+        Ok(match self {
+            Self::Resolved(element) => Some(element),
+            _ => None,
+        })
     }
+
+    /// Resolves all unresolved references in place, fetching up to `parallelism` pages at the
+    /// same time. References that fail stay unresolved, the first error is returned after all
+    /// others are resolved.
+    pub fn resolve_all(
+        ilias_client: &IliasClient,
+        references: &mut [Reference<T>],
+        parallelism: usize,
+    ) -> Result<(), Whatever> {
+        let unresolved = references
+            .iter()
+            .enumerate()
+            .filter_map(|(index, reference)| match reference {
+                Self::Unresolved(querypath) => Some((index, querypath.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let querypaths = unresolved
+            .iter()
+            .map(|(_, querypath)| querypath.as_str())
+            .collect::<Vec<_>>();
+        let pages = ilias_client.get_querypaths_concurrently(&querypaths, parallelism);
+
+        // Parsing may fetch further pages, so it happens once all pages are fetched
+        let mut errors = vec![];
+        for ((index, querypath), page) in unresolved.iter().zip(pages) {
+            let resolved = page.and_then(|page| parse_page(ilias_client, querypath, &page));
+            match resolved {
+                Ok(element) => references[*index] = Self::Resolved(element),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        let failed = errors.len();
+        match errors.into_iter().next() {
+            Some(error) => Err(error).whatever_context(format!(
+                "Could not resolve {failed} of {} references",
+                unresolved.len()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn unresolved_querypath(&self) -> Result<&str, Whatever> {
+        match self {
+            Self::Unavailable => whatever!("Reference unavailable"),
+            Self::Resolved(_) => whatever!("Already resolved"),
+            Self::Unresolved(querypath) => Ok(querypath),
+        }
+    }
+}
+
+fn parse_page<T: IliasElement>(
+    ilias_client: &IliasClient,
+    querypath: &str,
+    element: &Html,
+) -> Result<T, Whatever> {
+    T::parse(element.root_element(), ilias_client).or_else(|error| {
+        if let Some(agreement) = Agreement::detect(element, ilias_client) {
+            whatever!(
+                "{querypath} shows the agreement \"{}\" that has to be accepted first",
+                agreement.title
+            );
+        }
+        ilias_client.capture_fixture(querypath, element, &error);
+        Err(error)
+    })
 }
@@ -0,0 +1,200 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, NaiveTime, Weekday};
+use regex::Regex;
+use snafu::{OptionExt, Whatever};
+
+use super::{
+    IliasElement,
+    calendar::{Appointment, Calendar},
+    group::Group,
+    session::Session,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScheduleEntryKind {
+    Lecture,
+    Tutorial,
+    Appointment,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduleEntry {
+    pub kind: ScheduleEntryKind,
+    pub title: String,
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    pub location: Option<String>,
+    pub course: Option<String>,
+    pub querypath: Option<String>,
+}
+
+/// All schedule entries of a single calendar week (monday to sunday)
+#[derive(Debug, Clone)]
+pub struct WeeklySchedule {
+    pub week_start: NaiveDate,
+    entries: Vec<ScheduleEntry>,
+}
+
+const TUTORIAL_KEYWORDS: [&str; 4] = ["tutorium", "übung", "tutorial", "exercise"];
+const LECTURE_KEYWORDS: [&str; 2] = ["vorlesung", "lecture"];
+
+static SLOT_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl ScheduleEntry {
+    pub fn weekday(&self) -> Weekday {
+        self.start.weekday()
+    }
+
+    /// Guesses whether a title refers to a lecture, a tutorial or something else
+    pub fn kind_from_title(title: &str) -> ScheduleEntryKind {
+        let title = title.to_lowercase();
+        if TUTORIAL_KEYWORDS
+            .iter()
+            .any(|keyword| title.contains(keyword))
+        {
+            ScheduleEntryKind::Tutorial
+        } else if LECTURE_KEYWORDS
+            .iter()
+            .any(|keyword| title.contains(keyword))
+        {
+            ScheduleEntryKind::Lecture
+        } else {
+            ScheduleEntryKind::Appointment
+        }
+    }
+
+    /// The slot of a tutorial group in the week starting at `week_start`, guessed from a weekday
+    /// and time like "Mo 14:00-15:30" in its name or description
+    pub fn from_tutorial_group(group: &Group, week_start: NaiveDate) -> Option<ScheduleEntry> {
+        let slot_regex = SLOT_REGEX.get_or_init(|| {
+            Regex::new(r"(?i)\b(?<day>mo|montag|mon|monday|di|dienstag|tue|tuesday|mi|mittwoch|wed|wednesday|do|donnerstag|thu|thursday|fr|freitag|fri|friday|sa|samstag|sat|saturday|so|sonntag|sun|sunday)\b\.?,?\s+(?<start>\d{1,2}[:.]\d{2})(?:\s*(?:-|–|bis)\s*(?<end>\d{1,2}[:.]\d{2}))?")
+                .expect("Could not parse regex")
+        });
+
+        let text = format!("{} {}", group.name, group.description);
+        let captures = slot_regex.captures(&text)?;
+        let weekday = match &captures["day"].to_lowercase()[..2] {
+            "mo" => Weekday::Mon,
+            "di" | "tu" => Weekday::Tue,
+            "mi" | "we" => Weekday::Wed,
+            "do" | "th" => Weekday::Thu,
+            "fr" => Weekday::Fri,
+            "sa" => Weekday::Sat,
+            _ => Weekday::Sun,
+        };
+        let date = week_start + Days::new(weekday.num_days_from_monday() as u64);
+        let at = |time: &str| {
+            let time = NaiveTime::parse_from_str(&time.replace('.', ":"), "%H:%M").ok()?;
+            date.and_time(time).and_local_timezone(Local).earliest()
+        };
+
+        Some(ScheduleEntry {
+            kind: ScheduleEntryKind::Tutorial,
+            title: group.name.clone(),
+            start: at(&captures["start"])?,
+            end: captures.name("end").and_then(|end| at(end.as_str())),
+            location: None,
+            course: None,
+            querypath: Group::querypath_from_id(group.id),
+        })
+    }
+}
+
+impl From<&Appointment> for ScheduleEntry {
+    fn from(appointment: &Appointment) -> Self {
+        ScheduleEntry {
+            kind: ScheduleEntry::kind_from_title(&appointment.title),
+            title: appointment.title.clone(),
+            start: appointment.start,
+            end: appointment.end,
+            location: appointment.location.clone(),
+            course: appointment.object_title.clone(),
+            querypath: appointment.object_querypath.clone(),
+        }
+    }
+}
+
+impl TryFrom<&Session> for ScheduleEntry {
+    type Error = Whatever;
+
+    /// Fails for sessions without a date
+    fn try_from(session: &Session) -> Result<Self, Whatever> {
+        Ok(ScheduleEntry {
+            kind: ScheduleEntry::kind_from_title(&session.name),
+            title: session.name.clone(),
+            start: session
+                .start
+                .whatever_context(format!("Session {} has no date", session.name))?,
+            end: session.end,
+            location: session.location.clone(),
+            course: None,
+            querypath: None,
+        })
+    }
+}
+
+impl WeeklySchedule {
+    /// Creates an empty schedule for the week that contains `date`
+    pub fn for_week_of(date: DateTime<Local>) -> WeeklySchedule {
+        let date = date.date_naive();
+        let week_start = date - Days::new(date.weekday().num_days_from_monday() as u64);
+        WeeklySchedule {
+            week_start,
+            entries: vec![],
+        }
+    }
+
+    pub fn contains(&self, date: DateTime<Local>) -> bool {
+        let date = date.date_naive();
+        date >= self.week_start && date < self.week_start + Days::new(7)
+    }
+
+    /// Adds the entry if it takes place in this week, returning whether it was added
+    pub fn add(&mut self, entry: ScheduleEntry) -> bool {
+        if !self.contains(entry.start) {
+            return false;
+        }
+        let position = self
+            .entries
+            .partition_point(|existing| existing.start <= entry.start);
+        self.entries.insert(position, entry);
+        true
+    }
+
+    pub fn add_all<T: Into<ScheduleEntry>>(&mut self, entries: impl IntoIterator<Item = T>) {
+        for entry in entries {
+            self.add(entry.into());
+        }
+    }
+
+    pub fn add_calendar(&mut self, calendar: &Calendar) {
+        self.add_all(&calendar.appointments);
+    }
+
+    /// Adds the slot of a tutorial group, returning whether one was found in its name or
+    /// description
+    pub fn add_tutorial_group(&mut self, group: &Group) -> bool {
+        ScheduleEntry::from_tutorial_group(group, self.week_start)
+            .is_some_and(|entry| self.add(entry))
+    }
+
+    /// Adds the sessions that have a date
+    pub fn add_sessions<'a>(&mut self, sessions: impl IntoIterator<Item = &'a Session>) {
+        for session in sessions {
+            if let Ok(entry) = ScheduleEntry::try_from(session) {
+                self.add(entry);
+            }
+        }
+    }
+
+    pub fn entries(&self) -> &[ScheduleEntry] {
+        &self.entries
+    }
+
+    pub fn day(&self, weekday: Weekday) -> impl Iterator<Item = &ScheduleEntry> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.weekday() == weekday)
+    }
+}
@@ -0,0 +1,155 @@
+use std::{fs, path::Path, sync::OnceLock};
+
+use log::{debug, warn};
+use reqwest::Url;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    ILIAS_URL, IliasElement, client::IliasClient, id::RefId, local_file::unpack_zip,
+    mirror::mirror, querypath_from_href,
+};
+
+/// An ILIAS SCORM learning module (`sahs`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ScormModule {
+    pub name: String,
+    pub description: String,
+    /// The offline package or export of the module, only offered if the module allows it
+    download_querypath: Option<String>,
+    player_querypath: Option<String>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DOWNLOAD_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PLAYER_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static FRAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for ScormModule {
+    fn type_identifier() -> Option<&'static str> {
+        Some("sahs")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let download_link_selector = DOWNLOAD_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#"a[href*="offlineMode"], a[href*="downloadExportFile"], a[href*="cmd=download"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+        let player_link_selector = PLAYER_LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(r#"a[href*="ilSAHSPresentationGUI"], a[href*="ilsahspresentationgui"]"#)
+                .expect("Could not parse selector")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+        let link_querypath = |selector: &Selector| {
+            element
+                .select(selector)
+                .next()
+                .and_then(|link| link.attr("href"))
+                .map(querypath_from_href)
+        };
+
+        let scorm_module = ScormModule {
+            name,
+            description,
+            download_querypath: link_querypath(download_link_selector),
+            player_querypath: link_querypath(player_link_selector),
+        };
+        debug!("SCORM module: {:?}", scorm_module);
+
+        Ok(scorm_module)
+    }
+}
+
+impl ScormModule {
+    pub fn has_download(&self) -> bool {
+        self.download_querypath.is_some()
+    }
+
+    /// Downloads the package zip of the module
+    pub fn download(&self, ilias_client: &IliasClient, to: &Path) -> Result<(), Whatever> {
+        ilias_client.download_file(
+            self.download_querypath
+                .as_ref()
+                .whatever_context(format!("{} offers no download", self.name))?,
+            to,
+        )
+    }
+
+    /// Puts the module into `directory`, from its package if it offers one and otherwise
+    /// by mirroring the content frames of the player
+    pub fn archive(&self, ilias_client: &IliasClient, directory: &Path) -> Result<(), Whatever> {
+        fs::create_dir_all(directory).whatever_context("Could not create archive directory")?;
+        if self.has_download() {
+            let package = directory.join("package.zip");
+            self.download(ilias_client, &package)?;
+            return unpack_zip(&package, directory);
+        }
+        self.archive_player(ilias_client, directory)
+    }
+
+    fn archive_player(&self, ilias_client: &IliasClient, directory: &Path) -> Result<(), Whatever> {
+        let frame_selector = FRAME_SELECTOR.get_or_init(|| {
+            Selector::parse("frame[src], iframe[src]").expect("Could not parse selector")
+        });
+
+        let player_querypath = self
+            .player_querypath
+            .as_ref()
+            .whatever_context(format!("{} can not be started", self.name))?;
+        let base_url = Url::parse(ILIAS_URL).whatever_context("Could not parse ilias url")?;
+        let player_url = base_url
+            .join(player_querypath)
+            .whatever_context("Could not resolve player url")?;
+        let player = ilias_client
+            .get_querypath(player_querypath)
+            .whatever_context("Could not get SCORM player")?;
+
+        let frames = player
+            .select(frame_selector)
+            .filter_map(|frame| player_url.join(frame.attr("src")?).ok())
+            .filter(|url| url.host_str() == base_url.host_str())
+            .collect::<Vec<_>>();
+        if frames.is_empty() {
+            warn!("SCORM player of {} has no content frames", self.name);
+        }
+        for (index, frame_url) in frames.iter().enumerate() {
+            let frame_directory = match index {
+                0 => directory.to_path_buf(),
+                _ => directory.join(format!("frame_{index}")),
+            };
+            mirror(ilias_client, frame_url, &frame_directory)?;
+        }
+
+        Ok(())
+    }
+}
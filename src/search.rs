@@ -0,0 +1,160 @@
+use std::{collections::HashSet, sync::OnceLock};
+
+use log::{debug, info};
+use scraper::{ElementRef, Html, Selector};
+use snafu::{OptionExt, ResultExt, Whatever, whatever};
+
+use super::{client::IliasClient, form::HtmlForm, id::RefId, querypath_from_href, type_from_icon};
+
+/// Restricts a search, see [`IliasClient::search`]
+#[derive(Debug, Clone, Default)]
+pub struct SearchFilter {
+    /// ILIAS types to search for (e.g. `file`, `crs`), all types if empty
+    pub types: Vec<String>,
+    /// The ref_id of a container to search in instead of the whole repository
+    pub area: Option<RefId>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub title: String,
+    pub description: String,
+    /// The ILIAS type of the object (e.g. `file`), guessed from its icon
+    pub type_identifier: Option<String>,
+    pub ref_id: Option<RefId>,
+    /// Titles of the containers the object lies in, outermost first
+    pub path: Vec<String>,
+    pub querypath: String,
+}
+
+static ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_ICON_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ITEM_PATH_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PAGE_LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+pub(crate) const QUERYPATH: &str = "ilias.php?baseClass=ilSearchControllerGUI";
+
+/// Fills in and submits the search form and collects the hits of all result pages
+pub(crate) fn search(
+    ilias_client: &IliasClient,
+    query: &str,
+    filter: &SearchFilter,
+) -> Result<Vec<SearchHit>, Whatever> {
+    let page_link_selector = PAGE_LINK_SELECTOR
+        .get_or_init(|| Selector::parse(".ilTableNav a[href]").expect("Could not parse selector"));
+
+    let search_page = ilias_client
+        .get_querypath(QUERYPATH)
+        .whatever_context("Could not get search page")?;
+    let mut form = HtmlForm::find(&search_page, |form| form.has_button("performSearch"))
+        .whatever_context("Could not find search form")?;
+    form.set("term", query);
+    if !filter.types.is_empty() {
+        form.set("type", "2");
+        for type_identifier in &filter.types {
+            form.set(&format!("filter_type[{type_identifier}]"), "1");
+        }
+    }
+    if let Some(area) = &filter.area {
+        form.set("area", &area.to_string());
+    }
+
+    let response = form
+        .submit(ilias_client, Some("performSearch"))
+        .whatever_context("Could not submit search")?;
+    let first_page = Html::parse_document(&ilias_client.get_text(response)?);
+
+    let mut hits = parse_hits(first_page.root_element());
+    let mut unvisited = page_links(&first_page, page_link_selector);
+    let mut visited = HashSet::new();
+    while let Some(querypath) = unvisited.pop() {
+        if !visited.insert(querypath.clone()) {
+            continue;
+        }
+        let page = ilias_client
+            .get_querypath(&querypath)
+            .whatever_context("Could not get search result page")?;
+        for hit in parse_hits(page.root_element()) {
+            if !hits.iter().any(|known| known.querypath == hit.querypath) {
+                hits.push(hit);
+            }
+        }
+        unvisited.extend(
+            page_links(&page, page_link_selector)
+                .into_iter()
+                .filter(|querypath| !visited.contains(querypath)),
+        );
+    }
+
+    if hits.is_empty() && first_page.html().contains("alert-danger") {
+        whatever!("Ilias rejected search for {query}");
+    }
+    info!("Found {} hits for {query}", hits.len());
+    Ok(hits)
+}
+
+fn page_links(page: &Html, page_link_selector: &Selector) -> Vec<String> {
+    page.select(page_link_selector)
+        .filter_map(|link| link.attr("href"))
+        .map(querypath_from_href)
+        .collect()
+}
+
+fn parse_hits(element: ElementRef) -> Vec<SearchHit> {
+    let item_selector = ITEM_SELECTOR.get_or_init(|| {
+        Selector::parse(".ilObjListRow, .il_ContainerListItem, .il-item")
+            .expect("Could not parse selector")
+    });
+    let item_title_selector = ITEM_TITLE_SELECTOR.get_or_init(|| {
+        Selector::parse(".il_ContainerItemTitle a[href], .il-item-title a[href]")
+            .expect("Could not parse selector")
+    });
+    let item_description_selector = ITEM_DESCRIPTION_SELECTOR.get_or_init(|| {
+        Selector::parse(".il_Description, .il-item-description").expect("Could not parse selector")
+    });
+    let item_icon_selector = ITEM_ICON_SELECTOR.get_or_init(|| {
+        Selector::parse("img.ilListItemIcon, img.icon").expect("Could not parse selector")
+    });
+    let item_path_selector = ITEM_PATH_SELECTOR.get_or_init(|| {
+        Selector::parse(".il_ItemPath a, .ilSearchResultPath a, .breadcrumb a")
+            .expect("Could not parse selector")
+    });
+
+    // Nested rows are matched twice, so skip known querypaths
+    let mut hits: Vec<SearchHit> = vec![];
+    for item in element.select(item_selector) {
+        let Some(title) = item.select(item_title_selector).next() else {
+            continue;
+        };
+        let querypath = querypath_from_href(title.attr("href").unwrap_or_default());
+        if hits.iter().any(|hit| hit.querypath == querypath) {
+            continue;
+        }
+
+        hits.push(SearchHit {
+            title: title.text().collect::<String>().trim().to_string(),
+            description: item
+                .select(item_description_selector)
+                .next()
+                .map(|description| description.text().collect::<String>().trim().to_string())
+                .unwrap_or_default(),
+            type_identifier: item
+                .select(item_icon_selector)
+                .next()
+                .and_then(|icon| icon.attr("src"))
+                .and_then(type_from_icon),
+            ref_id: RefId::from_querypath(&querypath),
+            path: item
+                .select(item_path_selector)
+                .map(|container| container.text().collect::<String>().trim().to_string())
+                .filter(|container| !container.is_empty())
+                .collect(),
+            querypath,
+        });
+    }
+    debug!("Search hits: {:?}", hits);
+
+    hits
+}
@@ -0,0 +1,128 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local, NaiveTime};
+use log::debug;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, Whatever};
+
+use super::{
+    IliasElement,
+    client::IliasClient,
+    file::File,
+    folder::{FolderElement, parse_container_elements},
+    id::RefId,
+    info_screen::InfoScreen,
+    parse_date,
+};
+
+/// An ILIAS session (`sess`), a single course appointment with its materials
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Session {
+    pub name: String,
+    pub description: String,
+    pub start: Option<DateTime<Local>>,
+    pub end: Option<DateTime<Local>>,
+    pub location: Option<String>,
+    pub lecturer: Option<String>,
+    pub materials: Vec<File>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for Session {
+    fn type_identifier() -> Option<&'static str> {
+        Some("sess")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+
+        let info = InfoScreen::parse(element, ilias_client)?;
+        let (start, end) = info
+            .property(&["Termin", "Appointment", "Datum", "Date"])
+            .map(|appointment| parse_appointment(&appointment.value))
+            .unwrap_or_default();
+        let location = info
+            .property(&["Ort", "Location"])
+            .map(|location| location.value.clone())
+            .filter(|location| !location.is_empty());
+        let lecturer = info
+            .property(&["Dozent", "Lecturer", "Tutor"])
+            .or_else(|| {
+                info.section(&["Ansprechpartner", "Tutor", "Contact"])?
+                    .property(&["Name"])
+            })
+            .map(|lecturer| lecturer.value.clone())
+            .filter(|lecturer| !lecturer.is_empty());
+
+        let materials = parse_container_elements(element, ilias_client)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|element| match element {
+                FolderElement::File { file, .. } => Some(file),
+                _ => None,
+            })
+            .collect();
+
+        let session = Session {
+            name,
+            description,
+            start,
+            end,
+            location,
+            lecturer,
+            materials,
+        };
+        debug!("Session: {:?}", session);
+
+        Ok(session)
+    }
+}
+
+/// Appointments look like "13. Nov 2024, 10:00 - 11:30" or span days with a full end date
+fn parse_appointment(appointment: &str) -> (Option<DateTime<Local>>, Option<DateTime<Local>>) {
+    let (start, end) = appointment
+        .split_once(" - ")
+        .map_or((appointment, None), |(start, end)| {
+            (start, Some(end.trim()))
+        });
+    let Ok(start) = parse_date(start.trim()) else {
+        return (None, None);
+    };
+    let end = end.and_then(|end| {
+        parse_date(end).ok().or_else(|| {
+            NaiveTime::parse_from_str(end, "%H:%M")
+                .ok()
+                .and_then(|time| start.with_time(time).earliest())
+        })
+    });
+    (Some(start), end)
+}
@@ -0,0 +1,185 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+use log::debug;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, Whatever};
+
+use super::{
+    IliasElement,
+    client::IliasClient,
+    id::RefId,
+    info_screen::InfoScreen,
+    overview::{Deadline, DeadlineKind},
+    parse_date,
+};
+
+/// An ILIAS survey (`svy`)
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Survey {
+    pub name: String,
+    pub description: String,
+    pub querypath: Option<String>,
+    pub available_from: Option<DateTime<Local>>,
+    pub available_until: Option<DateTime<Local>>,
+    pub anonymous: Option<bool>,
+    pub participation: SurveyParticipation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SurveyParticipation {
+    NotStarted,
+    Started,
+    Finished,
+    Unknown,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PERMALINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ACTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static MESSAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for Survey {
+    fn type_identifier() -> Option<&'static str> {
+        Some("svy")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let permalink_selector = PERMALINK_SELECTOR.get_or_init(|| {
+            Selector::parse("#current_perma_link").expect("Could not parse selector")
+        });
+        let action_selector = ACTION_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer .btn, #ilContentContainer input[type=submit]")
+                .expect("Could not parse selector")
+        });
+        let message_selector = MESSAGE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer .alert").expect("Could not parse selector")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+        let querypath = element
+            .select(permalink_selector)
+            .next()
+            .and_then(|permalink| permalink.attr("value"))
+            .map(str::to_string);
+
+        let info = InfoScreen::parse(element, ilias_client)?;
+        let (available_from, available_until) = info
+            .property(&["Verfügbarkeit", "Availability", "Zeitraum", "Period"])
+            .and_then(|property| property.value.split_once(" - "))
+            .map(|(from, until)| (parse_date(from.trim()).ok(), parse_date(until.trim()).ok()))
+            .unwrap_or_else(|| {
+                let date_of = |names: &[&str]| {
+                    info.property(names)
+                        .and_then(|property| parse_date(&property.value).ok())
+                };
+                (
+                    date_of(&["Startzeit", "Start", "Starting Time"]),
+                    date_of(&["Endzeit", "Ende", "End", "Ending Time"]),
+                )
+            });
+        let anonymous = info
+            .property(&["Anonymisierung", "Anonymization", "Anonymität", "Anonymity"])
+            .map(|property| {
+                let value = property.value.to_lowercase();
+                !(value.contains("nicht")
+                    || value.contains("not")
+                    || value.contains("personalisiert")
+                    || value.contains("personalized"))
+            });
+
+        let actions = element
+            .select(action_selector)
+            .map(|action| {
+                action
+                    .attr("value")
+                    .map(str::to_string)
+                    .unwrap_or_else(|| action.text().collect::<String>())
+                    .to_lowercase()
+            })
+            .collect::<Vec<_>>();
+        let messages = element
+            .select(message_selector)
+            .map(|message| message.text().collect::<String>().to_lowercase())
+            .collect::<Vec<_>>();
+        let participation = if messages.iter().any(|message| {
+            message.contains("bereits beendet") || message.contains("already finished")
+        }) {
+            SurveyParticipation::Finished
+        } else if actions
+            .iter()
+            .any(|action| action.contains("fortsetzen") || action.contains("resume"))
+        {
+            SurveyParticipation::Started
+        } else if actions
+            .iter()
+            .any(|action| action.contains("starten") || action.contains("start"))
+        {
+            SurveyParticipation::NotStarted
+        } else {
+            SurveyParticipation::Unknown
+        };
+
+        let survey = Survey {
+            name,
+            description,
+            querypath,
+            available_from,
+            available_until,
+            anonymous,
+            participation,
+        };
+        debug!("Survey: {:?}", survey);
+
+        Ok(survey)
+    }
+}
+
+impl Survey {
+    pub fn is_pending(&self) -> bool {
+        self.participation != SurveyParticipation::Finished
+            && self.available_until.is_none_or(|date| date >= Local::now())
+    }
+
+    /// The end of the availability window if the survey still has to be completed
+    pub fn deadline(&self) -> Option<Deadline> {
+        if !self.is_pending() {
+            return None;
+        }
+        Some(Deadline {
+            kind: DeadlineKind::Survey,
+            title: self.name.clone(),
+            date: self.available_until?,
+            source: self.name.clone(),
+            querypath: self.querypath.clone(),
+            state: None,
+        })
+    }
+}
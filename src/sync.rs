@@ -0,0 +1,439 @@
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use chrono::{DateTime, Local};
+use log::{debug, info, warn};
+use serde::{Deserialize, Serialize};
+use snafu::{Report, ResultExt, Whatever};
+
+pub mod import;
+
+use super::{
+    IliasContainer,
+    client::{IliasClient, write_private},
+    file::{File, VerifiedDownload},
+    folder::{FolderElement, walk::FolderWalk},
+    id::RefId,
+    local_file::file_name_component,
+    lockfile::DirectoryLock,
+};
+
+#[cfg(feature = "encryption")]
+use super::encryption::Encryption;
+
+/// How long a sync may go without refreshing its lock before another sync takes the directory
+const LOCK_STALE_AFTER: Duration = Duration::from_secs(60 * 60);
+
+/// What a sync knows about the files it put into its target directory
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SyncState {
+    /// By path relative to the target directory
+    pub files: BTreeMap<PathBuf, SyncedFile>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SyncedFile {
+    pub id: Option<RefId>,
+    /// The date ILIAS listed when the file was downloaded. Unknown for files taken over from
+    /// other tools, whose local copy is trusted until ILIAS lists a change.
+    pub date: Option<DateTime<Local>>,
+    /// In bytes
+    pub size: Option<u64>,
+    /// Hex encoded SHA-256 of the content
+    pub sha256: Option<String>,
+}
+
+/// Outcome of a sync run, e.g. to serialize as JSON for monitoring or a summary notification
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncReport {
+    pub started: DateTime<Local>,
+    pub duration: Duration,
+    pub added: Vec<SyncChange>,
+    pub updated: Vec<SyncChange>,
+    pub unchanged: usize,
+    /// Files that are not listed anymore. Their local copies are kept.
+    pub removed: Vec<PathBuf>,
+    pub warnings: Vec<SyncIssue>,
+    pub errors: Vec<SyncIssue>,
+    /// Bytes downloaded in total
+    pub transferred: u64,
+}
+
+/// A file that was downloaded during a sync
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncChange {
+    pub path: PathBuf,
+    /// In bytes
+    pub size: u64,
+    pub duration: Duration,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SyncIssue {
+    /// Relative to the target directory, if the issue concerns a single file or container
+    pub path: Option<PathBuf>,
+    pub message: String,
+}
+
+/// The target directory of a sync and, with the `encryption` feature, how it is encrypted
+#[derive(Debug, Clone, Copy)]
+struct Storage<'a> {
+    directory: &'a Path,
+    #[cfg(feature = "encryption")]
+    encryption: Option<&'a Encryption>,
+}
+
+impl SyncState {
+    pub const FILE_NAME: &str = ".ilias-sync.json";
+
+    /// Replaces [`SyncState::FILE_NAME`] when the sync is encrypted
+    #[cfg(feature = "encryption")]
+    pub const ENCRYPTED_FILE_NAME: &str = ".ilias-sync.json.age";
+
+    /// Reads the state of `directory`, which is empty if it was never synced
+    pub fn load(directory: &Path) -> Result<SyncState, Whatever> {
+        Self::read(Storage::plain(directory))
+    }
+
+    pub fn store(&self, directory: &Path) -> Result<(), Whatever> {
+        self.write(Storage::plain(directory))
+    }
+
+    /// Reads the state of an encrypted sync, see [`sync_encrypted`]
+    #[cfg(feature = "encryption")]
+    pub fn load_encrypted(
+        directory: &Path,
+        encryption: &Encryption,
+    ) -> Result<SyncState, Whatever> {
+        Self::read(Storage {
+            directory,
+            encryption: Some(encryption),
+        })
+    }
+
+    #[cfg(feature = "encryption")]
+    pub fn store_encrypted(
+        &self,
+        directory: &Path,
+        encryption: &Encryption,
+    ) -> Result<(), Whatever> {
+        self.write(Storage {
+            directory,
+            encryption: Some(encryption),
+        })
+    }
+
+    fn read(storage: Storage) -> Result<SyncState, Whatever> {
+        let state = match fs::read(storage.state_path()) {
+            Ok(state) => state,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(SyncState::default()),
+            Err(error) => return Err(error).whatever_context("Could not read sync state"),
+        };
+        let state = storage.decrypt(state)?;
+        serde_json::from_slice(&state).whatever_context("Could not parse sync state")
+    }
+
+    fn write(&self, storage: Storage) -> Result<(), Whatever> {
+        let state =
+            serde_json::to_vec_pretty(self).whatever_context("Could not serialize sync state")?;
+        let state = storage.encrypt(state)?;
+        write_private(&storage.state_path(), &state).whatever_context("Could not write sync state")
+    }
+
+    /// The entry of a listed file, found by its id or else by its path
+    fn find(&self, path: &Path, file: &File) -> Option<(&PathBuf, &SyncedFile)> {
+        if let Some(id) = file.id
+            && let Some(entry) = self.files.iter().find(|(_, synced)| synced.id == Some(id))
+        {
+            return Some(entry);
+        }
+        self.files
+            .get_key_value(path)
+            // Names without an extension get one when downloaded
+            .or_else(|| {
+                self.files
+                    .iter()
+                    .find(|(synced_path, _)| synced_path.with_extension("") == path)
+            })
+    }
+}
+
+impl<'a> Storage<'a> {
+    fn plain(directory: &'a Path) -> Storage<'a> {
+        Storage {
+            directory,
+            #[cfg(feature = "encryption")]
+            encryption: None,
+        }
+    }
+
+    fn state_path(&self) -> PathBuf {
+        #[cfg(feature = "encryption")]
+        if self.encryption.is_some() {
+            return self.directory.join(SyncState::ENCRYPTED_FILE_NAME);
+        }
+        self.directory.join(SyncState::FILE_NAME)
+    }
+
+    fn encrypt(&self, state: Vec<u8>) -> Result<Vec<u8>, Whatever> {
+        #[cfg(feature = "encryption")]
+        if let Some(encryption) = self.encryption {
+            return encryption.encrypt(&state);
+        }
+        Ok(state)
+    }
+
+    fn decrypt(&self, state: Vec<u8>) -> Result<Vec<u8>, Whatever> {
+        #[cfg(feature = "encryption")]
+        if let Some(encryption) = self.encryption {
+            return encryption.decrypt(&state);
+        }
+        Ok(state)
+    }
+
+    /// Runs `download_into` on `target`, or on a staging directory that is encrypted into
+    /// `target` if the sync is encrypted
+    fn download(
+        &self,
+        target: &Path,
+        download_into: impl FnOnce(&Path) -> Result<VerifiedDownload, Whatever>,
+    ) -> Result<VerifiedDownload, Whatever> {
+        #[cfg(feature = "encryption")]
+        if let Some(encryption) = self.encryption {
+            let download = encryption.encrypt_into(target, download_into)?;
+            let name = download.path.file_name().unwrap_or_default();
+            return Ok(VerifiedDownload {
+                path: Encryption::encrypted_path(&target.join(name)),
+                ..download
+            });
+        }
+        download_into(target)
+    }
+}
+
+impl SyncReport {
+    fn issue(issues: &mut Vec<SyncIssue>, path: Option<&Path>, message: String) {
+        warn!("{message}");
+        issues.push(SyncIssue {
+            path: path.map(Path::to_path_buf),
+            message,
+        });
+    }
+}
+
+/// Downloads the files in `container` and everything below it into `directory`, laid out like
+/// the containers on ILIAS.
+///
+/// Files are only downloaded if they are new or ILIAS lists a newer date than at the last sync.
+/// What was synced is kept in [`SyncState::FILE_NAME`] in `directory`, which is locked while the
+/// sync runs. Errors of single files or containers do not stop the sync but are collected in the
+/// report.
+pub fn sync<C: IliasContainer>(
+    ilias_client: &IliasClient,
+    container: &C,
+    directory: &Path,
+) -> Result<SyncReport, Whatever> {
+    sync_with(ilias_client, container, Storage::plain(directory))
+}
+
+/// Syncs like [`sync`], but keeps the files and the state in `directory` encrypted. Downloads
+/// are staged outside of `directory`, so no plaintext ever lands there.
+#[cfg(feature = "encryption")]
+pub fn sync_encrypted<C: IliasContainer>(
+    ilias_client: &IliasClient,
+    container: &C,
+    directory: &Path,
+    encryption: &Encryption,
+) -> Result<SyncReport, Whatever> {
+    sync_with(
+        ilias_client,
+        container,
+        Storage {
+            directory,
+            encryption: Some(encryption),
+        },
+    )
+}
+
+fn sync_with<C: IliasContainer>(
+    ilias_client: &IliasClient,
+    container: &C,
+    storage: Storage,
+) -> Result<SyncReport, Whatever> {
+    let directory = storage.directory;
+    let lock = DirectoryLock::acquire(directory, LOCK_STALE_AFTER)?;
+    let mut state = SyncState::read(storage)?;
+
+    let start = Instant::now();
+    let mut report = SyncReport {
+        started: Local::now(),
+        duration: Duration::ZERO,
+        added: vec![],
+        updated: vec![],
+        unchanged: 0,
+        removed: vec![],
+        warnings: vec![],
+        errors: vec![],
+        transferred: 0,
+    };
+
+    let elements = container
+        .children(ilias_client)
+        .whatever_context("Could not list container to sync")?;
+    let mut listed = BTreeMap::new();
+    for entry in FolderWalk::from_elements(
+        ilias_client,
+        elements.into_iter().map(|element| (None, element)),
+    ) {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(error) => {
+                SyncReport::issue(
+                    &mut report.errors,
+                    None,
+                    Report::from_error(error).to_string(),
+                );
+                continue;
+            }
+        };
+        let FolderElement::File { file, .. } = entry.element else {
+            continue;
+        };
+        let parent = entry
+            .parent_path
+            .iter()
+            .map(|name| file_name_component(name))
+            .collect::<PathBuf>();
+        let path = parent.join(file_name_component(&file.name));
+        if file.download_querypath.is_none() {
+            SyncReport::issue(
+                &mut report.warnings,
+                Some(&path),
+                format!("{} can not be downloaded", file.name),
+            );
+            continue;
+        }
+
+        let known = state
+            .find(&path, &file)
+            .map(|(known_path, synced)| (known_path.clone(), synced.clone()));
+        // Without a date on either side there is nothing that could show a change
+        let is_current = |synced: &SyncedFile| match (synced.date, file.date) {
+            (Some(synced), Some(listed)) => synced >= listed,
+            _ => true,
+        };
+        if let Some((known_path, synced)) = &known
+            && directory.join(known_path).exists()
+            && is_current(synced)
+        {
+            debug!("{} is up to date", known_path.display());
+            report.unchanged += 1;
+            // Files taken over from other tools learn their id and date from the listing
+            let synced = SyncedFile {
+                id: file.id.or(synced.id),
+                date: synced.date.or(file.date),
+                ..synced.clone()
+            };
+            listed.insert(known_path.clone(), synced);
+            continue;
+        }
+
+        let download_start = Instant::now();
+        let target = directory.join(&parent);
+        let download_into = |to: &Path| {
+            file.download_verified(ilias_client, to)
+                .whatever_context(format!("Could not sync {}", path.display()))
+        };
+        let download: Result<_, Whatever> = fs::create_dir_all(&target)
+            .whatever_context("Could not create directory")
+            .and_then(|()| storage.download(&target, download_into));
+        let download = match download {
+            Ok(download) => download,
+            Err(error) => {
+                SyncReport::issue(
+                    &mut report.errors,
+                    Some(&path),
+                    Report::from_error(error).to_string(),
+                );
+                // Keep what was known about the file, so the next sync retries it
+                if let Some((known_path, synced)) = known {
+                    listed.insert(known_path, synced);
+                }
+                continue;
+            }
+        };
+        if let Err(error) = file.restore_date(&download.path) {
+            SyncReport::issue(
+                &mut report.warnings,
+                Some(&path),
+                Report::from_error(error).to_string(),
+            );
+        }
+        let local_path = download
+            .path
+            .strip_prefix(directory)
+            .unwrap_or(&download.path)
+            .to_path_buf();
+        info!("Synced {}", local_path.display());
+
+        report.transferred += download.size;
+        let change = SyncChange {
+            path: local_path.clone(),
+            size: download.size,
+            duration: download_start.elapsed(),
+        };
+        if known.is_some() {
+            report.updated.push(change);
+        } else {
+            report.added.push(change);
+        }
+        listed.insert(
+            local_path,
+            SyncedFile {
+                id: file.id,
+                date: file.date,
+                size: Some(download.size),
+                sha256: Some(download.sha256),
+            },
+        );
+        if let Err(error) = lock.refresh() {
+            warn!("{}", Report::from_error(error));
+        }
+    }
+
+    let unlisted = state
+        .files
+        .into_iter()
+        .filter(|(path, _)| !listed.contains_key(path))
+        .collect::<Vec<_>>();
+    if report.errors.is_empty() {
+        report.removed = unlisted.into_iter().map(|(path, _)| path).collect();
+        state.files = listed;
+    } else {
+        // Files may only look removed because their container could not be listed
+        state.files = unlisted.into_iter().chain(listed).collect();
+    }
+    state.write(storage)?;
+
+    report.duration = start.elapsed();
+    info!(
+        "Synced {} new and {} updated files into {} in {:?}",
+        report.added.len(),
+        report.updated.len(),
+        directory.display(),
+        report.duration
+    );
+    if !report.errors.is_empty() {
+        warn!(
+            "Sync of {} had {} errors",
+            directory.display(),
+            report.errors.len()
+        );
+    }
+    Ok(report)
+}
@@ -0,0 +1,80 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use log::{debug, info};
+use serde::Deserialize;
+use snafu::{ResultExt, Whatever};
+
+use crate::local_file::files_below;
+
+use super::{SyncState, SyncedFile};
+
+/// The part of the report PFERD keeps in its output directory that lists what it downloaded
+#[derive(Debug, Deserialize)]
+struct PferdReport {
+    known_files: Vec<PathBuf>,
+}
+
+impl SyncState {
+    /// Takes over the files a PFERD crawl put into `course`, which is relative to the `directory`
+    /// PFERD keeps its `.report` in. The state is the one of `directory.join(course)`.
+    ///
+    /// The files are not downloaded again unless ILIAS lists them with a change later. Store the
+    /// state before the first sync.
+    pub fn import_pferd(directory: &Path, course: &Path) -> Result<SyncState, Whatever> {
+        let report =
+            fs::read(directory.join(".report")).whatever_context("Could not read PFERD report")?;
+        let report: PferdReport =
+            serde_json::from_slice(&report).whatever_context("Could not parse PFERD report")?;
+
+        let mut state = SyncState::default();
+        for path in report.known_files {
+            let Ok(relative) = path.strip_prefix(course) else {
+                continue;
+            };
+            match fs::metadata(directory.join(&path)) {
+                Ok(metadata) if metadata.is_file() => {
+                    state
+                        .files
+                        .insert(relative.to_path_buf(), imported(metadata.len()));
+                }
+                _ => debug!("{} is gone, not taking it over", path.display()),
+            }
+        }
+        info!("Took over {} files from PFERD", state.files.len());
+        Ok(state)
+    }
+
+    /// Takes over every file below `directory`. KIT-ILIAS-downloader keeps no state of its own
+    /// and lays out courses like the sync does, so the files themselves are all there is.
+    pub fn import_kit_ilias_downloader(directory: &Path) -> Result<SyncState, Whatever> {
+        let mut state = SyncState::default();
+        for path in files_below(directory)? {
+            // Hidden files are state and lockfiles, of this crate or others
+            if path
+                .components()
+                .any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+            {
+                continue;
+            }
+            let metadata = fs::metadata(directory.join(&path))
+                .whatever_context(format!("Could not read metadata of {}", path.display()))?;
+            state.files.insert(path, imported(metadata.len()));
+        }
+        info!(
+            "Took over {} files from KIT-ILIAS-downloader",
+            state.files.len()
+        );
+        Ok(state)
+    }
+}
+
+/// A file another tool downloaded. Without a date it counts as current until ILIAS lists one.
+fn imported(size: u64) -> SyncedFile {
+    SyncedFile {
+        size: Some(size),
+        ..SyncedFile::default()
+    }
+}
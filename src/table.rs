@@ -0,0 +1,57 @@
+use std::sync::OnceLock;
+
+use scraper::{ElementRef, Selector};
+
+/// A html table whose cells can be looked up by their (localized) column header
+#[derive(Debug)]
+pub(crate) struct Table<'a> {
+    headers: Vec<String>,
+    pub rows: Vec<Vec<ElementRef<'a>>>,
+}
+
+static HEADER_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static ROW_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static CELL_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl<'a> Table<'a> {
+    pub fn parse(table: ElementRef<'a>) -> Table<'a> {
+        let header_selector = HEADER_SELECTOR
+            .get_or_init(|| Selector::parse("thead th").expect("Could not parse selector"));
+        let row_selector = ROW_SELECTOR
+            .get_or_init(|| Selector::parse("tbody tr").expect("Could not parse selector"));
+        let cell_selector =
+            CELL_SELECTOR.get_or_init(|| Selector::parse("td").expect("Could not parse selector"));
+
+        let headers = table
+            .select(header_selector)
+            .map(|header| header.text().collect::<String>().trim().to_string())
+            .collect();
+        let rows = table
+            .select(row_selector)
+            .map(|row| row.select(cell_selector).collect::<Vec<_>>())
+            // Empty tables contain a single row with a "no entries" message
+            .filter(|cells| cells.len() > 1)
+            .collect();
+
+        Table { headers, rows }
+    }
+
+    pub fn headers(&self) -> &[String] {
+        &self.headers
+    }
+
+    pub fn column(&self, names: &[&str]) -> Option<usize> {
+        self.headers
+            .iter()
+            .position(|header| names.iter().any(|name| header.starts_with(name)))
+    }
+
+    pub fn cell(&self, row: &[ElementRef<'a>], names: &[&str]) -> Option<ElementRef<'a>> {
+        self.column(names).and_then(|index| row.get(index).copied())
+    }
+
+    pub fn cell_text(&self, row: &[ElementRef<'a>], names: &[&str]) -> Option<String> {
+        self.cell(row, names)
+            .map(|cell| cell.text().collect::<String>().trim().to_string())
+    }
+}
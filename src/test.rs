@@ -0,0 +1,328 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local};
+use log::debug;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{
+    IliasElement,
+    client::IliasClient,
+    id::RefId,
+    info_screen::InfoScreen,
+    overview::{Deadline, DeadlineKind},
+    parse_date, parse_number,
+    reference::Reference,
+    table::Table,
+};
+
+/// An ILIAS test (`tst`)
+#[derive(Debug, Serialize, Deserialize)]
+#[allow(dead_code)]
+pub struct Test {
+    pub name: String,
+    pub description: String,
+    pub querypath: Option<String>,
+    pub available_from: Option<DateTime<Local>>,
+    pub available_until: Option<DateTime<Local>>,
+    results: Reference<TestResults>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestResults {
+    pub passed: Option<bool>,
+    pub mark: Option<String>,
+    pub attempts: Vec<TestAttempt>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestAttempt {
+    pub number: u32,
+    pub date: Option<DateTime<Local>>,
+    pub reached_points: Option<f64>,
+    pub max_points: Option<f64>,
+    pub percentage: Option<f64>,
+    /// Whether the mark of this attempt passes the test
+    pub passed: Option<bool>,
+    details: Reference<TestAttemptDetails>,
+}
+
+/// The per-question breakdown of a single attempt
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TestAttemptDetails {
+    pub questions: Vec<QuestionResult>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuestionResult {
+    pub title: String,
+    pub reached_points: Option<f64>,
+    pub max_points: Option<f64>,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PERMALINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static RESULTS_TAB_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for Test {
+    fn type_identifier() -> Option<&'static str> {
+        Some("tst")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let permalink_selector = PERMALINK_SELECTOR.get_or_init(|| {
+            Selector::parse("#current_perma_link").expect("Could not parse selector")
+        });
+        let results_tab_selector = RESULTS_TAB_SELECTOR.get_or_init(|| {
+            Selector::parse("#tab_results a, #tab_myresults a").expect("Could not parse selector")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+        let querypath = element
+            .select(permalink_selector)
+            .next()
+            .and_then(|permalink| permalink.attr("value"))
+            .map(str::to_string);
+        let results_querypath = element
+            .select(results_tab_selector)
+            .next()
+            .and_then(|link| link.attr("href"))
+            .map(str::to_string);
+
+        // The introduction page lists the access restrictions like an info screen
+        let info = InfoScreen::parse(element, ilias_client)?;
+        let date_of = |names: &[&str]| {
+            info.property(names)
+                .and_then(|property| parse_date(&property.value).ok())
+        };
+        let available_from = date_of(&["Startzeit", "Beginn", "Starting Time", "Start"]);
+        let available_until = date_of(&["Endzeit", "Ende", "Ending Time", "End"]);
+
+        Ok(Test {
+            name,
+            description,
+            querypath,
+            available_from,
+            available_until,
+            results: Reference::from_optional_querypath(results_querypath),
+        })
+    }
+}
+
+impl Test {
+    /// The end of the access period if it has not passed yet
+    pub fn deadline(&self) -> Option<Deadline> {
+        let date = self.available_until.filter(|date| *date >= Local::now())?;
+        Some(Deadline {
+            kind: DeadlineKind::Test,
+            title: self.name.clone(),
+            date,
+            source: self.name.clone(),
+            querypath: self.querypath.clone(),
+            state: None,
+        })
+    }
+
+    pub fn get_results(
+        &mut self,
+        ilias_client: &IliasClient,
+    ) -> Result<Option<&mut TestResults>, Whatever> {
+        self.results
+            .resolve_cached_mut(ilias_client)
+            .whatever_context("Could not parse test results")
+    }
+}
+
+static RESULT_TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static MESSAGE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for TestResults {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let result_table_selector = RESULT_TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+        let message_selector = MESSAGE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer .alert").expect("Could not parse selector")
+        });
+        let link_selector = LINK_SELECTOR
+            .get_or_init(|| Selector::parse("a[href]").expect("Could not parse selector"));
+
+        let message = element
+            .select(message_selector)
+            .map(|message| message.text().collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let passed = parse_passed(&message);
+
+        let mut attempts = vec![];
+        let mut mark = None;
+        for table in element.select(result_table_selector) {
+            let table = Table::parse(table);
+            if table.column(&["Durchlauf", "Pass", "Attempt"]).is_none() {
+                continue;
+            }
+
+            for row in &table.rows {
+                let Some(number) = table
+                    .cell_text(row, &["Durchlauf", "Pass", "Attempt"])
+                    .and_then(|number| number.parse().ok())
+                else {
+                    continue;
+                };
+                let (reached_points, max_points) = table
+                    .cell_text(
+                        row,
+                        &["Erreichte Punkte", "Reached Points", "Punkte", "Points"],
+                    )
+                    .map(|points| parse_points(&points))
+                    .unwrap_or_default();
+                let details_querypath = row
+                    .iter()
+                    .flat_map(|cell| cell.select(link_selector))
+                    .filter_map(|link| link.attr("href"))
+                    .find(|querypath| querypath.contains("PassDetails"))
+                    .map(str::to_string);
+                let attempt_mark = table.cell_text(row, &["Note", "Mark"]);
+                let attempt_passed = attempt_mark.as_deref().and_then(parse_passed);
+                if attempt_mark.is_some() {
+                    mark = attempt_mark;
+                }
+
+                attempts.push(TestAttempt {
+                    number,
+                    date: table
+                        .cell_text(row, &["Datum", "Date"])
+                        .and_then(|date| parse_date(&date).ok()),
+                    reached_points,
+                    max_points,
+                    percentage: table
+                        .cell_text(row, &["Prozent", "Percent"])
+                        .and_then(|percentage| parse_number(&percentage)),
+                    passed: attempt_passed,
+                    details: Reference::from_optional_querypath(details_querypath),
+                });
+            }
+        }
+        debug!("Test attempts: {attempts:?}");
+
+        Ok(TestResults {
+            passed,
+            mark,
+            attempts,
+        })
+    }
+}
+
+impl TestAttempt {
+    pub fn get_details(
+        &mut self,
+        ilias_client: &IliasClient,
+    ) -> Result<Option<&TestAttemptDetails>, Whatever> {
+        self.details
+            .resolve_cached(ilias_client)
+            .whatever_context("Could not parse attempt details")
+    }
+}
+
+static DETAIL_TABLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for TestAttemptDetails {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    fn querypath_from_id(_id: RefId) -> Option<String> {
+        None
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let detail_table_selector = DETAIL_TABLE_SELECTOR.get_or_init(|| {
+            Selector::parse("#ilContentContainer table").expect("Could not parse selector")
+        });
+
+        let mut questions = vec![];
+        for table in element.select(detail_table_selector) {
+            let table = Table::parse(table);
+            for row in &table.rows {
+                let Some(title) =
+                    table.cell_text(row, &["Fragetitel", "Question Title", "Titel", "Title"])
+                else {
+                    continue;
+                };
+                questions.push(QuestionResult {
+                    title,
+                    reached_points: table
+                        .cell_text(row, &["Erreichte Punkte", "Reached Points"])
+                        .and_then(|points| parse_number(&points)),
+                    max_points: table
+                        .cell_text(row, &["Max. Punkte", "Maximum Points", "Max. Points"])
+                        .and_then(|points| parse_number(&points)),
+                });
+            }
+        }
+
+        Ok(TestAttemptDetails { questions })
+    }
+}
+
+/// Parses "12 von 20" / "12 of 20" style point values
+fn parse_points(points: &str) -> (Option<f64>, Option<f64>) {
+    let mut parts = points
+        .split(|c: char| c.is_whitespace() || c == '/')
+        .filter_map(parse_number);
+    (parts.next(), parts.next())
+}
+
+/// Whether a message or mark like "bestanden" / "nicht bestanden" says the test is passed
+fn parse_passed(text: &str) -> Option<bool> {
+    let text = text.to_lowercase();
+    if ["nicht bestanden", "not passed", "failed"]
+        .iter()
+        .any(|keyword| text.contains(keyword))
+    {
+        Some(false)
+    } else if ["bestanden", "passed"]
+        .iter()
+        .any(|keyword| text.contains(keyword))
+    {
+        Some(true)
+    } else {
+        None
+    }
+}
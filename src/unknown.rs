@@ -0,0 +1,88 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use regex::Regex;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, Whatever};
+
+use super::{IliasElement, client::IliasClient, id::RefId, querypath_from_href};
+
+/// An object of a type this crate does not understand (yet), with what every object shows
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnknownElement {
+    pub name: String,
+    pub description: String,
+    /// The ILIAS type (e.g. `wiki`), guessed from the icon or permalink of the object
+    pub type_identifier: Option<String>,
+    /// The ref_id, if the link of the object contains it
+    pub id: Option<RefId>,
+    pub querypath: String,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static PERMALINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+static PERMALINK_REGEX: OnceLock<Regex> = OnceLock::new();
+
+impl IliasElement for UnknownElement {
+    fn type_identifier() -> Option<&'static str> {
+        None
+    }
+
+    /// The repository opens objects of any type by their ref_id
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!("ilias.php?baseClass=ilrepositorygui&ref_id={id}"))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let permalink_selector = PERMALINK_SELECTOR.get_or_init(|| {
+            Selector::parse("#current_perma_link").expect("Could not parse selector")
+        });
+        let permalink_regex = PERMALINK_REGEX.get_or_init(|| {
+            Regex::new(r"(target=|goto\.php/)(?<type>[a-z]+)[_/](?<id>\d+)")
+                .expect("Could not parse regex")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+        let permalink = element
+            .select(permalink_selector)
+            .next()
+            .and_then(|permalink| permalink.attr("value"))
+            .whatever_context("Could not find permalink")?;
+        let captures = permalink_regex.captures(permalink);
+
+        let unknown_element = UnknownElement {
+            name,
+            description,
+            type_identifier: captures
+                .as_ref()
+                .map(|captures| captures["type"].to_string()),
+            id: captures
+                .as_ref()
+                .and_then(|captures| captures["id"].parse().ok()),
+            querypath: querypath_from_href(permalink),
+        };
+        debug!("Unknown element: {:?}", unknown_element);
+
+        Ok(unknown_element)
+    }
+}
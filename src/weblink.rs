@@ -0,0 +1,127 @@
+use std::sync::OnceLock;
+
+use log::debug;
+use reqwest::Url;
+use scraper::{ElementRef, Selector};
+use serde::{Deserialize, Serialize};
+use snafu::{OptionExt, ResultExt, Whatever};
+
+use super::{IliasElement, client::IliasClient, id::RefId, querypath_from_href};
+
+/// An ILIAS weblink (`webr`), either a single link or a list of links
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Weblink {
+    pub name: String,
+    pub description: String,
+    pub links: Vec<WeblinkEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeblinkEntry {
+    pub title: String,
+    pub description: String,
+    /// The link as found in the page, usually pointing to the `ilLinkResourceHandler`
+    pub href: String,
+}
+
+static NAME_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static DESCRIPTION_SELECTOR: OnceLock<Selector> = OnceLock::new();
+static LINK_SELECTOR: OnceLock<Selector> = OnceLock::new();
+
+impl IliasElement for Weblink {
+    fn type_identifier() -> Option<&'static str> {
+        Some("webr")
+    }
+
+    fn querypath_from_id(id: RefId) -> Option<String> {
+        Some(format!(
+            "goto.php/{}/{}",
+            Self::type_identifier().unwrap(),
+            id
+        ))
+    }
+
+    fn parse(element: ElementRef, _ilias_client: &IliasClient) -> Result<Self, Whatever> {
+        let name_selector = NAME_SELECTOR.get_or_init(|| {
+            Selector::parse(".il-page-content-header").expect("Could not parse selector")
+        });
+        let description_selector = DESCRIPTION_SELECTOR
+            .get_or_init(|| Selector::parse(".ilHeaderDesc").expect("Could not parse selector"));
+        let link_selector = LINK_SELECTOR.get_or_init(|| {
+            Selector::parse(
+                r#"#ilContentContainer a[href*="link_id="], #ilContentContainer a[href*="illinkresourcehandlergui"]"#,
+            )
+            .expect("Could not parse selector")
+        });
+
+        let name = element
+            .select(name_selector)
+            .next()
+            .whatever_context("Could not find name")?
+            .text()
+            .collect::<String>()
+            .trim()
+            .to_string();
+        let description = element
+            .select(description_selector)
+            .next()
+            .map(|description| description.text().collect())
+            .unwrap_or_default();
+
+        let mut links: Vec<WeblinkEntry> = vec![];
+        for link in element.select(link_selector) {
+            let href = link.attr("href").unwrap_or_default().to_string();
+            if links.iter().any(|known| known.href == href) {
+                continue;
+            }
+            let title = link.text().collect::<String>().trim().to_string();
+            // The description is the rest of the row or list item the link is in
+            let entry_description = link
+                .ancestors()
+                .filter_map(ElementRef::wrap)
+                .find(|ancestor| matches!(ancestor.value().name(), "tr" | "li"))
+                .map(|entry| {
+                    entry
+                        .text()
+                        .collect::<String>()
+                        .replacen(&title, "", 1)
+                        .trim()
+                        .to_string()
+                })
+                .unwrap_or_default();
+
+            links.push(WeblinkEntry {
+                title,
+                description: entry_description,
+                href,
+            });
+        }
+
+        let weblink = Weblink {
+            name,
+            description,
+            links,
+        };
+        debug!("Weblink: {:?}", weblink);
+
+        Ok(weblink)
+    }
+}
+
+impl WeblinkEntry {
+    /// The external url the link leads to, following the redirect of ILIAS
+    pub fn resolve_url(&self, ilias_client: &IliasClient) -> Result<Url, Whatever> {
+        if let Ok(url) = Url::parse(&self.href)
+            && !url.as_str().contains("link_id=")
+            && !url
+                .as_str()
+                .to_lowercase()
+                .contains("illinkresourcehandlergui")
+        {
+            return Ok(url);
+        }
+        ilias_client
+            .resolve_redirect(&querypath_from_href(&self.href))
+            .whatever_context(format!("Could not resolve link {}", self.title))
+    }
+}
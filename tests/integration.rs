@@ -0,0 +1,66 @@
+//! Runs write flows against a local ILIAS, see `ilias::integration::TestInstance`.
+//!
+//! `cargo test --features integration -- --test-threads 1`
+#![cfg(feature = "integration")]
+
+use std::{env, fs};
+
+use ilias::{integration::TestInstance, local_file::NamedLocalFile};
+
+#[test]
+fn login() {
+    let instance = TestInstance::from_env().unwrap();
+    instance.login().unwrap();
+}
+
+#[test]
+fn upload_and_delete_file() {
+    let instance = TestInstance::from_env().unwrap();
+    let mut client = instance.login().unwrap();
+    client.set_allow_deletion(true);
+
+    let name = format!("integration-{}.txt", std::process::id());
+    let path = env::temp_dir().join(&name);
+    fs::write(&path, "uploaded by the integration tests").unwrap();
+    instance
+        .folder(&client)
+        .unwrap()
+        .upload_files(
+            &client,
+            &[NamedLocalFile {
+                name: name.clone(),
+                path,
+            }],
+        )
+        .unwrap();
+
+    let folder = instance.folder(&client).unwrap();
+    let element = folder
+        .elements
+        .iter()
+        .find(|element| {
+            element
+                .file()
+                .is_some_and(|file| name.starts_with(&file.name))
+        })
+        .expect("Uploaded file is not listed");
+    element.delete(&client).unwrap();
+
+    let folder = instance.folder(&client).unwrap();
+    assert!(!folder.elements.iter().any(|element| {
+        element
+            .file()
+            .is_some_and(|file| name.starts_with(&file.name))
+    }));
+}
+
+#[test]
+fn join_group() {
+    let instance = TestInstance::from_env().unwrap();
+    let client = instance.login().unwrap();
+
+    let mut group = instance.group(&client).unwrap();
+    assert!(group.can_join(), "Seeded group should be joinable");
+    group.join(&client).unwrap();
+    assert!(!instance.group(&client).unwrap().can_join());
+}